@@ -0,0 +1,40 @@
+#! /usr/bin/bash
+# Generate the kernel symbol table (.ksymtab) from a linked kernel image.
+#
+# usage: gen-ksymtab.sh <nm> <kernel.bin> > ksymtab.s
+#
+# Layout, see src/kernel/ksym.rs:
+#   u32 count
+#   count * { u32 addr, u32 size, u32 name_offset }  sorted by address
+#   NUL-terminated names
+
+NM=$1
+KERNEL=$2
+
+"$NM" -n -S -C --defined-only "$KERNEL" | awk '
+    BEGIN { n = 0 }
+
+    # with size:    addr size type name...
+    # without size: addr type name...
+    $3 ~ /^[tTwW]$/ { addr = $1; size = $2; name = $0; sub(/^[^ ]+ [^ ]+ [^ ]+ /, "", name); emit() }
+    $2 ~ /^[tTwW]$/ { addr = $1; size = 0;  name = $0; sub(/^[^ ]+ [^ ]+ /, "", name); emit() }
+
+    function emit() {
+        # symbols still at the physical load address (boot code) are skipped
+        if (addr "" < "c0000000") return
+        gsub(/\\/, "\\\\", name)
+        gsub(/"/, "\\\"", name)
+        addrs[n] = addr; sizes[n] = size; names[n] = name; n++
+    }
+
+    END {
+        print "    .section .ksymtab, \"a\""
+        print "    .align 4"
+        print "    .long " n
+        for (i = 0; i < n; i++)
+            printf "    .long 0x%s, 0x%s, .Lksym_name%d - .Lksym_names\n", addrs[i], sizes[i], i
+        print ".Lksym_names:"
+        for (i = 0; i < n; i++)
+            printf ".Lksym_name%d: .asciz \"%s\"\n", i, names[i]
+    }
+'
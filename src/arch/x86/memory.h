@@ -0,0 +1,7 @@
+// Define constants for the kernel memory layout (keep in sync with src/mm/mod.rs)
+#define KERNEL_VIRT_BASE 0xC0000000
+#define KERNEL_PDE_INDEX 768 /* KERNEL_VIRT_BASE >> 22 */
+#define PDE_4M_RW 0x83 /* Present | Read/Write | 4 MiB page */
+#define PDE_RW 0x03 /* Present | Read/Write */
+#define CR4_PSE 0x00000010
+#define CR0_PG 0x80000000
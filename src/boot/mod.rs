@@ -0,0 +1,6 @@
+// src/boot/mod.rs
+
+pub mod multiboot;
+
+pub use multiboot::multiboot_init;
+pub use multiboot::boot_info;
@@ -0,0 +1,542 @@
+// src/boot/multiboot.rs
+use core::fmt;
+use core::ptr;
+use crate::mm;
+
+/// 引導程式在 EAX 中傳入的魔數
+pub const MULTIBOOT_BOOTLOADER_MAGIC: u32 = 0x2BADB002;
+
+// Multiboot 資訊結構的 flags 位元
+#[allow(dead_code)]
+pub const MULTIBOOT_INFO_MEMORY: u32 = 1 << 0;
+#[allow(dead_code)]
+pub const MULTIBOOT_INFO_BOOTDEV: u32 = 1 << 1;
+#[allow(dead_code)]
+pub const MULTIBOOT_INFO_CMDLINE: u32 = 1 << 2;
+#[allow(dead_code)]
+pub const MULTIBOOT_INFO_MODS: u32 = 1 << 3;
+#[allow(dead_code)]
+pub const MULTIBOOT_INFO_AOUT_SYMS: u32 = 1 << 4;
+#[allow(dead_code)]
+pub const MULTIBOOT_INFO_ELF_SHDR: u32 = 1 << 5;
+#[allow(dead_code)]
+pub const MULTIBOOT_INFO_MEM_MAP: u32 = 1 << 6;
+#[allow(dead_code)]
+pub const MULTIBOOT_INFO_DRIVE_INFO: u32 = 1 << 7;
+#[allow(dead_code)]
+pub const MULTIBOOT_INFO_CONFIG_TABLE: u32 = 1 << 8;
+#[allow(dead_code)]
+pub const MULTIBOOT_INFO_BOOT_LOADER_NAME: u32 = 1 << 9;
+#[allow(dead_code)]
+pub const MULTIBOOT_INFO_APM_TABLE: u32 = 1 << 10;
+#[allow(dead_code)]
+pub const MULTIBOOT_INFO_VBE_INFO: u32 = 1 << 11;
+#[allow(dead_code)]
+pub const MULTIBOOT_INFO_FRAMEBUFFER_INFO: u32 = 1 << 12;
+
+/// 命令列最大長度（含模組命令列）
+pub const MULTIBOOT_CMDLINE_MAX: usize = 256;
+/// 引導程式名稱最大長度
+pub const MULTIBOOT_NAME_MAX: usize = 64;
+/// 記憶體映射最大條目數
+pub const MULTIBOOT_MMAP_MAX: usize = 64;
+/// 模組最大數量
+pub const MULTIBOOT_MODS_MAX: usize = 16;
+/// 模組命令列最大長度
+pub const MULTIBOOT_MOD_CMDLINE_MAX: usize = 64;
+
+/// 引導程式傳入的原始資訊結構
+#[repr(C, packed)]
+#[derive(Clone, Copy)]
+struct RawInfo {
+    flags: u32,
+    mem_lower: u32,
+    mem_upper: u32,
+    boot_device: u32,
+    cmdline: u32,
+    mods_count: u32,
+    mods_addr: u32,
+    syms: [u32; 4],
+    mmap_length: u32,
+    mmap_addr: u32,
+    drives_length: u32,
+    drives_addr: u32,
+    config_table: u32,
+    boot_loader_name: u32,
+    apm_table: u32,
+    vbe_control_info: u32,
+    vbe_mode_info: u32,
+    vbe_mode: u16,
+    vbe_interface_seg: u16,
+    vbe_interface_off: u16,
+    vbe_interface_len: u16,
+    framebuffer_addr: u64,
+    framebuffer_pitch: u32,
+    framebuffer_width: u32,
+    framebuffer_height: u32,
+    framebuffer_bpp: u8,
+    framebuffer_type: u8,
+    color_info: [u8; 6],
+}
+
+/// 原始記憶體映射條目（`size` 不包含自身）
+#[repr(C, packed)]
+#[derive(Clone, Copy)]
+struct RawMmapEntry {
+    size: u32,
+    base_addr: u64,
+    length: u64,
+    kind: u32,
+}
+
+/// 原始模組條目
+#[repr(C, packed)]
+#[derive(Clone, Copy)]
+struct RawModule {
+    mod_start: u32,
+    mod_end: u32,
+    string: u32,
+    reserved: u32,
+}
+
+/// Multiboot 解析錯誤
+#[allow(dead_code)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MultibootError {
+    /// EAX 中的魔數不正確
+    InvalidMagic(u32),
+    /// 資訊結構指標為空
+    NullInfo,
+}
+
+impl fmt::Display for MultibootError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MultibootError::InvalidMagic(magic) => write!(f, "invalid multiboot magic 0x{:x}", magic),
+            MultibootError::NullInfo => write!(f, "multiboot info pointer is null"),
+        }
+    }
+}
+
+/// 固定長度的字串緩衝區
+#[derive(Clone, Copy)]
+pub struct BootString<const N: usize> {
+    buf: [u8; N],
+    len: usize,
+}
+
+impl<const N: usize> BootString<N> {
+    const fn empty() -> Self {
+        Self { buf: [0; N], len: 0 }
+    }
+
+    /// 從物理地址讀取以 NUL 結尾的字串，超過 N 的部分會被截斷
+    unsafe fn from_cstr(addr: u32) -> Self {
+        let mut s = Self::empty();
+        let src = phys_to_ptr::<u8>(addr as usize);
+
+        while s.len < N {
+            let chr = *src.add(s.len);
+            if chr == 0 {
+                break;
+            }
+            s.buf[s.len] = chr;
+            s.len += 1;
+        }
+
+        s
+    }
+
+    /// 取得字串內容
+    pub fn as_str(&self) -> &str {
+        match core::str::from_utf8(&self.buf[..self.len]) {
+            Ok(s) => s,
+            // 截斷可能切開多字節字符，只保留有效的前綴
+            Err(e) => unsafe { core::str::from_utf8_unchecked(&self.buf[..e.valid_up_to()]) },
+        }
+    }
+}
+
+/// 記憶體區域類型
+#[allow(dead_code)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MemoryRegionType {
+    Available,
+    Reserved,
+    AcpiReclaimable,
+    AcpiNvs,
+    BadMemory,
+    Unknown(u32),
+}
+
+impl MemoryRegionType {
+    fn from_raw(kind: u32) -> Self {
+        match kind {
+            1 => MemoryRegionType::Available,
+            2 => MemoryRegionType::Reserved,
+            3 => MemoryRegionType::AcpiReclaimable,
+            4 => MemoryRegionType::AcpiNvs,
+            5 => MemoryRegionType::BadMemory,
+            _ => MemoryRegionType::Unknown(kind),
+        }
+    }
+}
+
+impl fmt::Display for MemoryRegionType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MemoryRegionType::Available => write!(f, "available"),
+            MemoryRegionType::Reserved => write!(f, "reserved"),
+            MemoryRegionType::AcpiReclaimable => write!(f, "ACPI reclaimable"),
+            MemoryRegionType::AcpiNvs => write!(f, "ACPI NVS"),
+            MemoryRegionType::BadMemory => write!(f, "bad memory"),
+            MemoryRegionType::Unknown(kind) => write!(f, "unknown ({})", kind),
+        }
+    }
+}
+
+/// 記憶體映射條目
+#[derive(Debug, Clone, Copy)]
+pub struct MemoryMapEntry {
+    pub base: u64,
+    pub length: u64,
+    pub kind: MemoryRegionType,
+}
+
+impl MemoryMapEntry {
+    const EMPTY: Self = Self {
+        base: 0,
+        length: 0,
+        kind: MemoryRegionType::Reserved,
+    };
+
+    /// 區域結束地址（不含）
+    #[allow(dead_code)]
+    pub fn end(&self) -> u64 {
+        self.base.saturating_add(self.length)
+    }
+}
+
+/// 由引導程式載入的模組
+#[derive(Clone, Copy)]
+pub struct BootModule {
+    pub start: u32,
+    pub end: u32,
+    cmdline: BootString<MULTIBOOT_MOD_CMDLINE_MAX>,
+}
+
+impl BootModule {
+    const EMPTY: Self = Self {
+        start: 0,
+        end: 0,
+        cmdline: BootString::empty(),
+    };
+
+    /// 模組的命令列字串
+    #[allow(dead_code)]
+    pub fn cmdline(&self) -> &str {
+        self.cmdline.as_str()
+    }
+
+    /// 模組大小（位元組）
+    #[allow(dead_code)]
+    pub fn size(&self) -> usize {
+        self.end.saturating_sub(self.start) as usize
+    }
+}
+
+/// VBE 控制器資訊
+#[allow(dead_code)]
+#[derive(Debug, Clone, Copy)]
+pub struct VbeInfo {
+    pub control_info: u32,
+    pub mode_info: u32,
+    pub mode: u16,
+    pub interface_seg: u16,
+    pub interface_off: u16,
+    pub interface_len: u16,
+}
+
+/// 幀緩衝區的像素格式
+#[allow(dead_code)]
+#[derive(Debug, Clone, Copy)]
+pub enum FramebufferKind {
+    Indexed {
+        palette_addr: u32,
+        palette_len: u16,
+    },
+    Rgb {
+        red_position: u8,
+        red_mask_size: u8,
+        green_position: u8,
+        green_mask_size: u8,
+        blue_position: u8,
+        blue_mask_size: u8,
+    },
+    EgaText,
+    Unknown(u8),
+}
+
+/// 幀緩衝區資訊
+#[allow(dead_code)]
+#[derive(Debug, Clone, Copy)]
+pub struct FramebufferInfo {
+    pub addr: u64,
+    pub pitch: u32,
+    pub width: u32,
+    pub height: u32,
+    pub bpp: u8,
+    pub kind: FramebufferKind,
+}
+
+/// 解析後的引導資訊
+pub struct BootInfo {
+    flags: u32,
+    mem_lower: u32,
+    mem_upper: u32,
+    boot_device: u32,
+    cmdline: BootString<MULTIBOOT_CMDLINE_MAX>,
+    bootloader_name: BootString<MULTIBOOT_NAME_MAX>,
+    mmap: [MemoryMapEntry; MULTIBOOT_MMAP_MAX],
+    mmap_count: usize,
+    modules: [BootModule; MULTIBOOT_MODS_MAX],
+    module_count: usize,
+    vbe: Option<VbeInfo>,
+    framebuffer: Option<FramebufferInfo>,
+}
+
+#[allow(dead_code)]
+impl BootInfo {
+    fn has(&self, flag: u32) -> bool {
+        self.flags & flag != 0
+    }
+
+    /// 原始 flags 欄位
+    pub fn flags(&self) -> u32 {
+        self.flags
+    }
+
+    /// 低端記憶體大小（KiB，起始於 0）
+    pub fn mem_lower_kb(&self) -> Option<u32> {
+        self.has(MULTIBOOT_INFO_MEMORY).then_some(self.mem_lower)
+    }
+
+    /// 高端記憶體大小（KiB，起始於 1 MiB）
+    pub fn mem_upper_kb(&self) -> Option<u32> {
+        self.has(MULTIBOOT_INFO_MEMORY).then_some(self.mem_upper)
+    }
+
+    /// BIOS 引導設備
+    pub fn boot_device(&self) -> Option<u32> {
+        self.has(MULTIBOOT_INFO_BOOTDEV).then_some(self.boot_device)
+    }
+
+    /// 內核命令列
+    pub fn cmdline(&self) -> Option<&str> {
+        self.has(MULTIBOOT_INFO_CMDLINE).then(|| self.cmdline.as_str())
+    }
+
+    /// 引導程式名稱
+    pub fn bootloader_name(&self) -> Option<&str> {
+        self.has(MULTIBOOT_INFO_BOOT_LOADER_NAME).then(|| self.bootloader_name.as_str())
+    }
+
+    /// 記憶體映射
+    pub fn memory_map(&self) -> &[MemoryMapEntry] {
+        &self.mmap[..self.mmap_count]
+    }
+
+    /// 已載入的模組
+    pub fn modules(&self) -> &[BootModule] {
+        &self.modules[..self.module_count]
+    }
+
+    /// VBE 資訊
+    pub fn vbe(&self) -> Option<&VbeInfo> {
+        self.vbe.as_ref()
+    }
+
+    /// 幀緩衝區資訊
+    pub fn framebuffer(&self) -> Option<&FramebufferInfo> {
+        self.framebuffer.as_ref()
+    }
+
+    /// 可用記憶體總量（位元組）
+    ///
+    /// 優先使用記憶體映射，否則退回 mem_lower/mem_upper，兩者皆無時返回 0
+    pub fn total_available(&self) -> u64 {
+        if self.mmap_count > 0 {
+            self.memory_map()
+                .iter()
+                .filter(|e| e.kind == MemoryRegionType::Available)
+                .map(|e| e.length)
+                .sum()
+        } else {
+            match (self.mem_lower_kb(), self.mem_upper_kb()) {
+                (Some(lower), Some(upper)) => (lower as u64 + upper as u64) * 1024,
+                _ => 0,
+            }
+        }
+    }
+}
+
+static mut BOOT_INFO: Option<BootInfo> = None;
+
+/// 將物理地址轉換為可存取的指標
+///
+/// 引導程式的資料結構位於低端記憶體，透過內核直接映射區存取
+#[inline]
+fn phys_to_ptr<T>(addr: usize) -> *const T {
+    mm::phys_to_virt(addr) as *const T
+}
+
+/// 驗證魔數並解析 Multiboot 資訊結構
+///
+/// 所有資料都會被複製到內核自己的儲存空間，之後引導程式的記憶體可以被回收
+///
+/// # 參數
+/// * `magic` - 引導時 EAX 的值
+/// * `info_addr` - 引導時 EBX 的值（資訊結構的物理地址）
+/// # 返回
+/// 成功時返回 `Ok(())`，可透過 `boot_info()` 取得結果
+pub fn multiboot_init(magic: u32, info_addr: usize) -> Result<(), MultibootError> {
+    if magic != MULTIBOOT_BOOTLOADER_MAGIC {
+        return Err(MultibootError::InvalidMagic(magic));
+    }
+
+    if info_addr == 0 {
+        return Err(MultibootError::NullInfo);
+    }
+
+    let info = unsafe { parse_info(info_addr) };
+
+    unsafe {
+        BOOT_INFO = Some(info);
+    }
+
+    Ok(())
+}
+
+/// 取得解析後的引導資訊
+///
+/// # 返回
+/// 如果 `multiboot_init` 尚未成功執行則返回 `None`
+pub fn boot_info() -> Option<&'static BootInfo> {
+    unsafe { (*ptr::addr_of!(BOOT_INFO)).as_ref() }
+}
+
+unsafe fn parse_info(info_addr: usize) -> BootInfo {
+    let raw = ptr::read_unaligned(phys_to_ptr::<RawInfo>(info_addr));
+
+    let mut info = BootInfo {
+        flags: raw.flags,
+        mem_lower: raw.mem_lower,
+        mem_upper: raw.mem_upper,
+        boot_device: raw.boot_device,
+        cmdline: BootString::empty(),
+        bootloader_name: BootString::empty(),
+        mmap: [MemoryMapEntry::EMPTY; MULTIBOOT_MMAP_MAX],
+        mmap_count: 0,
+        modules: [BootModule::EMPTY; MULTIBOOT_MODS_MAX],
+        module_count: 0,
+        vbe: None,
+        framebuffer: None,
+    };
+
+    if info.has(MULTIBOOT_INFO_CMDLINE) && raw.cmdline != 0 {
+        info.cmdline = BootString::from_cstr(raw.cmdline);
+    }
+
+    if info.has(MULTIBOOT_INFO_BOOT_LOADER_NAME) && raw.boot_loader_name != 0 {
+        info.bootloader_name = BootString::from_cstr(raw.boot_loader_name);
+    }
+
+    if info.has(MULTIBOOT_INFO_MEM_MAP) {
+        parse_mmap(&mut info, raw.mmap_addr as usize, raw.mmap_length as usize);
+    }
+
+    if info.has(MULTIBOOT_INFO_MODS) {
+        parse_modules(&mut info, raw.mods_addr as usize, raw.mods_count as usize);
+    }
+
+    if info.has(MULTIBOOT_INFO_VBE_INFO) {
+        info.vbe = Some(VbeInfo {
+            control_info: raw.vbe_control_info,
+            mode_info: raw.vbe_mode_info,
+            mode: raw.vbe_mode,
+            interface_seg: raw.vbe_interface_seg,
+            interface_off: raw.vbe_interface_off,
+            interface_len: raw.vbe_interface_len,
+        });
+    }
+
+    if info.has(MULTIBOOT_INFO_FRAMEBUFFER_INFO) {
+        let c = raw.color_info;
+        let kind = match raw.framebuffer_type {
+            0 => FramebufferKind::Indexed {
+                palette_addr: u32::from_le_bytes([c[0], c[1], c[2], c[3]]),
+                palette_len: u16::from_le_bytes([c[4], c[5]]),
+            },
+            1 => FramebufferKind::Rgb {
+                red_position: c[0],
+                red_mask_size: c[1],
+                green_position: c[2],
+                green_mask_size: c[3],
+                blue_position: c[4],
+                blue_mask_size: c[5],
+            },
+            2 => FramebufferKind::EgaText,
+            other => FramebufferKind::Unknown(other),
+        };
+
+        info.framebuffer = Some(FramebufferInfo {
+            addr: raw.framebuffer_addr,
+            pitch: raw.framebuffer_pitch,
+            width: raw.framebuffer_width,
+            height: raw.framebuffer_height,
+            bpp: raw.framebuffer_bpp,
+            kind,
+        });
+    }
+
+    info
+}
+
+unsafe fn parse_mmap(info: &mut BootInfo, addr: usize, length: usize) {
+    let mut offset = 0;
+
+    while offset < length && info.mmap_count < MULTIBOOT_MMAP_MAX {
+        let entry = ptr::read_unaligned(phys_to_ptr::<RawMmapEntry>(addr + offset));
+
+        info.mmap[info.mmap_count] = MemoryMapEntry {
+            base: entry.base_addr,
+            length: entry.length,
+            kind: MemoryRegionType::from_raw(entry.kind),
+        };
+        info.mmap_count += 1;
+
+        // size 欄位不包含自身的 4 個位元組
+        offset += entry.size as usize + 4;
+    }
+}
+
+unsafe fn parse_modules(info: &mut BootInfo, addr: usize, count: usize) {
+    let count = core::cmp::min(count, MULTIBOOT_MODS_MAX);
+    let mods = phys_to_ptr::<RawModule>(addr);
+
+    for i in 0..count {
+        let raw = ptr::read_unaligned(mods.add(i));
+
+        info.modules[i] = BootModule {
+            start: raw.mod_start,
+            end: raw.mod_end,
+            cmdline: if raw.string != 0 {
+                BootString::from_cstr(raw.string)
+            } else {
+                BootString::empty()
+            },
+        };
+    }
+
+    info.module_count = count;
+}
@@ -0,0 +1,216 @@
+// src/drivers/i8042.rs
+use core::fmt;
+use core::sync::atomic::{AtomicBool, Ordering};
+use crate::hal::io;
+
+/// 資料端口
+const I8042_DATA: u16 = 0x60;
+/// 狀態寄存器（讀）/ 命令寄存器（寫）
+const I8042_STATUS: u16 = 0x64;
+const I8042_COMMAND: u16 = 0x64;
+
+/// 狀態：輸出緩衝有資料可讀
+pub const STATUS_OUTPUT_FULL: u8 = 0x01;
+/// 狀態：輸入緩衝已滿，不能寫入
+const STATUS_INPUT_FULL: u8 = 0x02;
+/// 狀態：輸出緩衝的資料來自輔助裝置（滑鼠）
+pub const STATUS_AUX_DATA: u8 = 0x20;
+
+// 控制器命令
+const CMD_READ_CONFIG: u8 = 0x20;
+const CMD_WRITE_CONFIG: u8 = 0x60;
+const CMD_DISABLE_AUX: u8 = 0xA7;
+const CMD_ENABLE_AUX: u8 = 0xA8;
+const CMD_TEST_AUX: u8 = 0xA9;
+const CMD_SELF_TEST: u8 = 0xAA;
+const CMD_TEST_KBD: u8 = 0xAB;
+const CMD_DISABLE_KBD: u8 = 0xAD;
+const CMD_ENABLE_KBD: u8 = 0xAE;
+/// 下一個寫入資料端口的位元組送往輔助裝置
+const CMD_WRITE_AUX: u8 = 0xD4;
+
+/// 配置：鍵盤中斷 (IRQ1)
+pub const CONFIG_KBD_IRQ: u8 = 0x01;
+/// 配置：輔助裝置中斷 (IRQ12)
+pub const CONFIG_AUX_IRQ: u8 = 0x02;
+/// 配置：輔助裝置時鐘關閉
+const CONFIG_AUX_CLOCK_DISABLE: u8 = 0x20;
+/// 配置：將鍵盤的掃描碼集 2 轉換為集 1
+const CONFIG_TRANSLATION: u8 = 0x40;
+
+const SELF_TEST_OK: u8 = 0x55;
+const PORT_TEST_OK: u8 = 0x00;
+
+/// 輪詢狀態寄存器的次數上限，每次約 1µs
+const I8042_TIMEOUT: usize = 100_000;
+
+/// 8042 錯誤
+#[allow(dead_code)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum I8042Error {
+    /// 等待控制器或裝置逾時
+    Timeout,
+    /// 控制器自檢失敗，附帶返回值
+    SelfTestFailed(u8),
+    /// 鍵盤端口測試失敗，附帶返回值
+    PortTestFailed(u8),
+}
+
+impl fmt::Display for I8042Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            I8042Error::Timeout => write!(f, "controller timeout"),
+            I8042Error::SelfTestFailed(r) => write!(f, "controller self test failed (0x{:02x})", r),
+            I8042Error::PortTestFailed(r) => write!(f, "keyboard port test failed (0x{:02x})", r),
+        }
+    }
+}
+
+static I8042_HAS_AUX: AtomicBool = AtomicBool::new(false);
+
+/// 讀取狀態寄存器
+pub fn i8042_read_status() -> u8 {
+    io::io_port_rb(I8042_STATUS)
+}
+
+fn wait_input_empty() -> Result<(), I8042Error> {
+    for _ in 0..I8042_TIMEOUT {
+        if i8042_read_status() & STATUS_INPUT_FULL == 0 {
+            return Ok(());
+        }
+        io::io_delay();
+    }
+    Err(I8042Error::Timeout)
+}
+
+fn wait_output_full() -> Result<(), I8042Error> {
+    for _ in 0..I8042_TIMEOUT {
+        if i8042_read_status() & STATUS_OUTPUT_FULL != 0 {
+            return Ok(());
+        }
+        io::io_delay();
+    }
+    Err(I8042Error::Timeout)
+}
+
+/// 讀取資料端口，等待資料到達
+pub fn i8042_read_data() -> Result<u8, I8042Error> {
+    wait_output_full()?;
+    Ok(io::io_port_rb(I8042_DATA))
+}
+
+/// 讀取輔助裝置送出的資料，期間收到的鍵盤資料會被丟棄
+pub fn i8042_read_aux_data() -> Result<u8, I8042Error> {
+    for _ in 0..I8042_TIMEOUT {
+        let status = i8042_read_status();
+        if status & STATUS_OUTPUT_FULL != 0 {
+            let byte = io::io_port_rb(I8042_DATA);
+            if status & STATUS_AUX_DATA != 0 {
+                return Ok(byte);
+            }
+        }
+        io::io_delay();
+    }
+    Err(I8042Error::Timeout)
+}
+
+/// 直接讀取資料端口，不檢查狀態（中斷處理中使用）
+pub fn i8042_read_data_now() -> u8 {
+    io::io_port_rb(I8042_DATA)
+}
+
+/// 向鍵盤寫入一個位元組
+pub fn i8042_write_data(byte: u8) -> Result<(), I8042Error> {
+    wait_input_empty()?;
+    io::io_port_wb(I8042_DATA, byte);
+    Ok(())
+}
+
+/// 向輔助裝置寫入一個位元組
+pub fn i8042_write_aux(byte: u8) -> Result<(), I8042Error> {
+    i8042_command(CMD_WRITE_AUX)?;
+    i8042_write_data(byte)
+}
+
+/// 發送控制器命令
+fn i8042_command(cmd: u8) -> Result<(), I8042Error> {
+    wait_input_empty()?;
+    io::io_port_wb(I8042_COMMAND, cmd);
+    Ok(())
+}
+
+/// 發送控制器命令並讀取回應
+fn i8042_command_read(cmd: u8) -> Result<u8, I8042Error> {
+    i8042_command(cmd)?;
+    i8042_read_data()
+}
+
+/// 讀取配置位元組
+pub fn i8042_read_config() -> Result<u8, I8042Error> {
+    i8042_command_read(CMD_READ_CONFIG)
+}
+
+/// 寫入配置位元組
+pub fn i8042_write_config(config: u8) -> Result<(), I8042Error> {
+    i8042_command(CMD_WRITE_CONFIG)?;
+    i8042_write_data(config)
+}
+
+/// 啟用輔助裝置端口
+pub fn i8042_enable_aux() -> Result<(), I8042Error> {
+    i8042_command(CMD_ENABLE_AUX)
+}
+
+/// 清空輸出緩衝中殘留的資料
+pub fn i8042_flush() {
+    for _ in 0..16 {
+        if i8042_read_status() & STATUS_OUTPUT_FULL == 0 {
+            return;
+        }
+        io::io_port_rb(I8042_DATA);
+    }
+}
+
+/// 是否有輔助裝置端口（雙通道控制器）
+pub fn i8042_has_aux() -> bool {
+    I8042_HAS_AUX.load(Ordering::Relaxed)
+}
+
+/// 初始化 8042 控制器
+///
+/// 關閉兩個端口並清空緩衝後進行自檢，確認是否為雙通道控制器，
+/// 最後只重新啟用鍵盤端口（保留掃描碼轉換），中斷由各裝置驅動自行開啟
+pub fn i8042_init() -> Result<(), I8042Error> {
+    i8042_command(CMD_DISABLE_KBD)?;
+    i8042_command(CMD_DISABLE_AUX)?;
+    i8042_flush();
+
+    let mut config = i8042_read_config()?;
+    config &= !(CONFIG_KBD_IRQ | CONFIG_AUX_IRQ);
+    config |= CONFIG_TRANSLATION;
+    i8042_write_config(config)?;
+
+    match i8042_command_read(CMD_SELF_TEST)? {
+        SELF_TEST_OK => {}
+        r => return Err(I8042Error::SelfTestFailed(r)),
+    }
+    // 部分控制器自檢後會重設配置
+    i8042_write_config(config)?;
+
+    // 啟用輔助端口後時鐘關閉位元被清除，表示為雙通道控制器
+    i8042_command(CMD_ENABLE_AUX)?;
+    let has_aux = i8042_read_config()? & CONFIG_AUX_CLOCK_DISABLE == 0;
+    i8042_command(CMD_DISABLE_AUX)?;
+
+    match i8042_command_read(CMD_TEST_KBD)? {
+        PORT_TEST_OK => {}
+        r => return Err(I8042Error::PortTestFailed(r)),
+    }
+    let aux_ok = has_aux && i8042_command_read(CMD_TEST_AUX)? == PORT_TEST_OK;
+    I8042_HAS_AUX.store(aux_ok, Ordering::Relaxed);
+
+    i8042_command(CMD_ENABLE_KBD)?;
+    i8042_flush();
+
+    Ok(())
+}
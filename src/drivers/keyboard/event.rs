@@ -0,0 +1,278 @@
+// src/drivers/keyboard/event.rs
+use super::layout::{self, Layout};
+use super::scancode::{KeyCode, ScancodeDecoder};
+
+/// 鍵盤 LED：Scroll Lock
+pub const LED_SCROLL_LOCK: u8 = 0x01;
+/// 鍵盤 LED：Num Lock
+pub const LED_NUM_LOCK: u8 = 0x02;
+/// 鍵盤 LED：Caps Lock
+pub const LED_CAPS_LOCK: u8 = 0x04;
+
+/// 修飾鍵與鎖定鍵狀態
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Modifiers {
+    pub shift: bool,
+    pub ctrl: bool,
+    pub alt: bool,
+    pub altgr: bool,
+    pub caps_lock: bool,
+    pub num_lock: bool,
+    pub scroll_lock: bool,
+}
+
+impl Modifiers {
+    /// 對應的 LED 位元遮罩（`0xED` 命令的參數）
+    pub fn leds(&self) -> u8 {
+        let mut leds = 0;
+        if self.scroll_lock {
+            leds |= LED_SCROLL_LOCK;
+        }
+        if self.num_lock {
+            leds |= LED_NUM_LOCK;
+        }
+        if self.caps_lock {
+            leds |= LED_CAPS_LOCK;
+        }
+        leds
+    }
+}
+
+/// 按鍵事件
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KeyEvent {
+    /// 實體按鍵；Num Lock 關閉時小鍵盤數字鍵會換成對應的編輯鍵
+    pub code: KeyCode,
+    /// 按下 (`true`) 或放開 (`false`)
+    pub pressed: bool,
+    /// 按住時由鍵盤自動重複產生的按下事件
+    pub repeat: bool,
+    /// 事件發生後的修飾鍵狀態
+    pub modifiers: Modifiers,
+    /// 按下時產生的字元，Ctrl+字母為對應的控制字元
+    pub ch: Option<char>,
+}
+
+/// 鍵盤狀態：將掃描碼轉換為按鍵事件
+pub struct KeyboardState {
+    decoder: ScancodeDecoder,
+    layout: &'static Layout,
+    modifiers: Modifiers,
+    /// 每個按鍵是否按住，用於辨別自動重複
+    held: [u32; 8],
+    left_shift: bool,
+    right_shift: bool,
+    left_ctrl: bool,
+    right_ctrl: bool,
+    left_alt: bool,
+    right_alt: bool,
+}
+
+impl KeyboardState {
+    pub const fn new(layout: &'static Layout) -> Self {
+        Self {
+            decoder: ScancodeDecoder::new(),
+            layout,
+            modifiers: Modifiers {
+                shift: false,
+                ctrl: false,
+                alt: false,
+                altgr: false,
+                caps_lock: false,
+                num_lock: false,
+                scroll_lock: false,
+            },
+            held: [0; 8],
+            left_shift: false,
+            right_shift: false,
+            left_ctrl: false,
+            right_ctrl: false,
+            left_alt: false,
+            right_alt: false,
+        }
+    }
+
+    pub fn layout(&self) -> &'static Layout {
+        self.layout
+    }
+
+    /// 切換佈局，已按住的按鍵狀態保留
+    pub fn set_layout(&mut self, layout: &'static Layout) {
+        self.layout = layout;
+        self.update_modifiers();
+    }
+
+    pub fn modifiers(&self) -> Modifiers {
+        self.modifiers
+    }
+
+    /// 設定鎖定鍵狀態
+    #[allow(dead_code)]
+    pub fn set_locks(&mut self, caps_lock: bool, num_lock: bool, scroll_lock: bool) {
+        self.modifiers.caps_lock = caps_lock;
+        self.modifiers.num_lock = num_lock;
+        self.modifiers.scroll_lock = scroll_lock;
+    }
+
+    /// 清除未完成的掃描碼序列與按住狀態（鍵盤重置後使用）
+    pub fn reset(&mut self) {
+        self.decoder.reset();
+        self.held = [0; 8];
+        self.left_shift = false;
+        self.right_shift = false;
+        self.left_ctrl = false;
+        self.right_ctrl = false;
+        self.left_alt = false;
+        self.right_alt = false;
+        self.update_modifiers();
+    }
+
+    fn is_held(&self, code: KeyCode) -> bool {
+        self.held[code.0 as usize / 32] & (1 << (code.0 % 32)) != 0
+    }
+
+    fn set_held(&mut self, code: KeyCode, held: bool) {
+        let bit = 1 << (code.0 % 32);
+        if held {
+            self.held[code.0 as usize / 32] |= bit;
+        } else {
+            self.held[code.0 as usize / 32] &= !bit;
+        }
+    }
+
+    fn update_modifiers(&mut self) {
+        let has_altgr = self.layout.altgr.is_some();
+        self.modifiers.shift = self.left_shift || self.right_shift;
+        self.modifiers.ctrl = self.left_ctrl || self.right_ctrl;
+        self.modifiers.alt = self.left_alt || (self.right_alt && !has_altgr);
+        self.modifiers.altgr = self.right_alt && has_altgr;
+    }
+
+    /// 輸入一個掃描碼位元組
+    ///
+    /// # 返回
+    /// 完整的按鍵事件，序列尚未結束時返回 `None`
+    pub fn process(&mut self, byte: u8) -> Option<KeyEvent> {
+        let (code, pressed) = self.decoder.feed(byte)?;
+        let repeat = pressed && self.is_held(code);
+        self.set_held(code, pressed);
+
+        match code {
+            KeyCode::LEFT_SHIFT => self.left_shift = pressed,
+            KeyCode::RIGHT_SHIFT => self.right_shift = pressed,
+            KeyCode::LEFT_CTRL => self.left_ctrl = pressed,
+            KeyCode::RIGHT_CTRL => self.right_ctrl = pressed,
+            KeyCode::LEFT_ALT => self.left_alt = pressed,
+            KeyCode::RIGHT_ALT => self.right_alt = pressed,
+            KeyCode::CAPS_LOCK if pressed && !repeat => self.modifiers.caps_lock ^= true,
+            KeyCode::NUM_LOCK if pressed && !repeat => self.modifiers.num_lock ^= true,
+            KeyCode::SCROLL_LOCK if pressed && !repeat => self.modifiers.scroll_lock ^= true,
+            _ => {}
+        }
+        self.update_modifiers();
+
+        let modifiers = self.modifiers;
+        // Shift 會暫時反轉 Num Lock
+        let num_lock = modifiers.num_lock != modifiers.shift;
+        let code = match layout::keypad_navigation(code) {
+            Some(navigation) if !num_lock => navigation,
+            _ => code,
+        };
+
+        let ch = if pressed { self.translate(code, modifiers, num_lock) } else { None };
+
+        Some(KeyEvent { code, pressed, repeat, modifiers, ch })
+    }
+
+    /// 將按鍵轉換為字元
+    fn translate(&self, code: KeyCode, modifiers: Modifiers, num_lock: bool) -> Option<char> {
+        if let Some(c) = layout::keypad_char(code, num_lock) {
+            return Some(c);
+        }
+
+        let shift = modifiers.shift != (modifiers.caps_lock && self.layout.is_alphabetic(code));
+        let c = self.layout.lookup(code, shift, modifiers.altgr)?;
+
+        if modifiers.ctrl && !modifiers.altgr {
+            return match c.to_ascii_uppercase() {
+                // Ctrl+@ .. Ctrl+_ 對應 0x00-0x1F
+                c @ '@'..='_' => Some((c as u8 - b'@') as char),
+                '?' => Some('\x7f'),
+                _ => Some(c),
+            };
+        }
+
+        Some(c)
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+    use super::super::layout::{LAYOUT_DE, LAYOUT_US};
+
+    /// 依序輸入掃描碼，收集產生的字元
+    fn type_chars(state: &mut KeyboardState, bytes: &[u8]) -> String {
+        bytes.iter().filter_map(|&b| state.process(b)).filter_map(|e| e.ch).collect()
+    }
+
+    #[test]
+    fn shift_and_caps_lock() {
+        let mut state = KeyboardState::new(&LAYOUT_US);
+        // a, Shift+a, Shift+1
+        assert_eq!(type_chars(&mut state, &[0x1E, 0x9E, 0x2A, 0x1E, 0x9E, 0x02, 0x82, 0xAA]), "aA!");
+
+        // Caps Lock 只影響字母，Shift 再反轉回小寫
+        type_chars(&mut state, &[0x3A, 0xBA]);
+        assert!(state.modifiers().caps_lock);
+        assert_eq!(state.modifiers().leds(), LED_CAPS_LOCK);
+        assert_eq!(type_chars(&mut state, &[0x1E, 0x9E, 0x02, 0x82, 0x36, 0x1E, 0x9E, 0xB6]), "A1a");
+    }
+
+    #[test]
+    fn lock_keys_ignore_repeat() {
+        let mut state = KeyboardState::new(&LAYOUT_US);
+        // 按住 Caps Lock 時的自動重複不應再次切換
+        let events: Vec<_> = [0x3A, 0x3A, 0x3A, 0xBA].iter().filter_map(|&b| state.process(b)).collect();
+        assert_eq!(events.iter().map(|e| e.repeat).collect::<Vec<_>>(), [false, true, true, false]);
+        assert!(state.modifiers().caps_lock);
+    }
+
+    #[test]
+    fn control_characters() {
+        let mut state = KeyboardState::new(&LAYOUT_US);
+        // Ctrl+C, Ctrl+[, 右 Ctrl+D
+        assert_eq!(
+            type_chars(&mut state, &[0x1D, 0x2E, 0xAE, 0x1A, 0x9A, 0x9D, 0xE0, 0x1D, 0x20]),
+            "\x03\x1b\x04"
+        );
+    }
+
+    #[test]
+    fn keypad_and_num_lock() {
+        let mut state = KeyboardState::new(&LAYOUT_US);
+        let event = state.process(0x4F).unwrap();
+        assert_eq!((event.code, event.ch), (KeyCode::END, None));
+
+        state.set_locks(false, true, false);
+        assert_eq!(type_chars(&mut state, &[0x4F, 0xCF, 0x4E, 0xE0, 0x35]), "1+/");
+        // Shift 暫時關閉 Num Lock
+        let event = [0x2A, 0x48].iter().filter_map(|&b| state.process(b)).last().unwrap();
+        assert_eq!((event.code, event.ch), (KeyCode::UP, None));
+    }
+
+    #[test]
+    fn altgr_and_layout_switch() {
+        let mut state = KeyboardState::new(&LAYOUT_US);
+        // 美式佈局中右 Alt 為一般 Alt
+        state.process(0xE0);
+        assert!(state.process(0x38).unwrap().modifiers.alt);
+        state.process(0xE0);
+        state.process(0xB8);
+
+        state.set_layout(&LAYOUT_DE);
+        assert_eq!(type_chars(&mut state, &[0x15, 0x95, 0x27, 0xA7]), "zö");
+        assert_eq!(type_chars(&mut state, &[0xE0, 0x38, 0x10, 0x90, 0x12, 0xE0, 0xB8]), "@€");
+        assert!(!state.modifiers().altgr);
+    }
+}
@@ -0,0 +1,223 @@
+// src/drivers/keyboard/layout.rs
+use super::scancode::KeyCode;
+
+/// 佈局表覆蓋的非擴展掃描碼範圍 (0x00-0x58)
+const KEYMAP_SIZE: usize = 0x59;
+
+/// 掃描碼到字元的對照表，`'\0'` 表示不產生字元
+pub type Keymap = [char; KEYMAP_SIZE];
+
+/// 鍵盤佈局
+pub struct Layout {
+    /// 佈局名稱
+    pub name: &'static str,
+    pub normal: Keymap,
+    pub shift: Keymap,
+    /// AltGr 層，沒有 AltGr 的佈局中右 Alt 作為一般 Alt
+    pub altgr: Option<Keymap>,
+}
+
+/// 由連續的按鍵區段建立對照表
+///
+/// # 參數
+/// * `ranges` - (起始掃描碼, 字元) 區段
+const fn keymap(ranges: &[(u8, &[char])]) -> Keymap {
+    let mut map = ['\0'; KEYMAP_SIZE];
+    let mut i = 0;
+    while i < ranges.len() {
+        let (start, chars) = ranges[i];
+        let mut j = 0;
+        while j < chars.len() {
+            map[start as usize + j] = chars[j];
+            j += 1;
+        }
+        i += 1;
+    }
+    map
+}
+
+impl Layout {
+    /// 查詢按鍵在指定層的字元
+    ///
+    /// # 參數
+    /// * `code` - 按鍵
+    /// * `shift` - 是否使用 Shift 層
+    /// * `altgr` - 是否使用 AltGr 層（優先於 Shift 層）
+    pub fn lookup(&self, code: KeyCode, shift: bool, altgr: bool) -> Option<char> {
+        let index = code.0 as usize;
+        if index >= KEYMAP_SIZE {
+            return None;
+        }
+
+        let map = match (altgr, &self.altgr) {
+            (true, Some(map)) => map,
+            (true, None) => return None,
+            _ if shift => &self.shift,
+            _ => &self.normal,
+        };
+
+        match map[index] {
+            '\0' => None,
+            c => Some(c),
+        }
+    }
+
+    /// 按鍵是否受 Caps Lock 影響（兩層都是字母）
+    pub fn is_alphabetic(&self, code: KeyCode) -> bool {
+        let index = code.0 as usize;
+        index < KEYMAP_SIZE && self.normal[index].is_alphabetic() && self.shift[index].is_alphabetic()
+    }
+}
+
+/// 小鍵盤在 Num Lock 開啟時的字元 (0x47-0x53)
+const KEYPAD_DIGITS: [char; 13] = ['7', '8', '9', '-', '4', '5', '6', '+', '1', '2', '3', '0', '.'];
+
+/// 小鍵盤在 Num Lock 關閉時對應的編輯鍵 (0x47-0x53)
+const KEYPAD_NAVIGATION: [Option<KeyCode>; 13] = [
+    Some(KeyCode::HOME),
+    Some(KeyCode::UP),
+    Some(KeyCode::PAGE_UP),
+    None,
+    Some(KeyCode::LEFT),
+    None,
+    Some(KeyCode::RIGHT),
+    None,
+    Some(KeyCode::END),
+    Some(KeyCode::DOWN),
+    Some(KeyCode::PAGE_DOWN),
+    Some(KeyCode::INSERT),
+    Some(KeyCode::DELETE),
+];
+
+/// 小鍵盤字元，與佈局無關
+///
+/// # 參數
+/// * `code` - 按鍵
+/// * `num_lock` - Num Lock 是否生效（已考慮 Shift）
+pub fn keypad_char(code: KeyCode, num_lock: bool) -> Option<char> {
+    match code {
+        KeyCode::KEYPAD_MULTIPLY => Some('*'),
+        KeyCode::KEYPAD_MINUS => Some('-'),
+        KeyCode::KEYPAD_PLUS => Some('+'),
+        KeyCode::KEYPAD_DIVIDE => Some('/'),
+        KeyCode::KEYPAD_ENTER => Some('\n'),
+        k if k.is_keypad_digit() && num_lock => Some(KEYPAD_DIGITS[(k.0 - KeyCode::KEYPAD_7.0) as usize]),
+        _ => None,
+    }
+}
+
+/// Num Lock 關閉時小鍵盤數字鍵對應的編輯鍵
+pub fn keypad_navigation(code: KeyCode) -> Option<KeyCode> {
+    if code.is_keypad_digit() {
+        KEYPAD_NAVIGATION[(code.0 - KeyCode::KEYPAD_7.0) as usize]
+    } else {
+        None
+    }
+}
+
+/// 美式 QWERTY
+pub static LAYOUT_US: Layout = Layout {
+    name: "us",
+    normal: keymap(&[
+        (0x01, &[
+            '\x1b', '1', '2', '3', '4', '5', '6', '7', '8', '9', '0', '-', '=', '\x08',
+            '\t', 'q', 'w', 'e', 'r', 't', 'y', 'u', 'i', 'o', 'p', '[', ']', '\n',
+        ]),
+        (0x1E, &['a', 's', 'd', 'f', 'g', 'h', 'j', 'k', 'l', ';', '\'', '`']),
+        (0x2B, &['\\', 'z', 'x', 'c', 'v', 'b', 'n', 'm', ',', '.', '/']),
+        (0x39, &[' ']),
+        (0x56, &['\\']),
+    ]),
+    shift: keymap(&[
+        (0x01, &[
+            '\x1b', '!', '@', '#', '$', '%', '^', '&', '*', '(', ')', '_', '+', '\x08',
+            '\t', 'Q', 'W', 'E', 'R', 'T', 'Y', 'U', 'I', 'O', 'P', '{', '}', '\n',
+        ]),
+        (0x1E, &['A', 'S', 'D', 'F', 'G', 'H', 'J', 'K', 'L', ':', '"', '~']),
+        (0x2B, &['|', 'Z', 'X', 'C', 'V', 'B', 'N', 'M', '<', '>', '?']),
+        (0x39, &[' ']),
+        (0x56, &['|']),
+    ]),
+    altgr: None,
+};
+
+/// 德式 QWERTZ，重音鍵直接產生字元（不支援死鍵）
+pub static LAYOUT_DE: Layout = Layout {
+    name: "de",
+    normal: keymap(&[
+        (0x01, &[
+            '\x1b', '1', '2', '3', '4', '5', '6', '7', '8', '9', '0', 'ß', '´', '\x08',
+            '\t', 'q', 'w', 'e', 'r', 't', 'z', 'u', 'i', 'o', 'p', 'ü', '+', '\n',
+        ]),
+        (0x1E, &['a', 's', 'd', 'f', 'g', 'h', 'j', 'k', 'l', 'ö', 'ä', '^']),
+        (0x2B, &['#', 'y', 'x', 'c', 'v', 'b', 'n', 'm', ',', '.', '-']),
+        (0x39, &[' ']),
+        (0x56, &['<']),
+    ]),
+    shift: keymap(&[
+        (0x01, &[
+            '\x1b', '!', '"', '§', '$', '%', '&', '/', '(', ')', '=', '?', '`', '\x08',
+            '\t', 'Q', 'W', 'E', 'R', 'T', 'Z', 'U', 'I', 'O', 'P', 'Ü', '*', '\n',
+        ]),
+        (0x1E, &['A', 'S', 'D', 'F', 'G', 'H', 'J', 'K', 'L', 'Ö', 'Ä', '°']),
+        (0x2B, &['\'', 'Y', 'X', 'C', 'V', 'B', 'N', 'M', ';', ':', '_']),
+        (0x39, &[' ']),
+        (0x56, &['>']),
+    ]),
+    altgr: Some(keymap(&[
+        (0x03, &['²', '³']),
+        (0x08, &['{', '[', ']', '}', '\\']),
+        (0x10, &['@']),
+        (0x12, &['€']),
+        (0x1B, &['~']),
+        (0x32, &['µ']),
+        (0x56, &['|']),
+    ])),
+};
+
+/// 所有內建佈局
+pub static LAYOUTS: [&Layout; 2] = [&LAYOUT_US, &LAYOUT_DE];
+
+/// 依名稱查找內建佈局
+#[allow(dead_code)]
+pub fn layout_find(name: &str) -> Option<&'static Layout> {
+    LAYOUTS.iter().copied().find(|layout| layout.name == name)
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn us_layers() {
+        assert_eq!(LAYOUT_US.lookup(KeyCode(0x10), false, false), Some('q'));
+        assert_eq!(LAYOUT_US.lookup(KeyCode(0x03), true, false), Some('@'));
+        assert_eq!(LAYOUT_US.lookup(KeyCode(0x03), false, true), None);
+        assert_eq!(LAYOUT_US.lookup(KeyCode::LEFT_SHIFT, false, false), None);
+        assert_eq!(LAYOUT_US.lookup(KeyCode::UP, false, false), None);
+        assert!(LAYOUT_US.is_alphabetic(KeyCode(0x1E)));
+        assert!(!LAYOUT_US.is_alphabetic(KeyCode(0x27)));
+    }
+
+    #[test]
+    fn de_layers() {
+        let de = layout_find("de").unwrap();
+        assert_eq!(de.lookup(KeyCode(0x15), false, false), Some('z'));
+        assert_eq!(de.lookup(KeyCode(0x27), true, false), Some('Ö'));
+        assert_eq!(de.lookup(KeyCode(0x10), false, true), Some('@'));
+        assert_eq!(de.lookup(KeyCode(0x12), true, true), Some('€'));
+        // ß 的 Shift 層是 ?，不受 Caps Lock 影響
+        assert!(!de.is_alphabetic(KeyCode(0x0C)));
+        assert!(de.is_alphabetic(KeyCode(0x28)));
+    }
+
+    #[test]
+    fn keypad() {
+        assert_eq!(keypad_char(KeyCode(0x4F), true), Some('1'));
+        assert_eq!(keypad_char(KeyCode(0x4F), false), None);
+        assert_eq!(keypad_char(KeyCode::KEYPAD_PLUS, false), Some('+'));
+        assert_eq!(keypad_navigation(KeyCode(0x4F)), Some(KeyCode::END));
+        assert_eq!(keypad_navigation(KeyCode(0x4C)), None);
+        assert!(layout_find("fr").is_none());
+    }
+}
@@ -0,0 +1,10 @@
+// src/drivers/keyboard/mod.rs
+
+pub mod event;
+pub mod layout;
+#[cfg(not(feature = "std"))]
+mod ps2;
+pub mod scancode;
+
+#[cfg(not(feature = "std"))]
+pub use ps2::*;
@@ -0,0 +1,255 @@
+// src/drivers/keyboard/ps2.rs
+use core::fmt;
+use crate::drivers::i8042::{self, I8042Error};
+use crate::kernel::asm::x86::interrupt::{self, TrapFrame};
+use crate::libs::ring::RingBuffer;
+use crate::libs::sync::SpinLock;
+use super::event::{KeyEvent, KeyboardState, Modifiers};
+use super::layout::{Layout, LAYOUT_US};
+
+/// 鍵盤 IRQ 線
+pub const KEYBOARD_IRQ: u8 = 1;
+/// 事件佇列大小
+const EVENT_QUEUE_SIZE: usize = 128;
+
+// 鍵盤命令
+const KBD_CMD_SET_LEDS: u8 = 0xED;
+const KBD_CMD_SCANCODE_SET: u8 = 0xF0;
+const KBD_CMD_ENABLE_SCANNING: u8 = 0xF4;
+const KBD_CMD_RESET: u8 = 0xFF;
+
+// 鍵盤回應
+const KBD_ACK: u8 = 0xFA;
+const KBD_RESEND: u8 = 0xFE;
+const KBD_SELF_TEST_OK: u8 = 0xAA;
+/// 按鍵緩衝溢出或偵測錯誤
+const KBD_ERROR: u8 = 0x00;
+const KBD_OVERRUN: u8 = 0xFF;
+
+/// 收到 Resend 時重送命令的次數
+const KBD_RETRIES: usize = 3;
+
+/// 鍵盤錯誤
+#[allow(dead_code)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyboardError {
+    /// 8042 控制器錯誤
+    Controller(I8042Error),
+    /// 命令沒有被確認，附帶鍵盤的回應
+    NoAck(u8),
+    /// 鍵盤自檢失敗，附帶返回值
+    SelfTestFailed(u8),
+    /// IRQ1 已被其他處理函數佔用
+    IrqBusy,
+}
+
+impl fmt::Display for KeyboardError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            KeyboardError::Controller(e) => write!(f, "{}", e),
+            KeyboardError::NoAck(r) => write!(f, "command not acknowledged (0x{:02x})", r),
+            KeyboardError::SelfTestFailed(r) => write!(f, "keyboard self test failed (0x{:02x})", r),
+            KeyboardError::IrqBusy => write!(f, "IRQ{} already in use", KEYBOARD_IRQ),
+        }
+    }
+}
+
+impl From<I8042Error> for KeyboardError {
+    fn from(e: I8042Error) -> Self {
+        KeyboardError::Controller(e)
+    }
+}
+
+/// 中斷中更新 LED 的進度
+///
+/// `0xED` 與遮罩需分別等待鍵盤確認，確認由 IRQ1 送達
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum LedState {
+    Idle,
+    /// 已送出 `0xED`
+    AwaitCommandAck,
+    /// 已送出遮罩
+    AwaitMaskAck,
+}
+
+struct Keyboard {
+    state: KeyboardState,
+    events: RingBuffer<KeyEvent, EVENT_QUEUE_SIZE>,
+    /// 佇列已滿而丟棄的事件數
+    dropped: usize,
+    led_state: LedState,
+    /// 最後送出的 LED 遮罩
+    leds: u8,
+}
+
+impl Keyboard {
+    const fn new() -> Self {
+        Self {
+            state: KeyboardState::new(&LAYOUT_US),
+            events: RingBuffer::new(),
+            dropped: 0,
+            led_state: LedState::Idle,
+            leds: 0,
+        }
+    }
+
+    /// 若鎖定鍵狀態與 LED 不一致，開始更新 LED
+    fn sync_leds(&mut self) {
+        if self.led_state == LedState::Idle && self.state.modifiers().leds() != self.leds {
+            self.send_led_byte(LedState::AwaitCommandAck);
+        }
+    }
+
+    fn send_led_byte(&mut self, next: LedState) {
+        let byte = match next {
+            LedState::AwaitCommandAck => KBD_CMD_SET_LEDS,
+            LedState::AwaitMaskAck => {
+                self.leds = self.state.modifiers().leds();
+                self.leds
+            }
+            LedState::Idle => return,
+        };
+
+        self.led_state = match i8042::i8042_write_data(byte) {
+            Ok(()) => next,
+            Err(_) => LedState::Idle,
+        };
+    }
+
+    fn handle_ack(&mut self) {
+        match self.led_state {
+            LedState::AwaitCommandAck => self.send_led_byte(LedState::AwaitMaskAck),
+            LedState::AwaitMaskAck => {
+                self.led_state = LedState::Idle;
+                // 等待確認期間鎖定鍵可能又被切換
+                self.sync_leds();
+            }
+            LedState::Idle => {}
+        }
+    }
+
+    fn handle_resend(&mut self) {
+        let state = self.led_state;
+        self.send_led_byte(state);
+    }
+
+    fn handle_scancode(&mut self, byte: u8) {
+        if let Some(event) = self.state.process(byte) {
+            if !self.events.push(event) {
+                self.dropped += 1;
+            }
+            self.sync_leds();
+        }
+    }
+}
+
+static KEYBOARD: SpinLock<Keyboard> = SpinLock::new(Keyboard::new());
+
+/// 以輪詢方式發送鍵盤命令並等待確認
+fn keyboard_command(byte: u8) -> Result<(), KeyboardError> {
+    for _ in 0..KBD_RETRIES {
+        i8042::i8042_write_data(byte)?;
+        match i8042::i8042_read_data()? {
+            KBD_ACK => return Ok(()),
+            KBD_RESEND => continue,
+            r => return Err(KeyboardError::NoAck(r)),
+        }
+    }
+    Err(KeyboardError::NoAck(KBD_RESEND))
+}
+
+/// 初始化 PS/2 鍵盤並註冊 IRQ1
+///
+/// 重置鍵盤並選擇掃描碼集 2（由 8042 轉換為集 1），需在 `i8042_init` 之後、
+/// 開啟中斷之前呼叫
+pub fn keyboard_init() -> Result<(), KeyboardError> {
+    keyboard_command(KBD_CMD_RESET)?;
+    match i8042::i8042_read_data()? {
+        KBD_SELF_TEST_OK => {}
+        r => return Err(KeyboardError::SelfTestFailed(r)),
+    }
+
+    keyboard_command(KBD_CMD_SCANCODE_SET)?;
+    keyboard_command(2)?;
+
+    let leds = {
+        let mut kbd = KEYBOARD.lock();
+        kbd.state.reset();
+        kbd.leds = kbd.state.modifiers().leds();
+        kbd.leds
+    };
+    keyboard_command(KBD_CMD_SET_LEDS)?;
+    keyboard_command(leds)?;
+
+    keyboard_command(KBD_CMD_ENABLE_SCANNING)?;
+
+    if !interrupt::register_irq_handler(KEYBOARD_IRQ, keyboard_irq_handler) {
+        return Err(KeyboardError::IrqBusy);
+    }
+    let config = i8042::i8042_read_config()?;
+    i8042::i8042_write_config(config | i8042::CONFIG_KBD_IRQ)?;
+
+    Ok(())
+}
+
+/// IRQ1 處理函數
+fn keyboard_irq_handler(_frame: &mut TrapFrame) {
+    // 輔助裝置的資料留給 IRQ12
+    let status = i8042::i8042_read_status();
+    if status & i8042::STATUS_OUTPUT_FULL == 0 || status & i8042::STATUS_AUX_DATA != 0 {
+        return;
+    }
+
+    let byte = i8042::i8042_read_data_now();
+    let mut kbd = KEYBOARD.lock();
+
+    match byte {
+        KBD_ACK => kbd.handle_ack(),
+        KBD_RESEND => kbd.handle_resend(),
+        KBD_ERROR | KBD_OVERRUN => {}
+        _ => kbd.handle_scancode(byte),
+    }
+}
+
+/// 從佇列中取出一個按鍵事件
+#[allow(dead_code)]
+pub fn keyboard_read_event() -> Option<KeyEvent> {
+    KEYBOARD.lock().events.pop()
+}
+
+/// 從佇列中取出下一個產生字元的按鍵
+///
+/// 不產生字元的事件（放開、修飾鍵等）會被丟棄
+#[allow(dead_code)]
+pub fn keyboard_read_char() -> Option<char> {
+    let mut kbd = KEYBOARD.lock();
+    while let Some(event) = kbd.events.pop() {
+        if let Some(c) = event.ch {
+            return Some(c);
+        }
+    }
+    None
+}
+
+/// 目前的修飾鍵狀態
+#[allow(dead_code)]
+pub fn keyboard_modifiers() -> Modifiers {
+    KEYBOARD.lock().state.modifiers()
+}
+
+/// 切換鍵盤佈局
+#[allow(dead_code)]
+pub fn keyboard_set_layout(layout: &'static Layout) {
+    KEYBOARD.lock().state.set_layout(layout);
+}
+
+/// 目前的鍵盤佈局
+pub fn keyboard_layout() -> &'static Layout {
+    KEYBOARD.lock().state.layout()
+}
+
+/// 因佇列已滿而丟棄的事件數
+#[allow(dead_code)]
+pub fn keyboard_dropped() -> usize {
+    KEYBOARD.lock().dropped
+}
@@ -0,0 +1,206 @@
+// src/drivers/keyboard/scancode.rs
+
+/// 擴展碼前綴
+const PREFIX_E0: u8 = 0xE0;
+/// Pause 鍵序列的前綴
+const PREFIX_E1: u8 = 0xE1;
+/// 斷碼（放開）標誌
+const BREAK_BIT: u8 = 0x80;
+/// Pause 鍵序列 `E1 1D 45 E1 9D C5` 在前綴後的長度
+const PAUSE_SEQUENCE_LEN: u8 = 5;
+
+/// 實體按鍵
+///
+/// 數值為掃描碼集 1 的通碼，帶 `E0` 前綴的擴展鍵再加上 0x80，
+/// 因此佈局表可以直接以數值索引
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct KeyCode(pub u8);
+
+#[allow(dead_code)]
+impl KeyCode {
+    pub const ESCAPE: KeyCode = KeyCode(0x01);
+    pub const BACKSPACE: KeyCode = KeyCode(0x0E);
+    pub const TAB: KeyCode = KeyCode(0x0F);
+    pub const ENTER: KeyCode = KeyCode(0x1C);
+    pub const LEFT_CTRL: KeyCode = KeyCode(0x1D);
+    pub const LEFT_SHIFT: KeyCode = KeyCode(0x2A);
+    pub const RIGHT_SHIFT: KeyCode = KeyCode(0x36);
+    pub const KEYPAD_MULTIPLY: KeyCode = KeyCode(0x37);
+    pub const LEFT_ALT: KeyCode = KeyCode(0x38);
+    pub const SPACE: KeyCode = KeyCode(0x39);
+    pub const CAPS_LOCK: KeyCode = KeyCode(0x3A);
+    pub const F1: KeyCode = KeyCode(0x3B);
+    pub const F2: KeyCode = KeyCode(0x3C);
+    pub const F3: KeyCode = KeyCode(0x3D);
+    pub const F4: KeyCode = KeyCode(0x3E);
+    pub const F5: KeyCode = KeyCode(0x3F);
+    pub const F6: KeyCode = KeyCode(0x40);
+    pub const F7: KeyCode = KeyCode(0x41);
+    pub const F8: KeyCode = KeyCode(0x42);
+    pub const F9: KeyCode = KeyCode(0x43);
+    pub const F10: KeyCode = KeyCode(0x44);
+    pub const NUM_LOCK: KeyCode = KeyCode(0x45);
+    pub const SCROLL_LOCK: KeyCode = KeyCode(0x46);
+    /// 小鍵盤 7 到 .（0x47-0x53）
+    pub const KEYPAD_7: KeyCode = KeyCode(0x47);
+    pub const KEYPAD_MINUS: KeyCode = KeyCode(0x4A);
+    pub const KEYPAD_PLUS: KeyCode = KeyCode(0x4E);
+    pub const KEYPAD_PERIOD: KeyCode = KeyCode(0x53);
+    /// ISO 鍵盤 Shift 左側的額外按鍵
+    pub const ISO_EXTRA: KeyCode = KeyCode(0x56);
+    pub const F11: KeyCode = KeyCode(0x57);
+    pub const F12: KeyCode = KeyCode(0x58);
+
+    pub const KEYPAD_ENTER: KeyCode = KeyCode(0x80 | 0x1C);
+    pub const RIGHT_CTRL: KeyCode = KeyCode(0x80 | 0x1D);
+    pub const KEYPAD_DIVIDE: KeyCode = KeyCode(0x80 | 0x35);
+    pub const PRINT_SCREEN: KeyCode = KeyCode(0x80 | 0x37);
+    /// 右 Alt，在有 AltGr 的佈局中作為 AltGr
+    pub const RIGHT_ALT: KeyCode = KeyCode(0x80 | 0x38);
+    /// Pause 沒有擴展碼，佔用未使用的 `E0 45`
+    pub const PAUSE: KeyCode = KeyCode(0x80 | 0x45);
+    pub const HOME: KeyCode = KeyCode(0x80 | 0x47);
+    pub const UP: KeyCode = KeyCode(0x80 | 0x48);
+    pub const PAGE_UP: KeyCode = KeyCode(0x80 | 0x49);
+    pub const LEFT: KeyCode = KeyCode(0x80 | 0x4B);
+    pub const RIGHT: KeyCode = KeyCode(0x80 | 0x4D);
+    pub const END: KeyCode = KeyCode(0x80 | 0x4F);
+    pub const DOWN: KeyCode = KeyCode(0x80 | 0x50);
+    pub const PAGE_DOWN: KeyCode = KeyCode(0x80 | 0x51);
+    pub const INSERT: KeyCode = KeyCode(0x80 | 0x52);
+    pub const DELETE: KeyCode = KeyCode(0x80 | 0x53);
+    pub const LEFT_GUI: KeyCode = KeyCode(0x80 | 0x5B);
+    pub const RIGHT_GUI: KeyCode = KeyCode(0x80 | 0x5C);
+    pub const MENU: KeyCode = KeyCode(0x80 | 0x5D);
+
+    /// 是否為帶 `E0` 前綴的擴展鍵
+    pub fn is_extended(self) -> bool {
+        self.0 & 0x80 != 0
+    }
+
+    /// 是否為小鍵盤的數字或小數點鍵（受 Num Lock 影響）
+    pub fn is_keypad_digit(self) -> bool {
+        (Self::KEYPAD_7.0..=Self::KEYPAD_PERIOD.0).contains(&self.0)
+            && self != Self::KEYPAD_MINUS
+            && self != Self::KEYPAD_PLUS
+    }
+
+    /// 功能鍵 F1-F12 的編號
+    ///
+    /// # 返回
+    /// 1-12，非功能鍵返回 `None`
+    pub fn function_key(self) -> Option<u8> {
+        match self {
+            k if (Self::F1.0..=Self::F10.0).contains(&k.0) => Some(k.0 - Self::F1.0 + 1),
+            Self::F11 => Some(11),
+            Self::F12 => Some(12),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum DecodeState {
+    Normal,
+    /// 收到 `E0`
+    Extended,
+    /// 收到 `E1`，剩餘需略過的位元組數
+    Pause(u8),
+}
+
+/// 掃描碼集 1 解碼器
+///
+/// 8042 開啟轉換時，集 2 鍵盤送出的掃描碼也會被轉換成集 1
+pub struct ScancodeDecoder {
+    state: DecodeState,
+}
+
+impl ScancodeDecoder {
+    pub const fn new() -> Self {
+        Self { state: DecodeState::Normal }
+    }
+
+    /// 輸入一個掃描碼位元組
+    ///
+    /// # 返回
+    /// 完整的按鍵與是否按下，序列尚未結束或被忽略時返回 `None`
+    pub fn feed(&mut self, byte: u8) -> Option<(KeyCode, bool)> {
+        match self.state {
+            DecodeState::Pause(remaining) => {
+                if remaining > 1 {
+                    self.state = DecodeState::Pause(remaining - 1);
+                    return None;
+                }
+                // Pause 只有按下，沒有放開
+                self.state = DecodeState::Normal;
+                Some((KeyCode::PAUSE, true))
+            }
+            _ if byte == PREFIX_E0 => {
+                self.state = DecodeState::Extended;
+                None
+            }
+            _ if byte == PREFIX_E1 => {
+                self.state = DecodeState::Pause(PAUSE_SEQUENCE_LEN);
+                None
+            }
+            DecodeState::Extended => {
+                self.state = DecodeState::Normal;
+                let code = byte & !BREAK_BIT;
+                // Print Screen 等按鍵附帶的假 Shift (E0 2A / E0 36)
+                if code == KeyCode::LEFT_SHIFT.0 || code == KeyCode::RIGHT_SHIFT.0 {
+                    return None;
+                }
+                Some((KeyCode(0x80 | code), byte & BREAK_BIT == 0))
+            }
+            DecodeState::Normal => {
+                let code = byte & !BREAK_BIT;
+                if code == 0 {
+                    return None;
+                }
+                Some((KeyCode(code), byte & BREAK_BIT == 0))
+            }
+        }
+    }
+
+    /// 丟棄未完成的序列
+    pub fn reset(&mut self) {
+        self.state = DecodeState::Normal;
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+
+    fn decode(bytes: &[u8]) -> Vec<(KeyCode, bool)> {
+        let mut decoder = ScancodeDecoder::new();
+        bytes.iter().filter_map(|&b| decoder.feed(b)).collect()
+    }
+
+    #[test]
+    fn make_and_break_codes() {
+        assert_eq!(decode(&[0x1E, 0x9E]), [(KeyCode(0x1E), true), (KeyCode(0x1E), false)]);
+        assert_eq!(decode(&[0xE0, 0x48, 0xE0, 0xC8]), [(KeyCode::UP, true), (KeyCode::UP, false)]);
+        assert_eq!(decode(&[0xE0, 0x38]), [(KeyCode::RIGHT_ALT, true)]);
+    }
+
+    #[test]
+    fn special_sequences() {
+        // Print Screen 按下與放開
+        assert_eq!(
+            decode(&[0xE0, 0x2A, 0xE0, 0x37, 0xE0, 0xB7, 0xE0, 0xAA]),
+            [(KeyCode::PRINT_SCREEN, true), (KeyCode::PRINT_SCREEN, false)]
+        );
+        assert_eq!(decode(&[0xE1, 0x1D, 0x45, 0xE1, 0x9D, 0xC5, 0x1C]), [(KeyCode::PAUSE, true), (KeyCode::ENTER, true)]);
+    }
+
+    #[test]
+    fn key_classes() {
+        assert_eq!(KeyCode::F1.function_key(), Some(1));
+        assert_eq!(KeyCode::F12.function_key(), Some(12));
+        assert_eq!(KeyCode::ESCAPE.function_key(), None);
+        assert!(KeyCode(0x4F).is_keypad_digit());
+        assert!(!KeyCode::KEYPAD_PLUS.is_keypad_digit());
+        assert!(KeyCode::END.is_extended());
+    }
+}
@@ -0,0 +1,13 @@
+// src/drivers/mod.rs
+
+#[cfg(not(feature = "std"))]
+pub mod i8042;
+pub mod keyboard;
+pub mod mouse;
+#[cfg(not(feature = "std"))]
+pub mod pit;
+#[cfg(not(feature = "std"))]
+pub mod rtc;
+#[cfg(not(feature = "std"))]
+pub mod serial;
+pub mod vga;
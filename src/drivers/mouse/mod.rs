@@ -0,0 +1,8 @@
+// src/drivers/mouse/mod.rs
+
+pub mod packet;
+#[cfg(not(feature = "std"))]
+mod ps2;
+
+#[cfg(not(feature = "std"))]
+pub use ps2::*;
@@ -0,0 +1,159 @@
+// src/drivers/mouse/packet.rs
+
+// 封包第一個位元組的標誌
+const PACKET_LEFT: u8 = 0x01;
+const PACKET_RIGHT: u8 = 0x02;
+const PACKET_MIDDLE: u8 = 0x04;
+/// 永遠為 1，用於同步封包邊界
+const PACKET_ALWAYS_ONE: u8 = 0x08;
+const PACKET_X_SIGN: u8 = 0x10;
+const PACKET_Y_SIGN: u8 = 0x20;
+const PACKET_X_OVERFLOW: u8 = 0x40;
+const PACKET_Y_OVERFLOW: u8 = 0x80;
+
+/// 標準 PS/2 封包長度
+pub const PACKET_LEN_STANDARD: usize = 3;
+/// IntelliMouse 封包長度（第 4 個位元組為滾輪）
+pub const PACKET_LEN_WHEEL: usize = 4;
+
+/// 滑鼠按鍵狀態
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct MouseButtons {
+    pub left: bool,
+    pub right: bool,
+    pub middle: bool,
+}
+
+/// 滑鼠事件
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct MouseEvent {
+    /// 水平移動量，向右為正
+    pub dx: i16,
+    /// 垂直移動量，以螢幕座標表示，向下為正
+    pub dy: i16,
+    /// 滾輪移動量，向下捲動為正；沒有滾輪時為 0
+    pub wheel: i8,
+    /// 事件發生時的按鍵狀態
+    pub buttons: MouseButtons,
+}
+
+/// PS/2 滑鼠封包解碼器
+pub struct PacketDecoder {
+    packet: [u8; PACKET_LEN_WHEEL],
+    index: usize,
+    packet_len: usize,
+}
+
+impl PacketDecoder {
+    pub const fn new() -> Self {
+        Self {
+            packet: [0; PACKET_LEN_WHEEL],
+            index: 0,
+            packet_len: PACKET_LEN_STANDARD,
+        }
+    }
+
+    /// 設定是否使用 IntelliMouse 4 位元組封包
+    pub fn set_wheel(&mut self, wheel: bool) {
+        self.packet_len = if wheel { PACKET_LEN_WHEEL } else { PACKET_LEN_STANDARD };
+        self.index = 0;
+    }
+
+    pub fn has_wheel(&self) -> bool {
+        self.packet_len == PACKET_LEN_WHEEL
+    }
+
+    /// 輸入一個位元組
+    ///
+    /// # 返回
+    /// 收齊一個封包時返回事件；第一個位元組不合法時丟棄以重新同步
+    pub fn feed(&mut self, byte: u8) -> Option<MouseEvent> {
+        if self.index == 0 && byte & PACKET_ALWAYS_ONE == 0 {
+            return None;
+        }
+
+        self.packet[self.index] = byte;
+        self.index += 1;
+        if self.index < self.packet_len {
+            return None;
+        }
+        self.index = 0;
+
+        Some(self.decode())
+    }
+
+    fn decode(&self) -> MouseEvent {
+        let flags = self.packet[0];
+        let motion = |value: u8, sign: u8, overflow: u8| -> i16 {
+            if flags & overflow != 0 {
+                0
+            } else if flags & sign != 0 {
+                value as i16 - 0x100
+            } else {
+                value as i16
+            }
+        };
+
+        // 滾輪為 4 位元有號數
+        let wheel = if self.has_wheel() { ((self.packet[3] << 4) as i8) >> 4 } else { 0 };
+
+        MouseEvent {
+            dx: motion(self.packet[1], PACKET_X_SIGN, PACKET_X_OVERFLOW),
+            // 裝置以向上為正
+            dy: -motion(self.packet[2], PACKET_Y_SIGN, PACKET_Y_OVERFLOW),
+            wheel,
+            buttons: MouseButtons {
+                left: flags & PACKET_LEFT != 0,
+                right: flags & PACKET_RIGHT != 0,
+                middle: flags & PACKET_MIDDLE != 0,
+            },
+        }
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+
+    fn decode(decoder: &mut PacketDecoder, bytes: &[u8]) -> Vec<MouseEvent> {
+        bytes.iter().filter_map(|&b| decoder.feed(b)).collect()
+    }
+
+    #[test]
+    fn standard_packets() {
+        let mut decoder = PacketDecoder::new();
+        // 向右 5、向上 3，左鍵按下
+        let events = decode(&mut decoder, &[0x09, 0x05, 0x03]);
+        assert_eq!(events, [MouseEvent {
+            dx: 5,
+            dy: -3,
+            wheel: 0,
+            buttons: MouseButtons { left: true, ..Default::default() },
+        }]);
+
+        // 向左 2、向下 1，右鍵與中鍵
+        let event = decode(&mut decoder, &[0x3E, 0xFE, 0xFF])[0];
+        assert_eq!((event.dx, event.dy), (-2, 1));
+        assert_eq!(event.buttons, MouseButtons { left: false, right: true, middle: true });
+    }
+
+    #[test]
+    fn overflow_and_resync() {
+        let mut decoder = PacketDecoder::new();
+        let event = decode(&mut decoder, &[0x48, 0xFF, 0x10])[0];
+        assert_eq!((event.dx, event.dy), (0, -16));
+
+        // 第 4 位元為 0 的位元組不能作為封包開頭
+        assert_eq!(decode(&mut decoder, &[0x00, 0x07, 0x08, 0x01, 0x00]).len(), 1);
+    }
+
+    #[test]
+    fn wheel_packets() {
+        let mut decoder = PacketDecoder::new();
+        decoder.set_wheel(true);
+        assert!(decoder.has_wheel());
+
+        let events = decode(&mut decoder, &[0x08, 0x00, 0x00, 0x01, 0x08, 0x00, 0x00, 0x0F]);
+        assert_eq!(events.iter().map(|e| e.wheel).collect::<Vec<_>>(), [1, -1]);
+    }
+}
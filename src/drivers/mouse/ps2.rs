@@ -0,0 +1,258 @@
+// src/drivers/mouse/ps2.rs
+use core::fmt;
+use crate::drivers::i8042::{self, I8042Error};
+use crate::kernel::asm::x86::interrupt::{self, TrapFrame};
+use crate::kernel::tty::tty;
+use crate::libs::ring::RingBuffer;
+use crate::libs::sync::SpinLock;
+use super::packet::{MouseEvent, PacketDecoder};
+
+/// 滑鼠 IRQ 線
+pub const MOUSE_IRQ: u8 = 12;
+/// 事件佇列大小
+const EVENT_QUEUE_SIZE: usize = 64;
+
+// 滑鼠命令
+const MOUSE_CMD_SET_SAMPLE_RATE: u8 = 0xF3;
+const MOUSE_CMD_GET_ID: u8 = 0xF2;
+const MOUSE_CMD_ENABLE_STREAMING: u8 = 0xF4;
+const MOUSE_CMD_SET_DEFAULTS: u8 = 0xF6;
+const MOUSE_CMD_RESET: u8 = 0xFF;
+
+// 滑鼠回應
+const MOUSE_ACK: u8 = 0xFA;
+const MOUSE_RESEND: u8 = 0xFE;
+const MOUSE_SELF_TEST_OK: u8 = 0xAA;
+
+/// 依序設定這些取樣率可啟用 IntelliMouse 滾輪
+const INTELLIMOUSE_SEQUENCE: [u8; 3] = [200, 100, 80];
+/// 啟用滾輪後的裝置 ID
+const MOUSE_ID_INTELLIMOUSE: u8 = 3;
+
+/// 收到 Resend 時重送命令的次數
+const MOUSE_RETRIES: usize = 3;
+
+/// 文字模式游標每個字元格對應的移動量
+const CELL_WIDTH: i32 = 8;
+const CELL_HEIGHT: i32 = 16;
+
+/// 滑鼠類型
+#[allow(dead_code)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MouseKind {
+    /// 三鍵、3 位元組封包
+    Standard,
+    /// 帶滾輪、4 位元組封包
+    IntelliMouse,
+}
+
+impl fmt::Display for MouseKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MouseKind::Standard => write!(f, "standard"),
+            MouseKind::IntelliMouse => write!(f, "IntelliMouse"),
+        }
+    }
+}
+
+/// 滑鼠錯誤
+#[allow(dead_code)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MouseError {
+    /// 控制器沒有輔助裝置端口
+    NoAuxPort,
+    /// 8042 控制器錯誤
+    Controller(I8042Error),
+    /// 命令沒有被確認，附帶滑鼠的回應
+    NoAck(u8),
+    /// 滑鼠自檢失敗，附帶返回值
+    SelfTestFailed(u8),
+    /// IRQ12 已被其他處理函數佔用
+    IrqBusy,
+}
+
+impl fmt::Display for MouseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MouseError::NoAuxPort => write!(f, "no auxiliary port"),
+            MouseError::Controller(e) => write!(f, "{}", e),
+            MouseError::NoAck(r) => write!(f, "command not acknowledged (0x{:02x})", r),
+            MouseError::SelfTestFailed(r) => write!(f, "mouse self test failed (0x{:02x})", r),
+            MouseError::IrqBusy => write!(f, "IRQ{} already in use", MOUSE_IRQ),
+        }
+    }
+}
+
+impl From<I8042Error> for MouseError {
+    fn from(e: I8042Error) -> Self {
+        MouseError::Controller(e)
+    }
+}
+
+struct Mouse {
+    decoder: PacketDecoder,
+    events: RingBuffer<MouseEvent, EVENT_QUEUE_SIZE>,
+    /// 佇列已滿而丟棄的事件數
+    dropped: usize,
+    /// 是否在文字模式畫出游標
+    text_cursor: bool,
+    /// 游標位置，單位為滑鼠移動量
+    x: i32,
+    y: i32,
+}
+
+impl Mouse {
+    const fn new() -> Self {
+        Self {
+            decoder: PacketDecoder::new(),
+            events: RingBuffer::new(),
+            dropped: 0,
+            text_cursor: false,
+            x: tty::TTY_WIDTH as i32 / 2 * CELL_WIDTH,
+            y: tty::TTY_HEIGHT as i32 / 2 * CELL_HEIGHT,
+        }
+    }
+
+    /// 游標所在的字元格
+    fn cell(&self) -> (usize, usize) {
+        ((self.x / CELL_WIDTH) as usize, (self.y / CELL_HEIGHT) as usize)
+    }
+
+    /// 移動游標
+    ///
+    /// # 返回
+    /// 游標是否換到另一個字元格
+    fn move_cursor(&mut self, event: &MouseEvent) -> bool {
+        let old = self.cell();
+        self.x = (self.x + event.dx as i32).clamp(0, tty::TTY_WIDTH as i32 * CELL_WIDTH - 1);
+        self.y = (self.y + event.dy as i32).clamp(0, tty::TTY_HEIGHT as i32 * CELL_HEIGHT - 1);
+        self.cell() != old
+    }
+}
+
+static MOUSE: SpinLock<Mouse> = SpinLock::new(Mouse::new());
+
+/// 以輪詢方式發送滑鼠命令並等待確認
+fn mouse_command(byte: u8) -> Result<(), MouseError> {
+    for _ in 0..MOUSE_RETRIES {
+        i8042::i8042_write_aux(byte)?;
+        match i8042::i8042_read_aux_data()? {
+            MOUSE_ACK => return Ok(()),
+            MOUSE_RESEND => continue,
+            r => return Err(MouseError::NoAck(r)),
+        }
+    }
+    Err(MouseError::NoAck(MOUSE_RESEND))
+}
+
+/// 初始化 PS/2 滑鼠並註冊 IRQ12
+///
+/// 啟用 8042 輔助端口，重置滑鼠並嘗試啟用 IntelliMouse 滾輪，
+/// 需在 `i8042_init` 之後、開啟中斷之前呼叫
+///
+/// # 返回
+/// 偵測到的滑鼠類型
+pub fn mouse_init() -> Result<MouseKind, MouseError> {
+    if !i8042::i8042_has_aux() {
+        return Err(MouseError::NoAuxPort);
+    }
+    i8042::i8042_enable_aux()?;
+
+    mouse_command(MOUSE_CMD_RESET)?;
+    match i8042::i8042_read_aux_data()? {
+        MOUSE_SELF_TEST_OK => {}
+        r => return Err(MouseError::SelfTestFailed(r)),
+    }
+    // 重置後送出裝置 ID
+    i8042::i8042_read_aux_data()?;
+
+    mouse_command(MOUSE_CMD_SET_DEFAULTS)?;
+    for rate in INTELLIMOUSE_SEQUENCE {
+        mouse_command(MOUSE_CMD_SET_SAMPLE_RATE)?;
+        mouse_command(rate)?;
+    }
+    mouse_command(MOUSE_CMD_GET_ID)?;
+    let kind = match i8042::i8042_read_aux_data()? {
+        MOUSE_ID_INTELLIMOUSE => MouseKind::IntelliMouse,
+        _ => MouseKind::Standard,
+    };
+
+    {
+        let mut mouse = MOUSE.lock();
+        mouse.decoder.set_wheel(kind == MouseKind::IntelliMouse);
+    }
+
+    mouse_command(MOUSE_CMD_ENABLE_STREAMING)?;
+
+    if !interrupt::register_irq_handler(MOUSE_IRQ, mouse_irq_handler) {
+        return Err(MouseError::IrqBusy);
+    }
+    let config = i8042::i8042_read_config()?;
+    i8042::i8042_write_config(config | i8042::CONFIG_AUX_IRQ)?;
+
+    Ok(kind)
+}
+
+/// IRQ12 處理函數
+fn mouse_irq_handler(_frame: &mut TrapFrame) {
+    let status = i8042::i8042_read_status();
+    if status & i8042::STATUS_OUTPUT_FULL == 0 || status & i8042::STATUS_AUX_DATA == 0 {
+        return;
+    }
+
+    let byte = i8042::i8042_read_data_now();
+    let cursor = {
+        let mut mouse = MOUSE.lock();
+        let Some(event) = mouse.decoder.feed(byte) else {
+            return;
+        };
+        if !mouse.events.push(event) {
+            mouse.dropped += 1;
+        }
+
+        let moved = mouse.move_cursor(&event);
+        (mouse.text_cursor && moved).then(|| mouse.cell())
+    };
+
+    if let Some(cell) = cursor {
+        let _lock = tty::tty_lock();
+        tty::tty_set_mouse_cursor(Some(cell));
+    }
+}
+
+/// 從佇列中取出一個滑鼠事件
+#[allow(dead_code)]
+pub fn mouse_read_event() -> Option<MouseEvent> {
+    MOUSE.lock().events.pop()
+}
+
+/// 顯示或隱藏文字模式的方塊游標
+pub fn mouse_set_text_cursor(enabled: bool) {
+    let cell = {
+        let mut mouse = MOUSE.lock();
+        mouse.text_cursor = enabled;
+        enabled.then(|| mouse.cell())
+    };
+
+    let _lock = tty::tty_lock();
+    tty::tty_set_mouse_cursor(cell);
+}
+
+/// 內核命令列是否含有 `mousecursor`
+///
+/// 文字模式的方塊游標預設關閉，只在指定此選項時顯示
+pub fn mouse_cursor_from_cmdline(cmdline: &str) -> bool {
+    cmdline.split_whitespace().any(|arg| arg == "mousecursor")
+}
+
+/// 文字模式游標所在的字元格
+#[allow(dead_code)]
+pub fn mouse_cursor_cell() -> (usize, usize) {
+    MOUSE.lock().cell()
+}
+
+/// 因佇列已滿而丟棄的事件數
+#[allow(dead_code)]
+pub fn mouse_dropped() -> usize {
+    MOUSE.lock().dropped
+}
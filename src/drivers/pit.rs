@@ -0,0 +1,176 @@
+// src/drivers/pit.rs
+use core::fmt;
+use core::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, Ordering};
+use crate::hal::io;
+use crate::kernel::asm::x86::interrupt::{self, TrapFrame};
+use crate::kernel::time::{self, Clocksource};
+
+/// 通道 0 資料端口，輸出接到 IRQ0
+const PIT_CHANNEL0: u16 = 0x40;
+/// 模式/命令端口
+const PIT_COMMAND: u16 = 0x43;
+
+/// 命令：通道 0，先低後高位元組，模式 2（速率產生器），二進位計數
+const CMD_CHANNEL0_RATE: u8 = 0x34;
+/// 命令：鎖存通道 0 的目前計數
+const CMD_CHANNEL0_LATCH: u8 = 0x00;
+
+/// PIT 的 IRQ 線
+pub const PIT_IRQ: u8 = 0;
+/// PIT 輸入時鐘頻率 (Hz)
+pub const PIT_BASE_FREQUENCY: u32 = 1_193_182;
+/// 預設的中斷頻率 (Hz)
+pub const PIT_DEFAULT_FREQUENCY: u32 = 1000;
+
+/// PIT 錯誤
+#[allow(dead_code)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PitError {
+    /// 頻率超出 19 Hz ~ 1193182 Hz 的範圍
+    InvalidFrequency(u32),
+    /// IRQ0 已被其他處理函數佔用
+    IrqBusy,
+}
+
+impl fmt::Display for PitError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PitError::InvalidFrequency(hz) => write!(f, "unsupported frequency {} Hz", hz),
+            PitError::IrqBusy => write!(f, "IRQ0 already in use"),
+        }
+    }
+}
+
+/// 目前的分頻值，0 表示尚未初始化
+static PIT_DIVISOR: AtomicU32 = AtomicU32::new(0);
+/// 開機以來經過的 PIT 輸入時鐘週期數，每次中斷累加一個分頻值
+static PIT_CYCLES: AtomicU64 = AtomicU64::new(0);
+static PIT_IRQ_REGISTERED: AtomicBool = AtomicBool::new(false);
+
+/// 以中斷計數為基礎的時鐘源，解析度為一個中斷週期
+static PIT_CLOCKSOURCE: Clocksource = Clocksource {
+    name: "pit",
+    rating: 100,
+    read_ns: pit_elapsed_ns,
+    irq_driven: true,
+};
+
+/// 由頻率計算分頻值
+fn pit_divisor(hz: u32) -> Result<u32, PitError> {
+    if hz == 0 {
+        return Err(PitError::InvalidFrequency(hz));
+    }
+
+    let divisor = (PIT_BASE_FREQUENCY + hz / 2) / hz;
+    if divisor == 0 || divisor > 0x10000 {
+        return Err(PitError::InvalidFrequency(hz));
+    }
+
+    Ok(divisor)
+}
+
+/// 初始化 PIT，註冊 IRQ0 與時鐘源
+///
+/// 需在 PIC 初始化之後呼叫
+///
+/// # 參數
+/// * `hz` - 中斷頻率，實際頻率為最接近的可分頻值
+pub fn pit_init(hz: u32) -> Result<(), PitError> {
+    pit_set_frequency(hz)?;
+
+    if !PIT_IRQ_REGISTERED.load(Ordering::Relaxed) {
+        if !interrupt::register_irq_handler(PIT_IRQ, pit_irq_handler) {
+            return Err(PitError::IrqBusy);
+        }
+        PIT_IRQ_REGISTERED.store(true, Ordering::Relaxed);
+        time::clocksource_register(&PIT_CLOCKSOURCE);
+    }
+
+    Ok(())
+}
+
+/// 重新設定中斷頻率
+///
+/// 已累計的時間不受影響
+///
+/// # 參數
+/// * `hz` - 中斷頻率
+pub fn pit_set_frequency(hz: u32) -> Result<(), PitError> {
+    let divisor = pit_divisor(hz)?;
+
+    // 分頻值 0x10000 以 0 寫入
+    io::io_port_wb(PIT_COMMAND, CMD_CHANNEL0_RATE);
+    io::io_port_wb(PIT_CHANNEL0, (divisor & 0xFF) as u8);
+    io::io_port_wb(PIT_CHANNEL0, ((divisor >> 8) & 0xFF) as u8);
+
+    PIT_DIVISOR.store(divisor, Ordering::Relaxed);
+    Ok(())
+}
+
+/// 目前的實際中斷頻率 (Hz)，未初始化時為 0
+#[allow(dead_code)]
+pub fn pit_frequency() -> u32 {
+    match PIT_DIVISOR.load(Ordering::Relaxed) {
+        0 => 0,
+        divisor => PIT_BASE_FREQUENCY / divisor,
+    }
+}
+
+/// 讀取通道 0 的目前計數值
+///
+/// 模式 2 下由分頻值遞減到 1 後重新載入
+pub fn pit_read_count() -> u16 {
+    io::io_port_wb(PIT_COMMAND, CMD_CHANNEL0_LATCH);
+    let lo = io::io_port_rb(PIT_CHANNEL0) as u16;
+    let hi = io::io_port_rb(PIT_CHANNEL0) as u16;
+    hi << 8 | lo
+}
+
+/// 開機以來 PIT 中斷累計的時間 (ns)，解析度為一個中斷週期
+pub fn pit_elapsed_ns() -> u64 {
+    time::cycles_to_ns(PIT_CYCLES.load(Ordering::Relaxed), PIT_BASE_FREQUENCY as u64)
+}
+
+/// 輪詢通道 0 的計數值忙等待，不依賴中斷
+///
+/// 用於中斷關閉時的延遲，每個中斷週期內至少需讀取一次計數值
+///
+/// # 參數
+/// * `ns` - 等待時間
+pub fn pit_poll_wait_ns(ns: u64) {
+    let divisor = PIT_DIVISOR.load(Ordering::Relaxed) as u64;
+    if divisor == 0 {
+        return;
+    }
+
+    let target = time::ns_to_cycles(ns, PIT_BASE_FREQUENCY as u64);
+    let mut elapsed = 0;
+    let mut prev = pit_read_count() as u64;
+
+    while elapsed < target {
+        let now = pit_read_count() as u64;
+        elapsed += if now <= prev { prev - now } else { prev + divisor - now };
+        prev = now;
+        core::hint::spin_loop();
+    }
+}
+
+/// IRQ0 處理
+fn pit_irq_handler(_frame: &mut TrapFrame) {
+    PIT_CYCLES.fetch_add(PIT_DIVISOR.load(Ordering::Relaxed) as u64, Ordering::Relaxed);
+    time::time_tick();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test_case]
+    fn divisor_range() {
+        assert_eq!(pit_divisor(1000), Ok(1193));
+        assert_eq!(pit_divisor(19), Ok(62799));
+        assert_eq!(pit_divisor(18), Err(PitError::InvalidFrequency(18)));
+        assert_eq!(pit_divisor(0), Err(PitError::InvalidFrequency(0)));
+        assert_eq!(pit_divisor(PIT_BASE_FREQUENCY), Ok(1));
+    }
+}
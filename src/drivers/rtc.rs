@@ -0,0 +1,334 @@
+// src/drivers/rtc.rs
+use core::fmt;
+use core::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, Ordering};
+use crate::hal::io;
+use crate::kernel::asm::x86::interrupt::{self, TrapFrame};
+use crate::kernel::time::{self, NANOS_PER_SEC};
+use crate::libs::datetime::DateTime;
+use crate::libs::sync::SpinLock;
+
+/// CMOS 索引端口
+const CMOS_INDEX: u16 = 0x70;
+/// CMOS 資料端口
+const CMOS_DATA: u16 = 0x71;
+
+// RTC 寄存器
+const RTC_SECONDS: u8 = 0x00;
+const RTC_MINUTES: u8 = 0x02;
+const RTC_HOURS: u8 = 0x04;
+const RTC_DAY: u8 = 0x07;
+const RTC_MONTH: u8 = 0x08;
+const RTC_YEAR: u8 = 0x09;
+const RTC_STATUS_A: u8 = 0x0A;
+const RTC_STATUS_B: u8 = 0x0B;
+const RTC_STATUS_C: u8 = 0x0C;
+/// 世紀寄存器，正式位置由 ACPI FADT 給出，0x32 為慣用位置
+const RTC_CENTURY: u8 = 0x32;
+
+/// A: 更新進行中
+const STATUS_A_UIP: u8 = 0x80;
+/// A: 速率選擇位元
+const STATUS_A_RATE_MASK: u8 = 0x0F;
+/// B: 週期中斷啟用
+const STATUS_B_PIE: u8 = 0x40;
+/// B: 二進位模式（否則為 BCD）
+const STATUS_B_BINARY: u8 = 0x04;
+/// B: 24 小時制
+const STATUS_B_24H: u8 = 0x02;
+/// C: 週期中斷旗標
+const STATUS_C_PF: u8 = 0x40;
+/// 12 小時制下小時的 PM 位元
+const HOUR_PM: u8 = 0x80;
+
+/// RTC 的 IRQ 線
+pub const RTC_IRQ: u8 = 8;
+/// RTC 的振盪頻率 (Hz)
+const RTC_BASE_FREQUENCY: u32 = 32768;
+
+/// 週期中斷回調，在 IRQ8 中執行
+pub type RtcCallback = fn();
+
+/// RTC 錯誤
+#[allow(dead_code)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RtcError {
+    /// 讀到的時間不合法
+    InvalidTime(DateTime),
+    /// 週期中斷速率超出 3-15
+    InvalidRate(u8),
+    /// IRQ8 已被其他處理函數佔用
+    IrqBusy,
+}
+
+impl fmt::Display for RtcError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RtcError::InvalidTime(dt) => write!(f, "invalid time {}", dt),
+            RtcError::InvalidRate(rate) => write!(f, "unsupported periodic rate {}", rate),
+            RtcError::IrqBusy => write!(f, "IRQ8 already in use"),
+        }
+    }
+}
+
+/// 時間寄存器的原始值
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct RtcRaw {
+    second: u8,
+    minute: u8,
+    hour: u8,
+    day: u8,
+    month: u8,
+    year: u8,
+    century: u8,
+}
+
+/// 索引與資料端口必須成對存取
+static CMOS_LOCK: SpinLock<()> = SpinLock::new(());
+
+/// 開機時刻的 UNIX 時間戳，由 `rtc_init` 設定
+static RTC_BOOT_UNIX: AtomicU64 = AtomicU64::new(0);
+static RTC_PERIODIC_HZ: AtomicU32 = AtomicU32::new(0);
+static RTC_IRQ_COUNT: AtomicU64 = AtomicU64::new(0);
+static RTC_IRQ_REGISTERED: AtomicBool = AtomicBool::new(false);
+static mut RTC_CALLBACK: Option<RtcCallback> = None;
+
+/// 讀取 CMOS 寄存器，呼叫者需持有 `CMOS_LOCK`
+fn cmos_read(reg: u8) -> u8 {
+    io::io_port_wb(CMOS_INDEX, reg);
+    io::io_port_rb(CMOS_DATA)
+}
+
+/// 寫入 CMOS 寄存器，呼叫者需持有 `CMOS_LOCK`
+fn cmos_write(reg: u8, value: u8) {
+    io::io_port_wb(CMOS_INDEX, reg);
+    io::io_port_wb(CMOS_DATA, value);
+}
+
+fn bcd_to_binary(value: u8) -> u8 {
+    (value >> 4) * 10 + (value & 0x0F)
+}
+
+fn rtc_read_raw() -> RtcRaw {
+    while cmos_read(RTC_STATUS_A) & STATUS_A_UIP != 0 {
+        core::hint::spin_loop();
+    }
+
+    RtcRaw {
+        second: cmos_read(RTC_SECONDS),
+        minute: cmos_read(RTC_MINUTES),
+        hour: cmos_read(RTC_HOURS),
+        day: cmos_read(RTC_DAY),
+        month: cmos_read(RTC_MONTH),
+        year: cmos_read(RTC_YEAR),
+        century: cmos_read(RTC_CENTURY),
+    }
+}
+
+/// 依狀態寄存器 B 的格式解碼原始值
+fn rtc_decode(raw: RtcRaw, status_b: u8) -> DateTime {
+    let pm = raw.hour & HOUR_PM != 0;
+    let mut raw = RtcRaw { hour: raw.hour & !HOUR_PM, ..raw };
+
+    if status_b & STATUS_B_BINARY == 0 {
+        raw = RtcRaw {
+            second: bcd_to_binary(raw.second),
+            minute: bcd_to_binary(raw.minute),
+            hour: bcd_to_binary(raw.hour),
+            day: bcd_to_binary(raw.day),
+            month: bcd_to_binary(raw.month),
+            year: bcd_to_binary(raw.year),
+            century: bcd_to_binary(raw.century),
+        };
+    }
+
+    // 12 小時制：12 AM 為 0 點，12 PM 為 12 點
+    if status_b & STATUS_B_24H == 0 {
+        raw.hour = raw.hour % 12 + if pm { 12 } else { 0 };
+    }
+
+    // 沒有世紀寄存器時讀到的值不可靠，假設為 20xx 年
+    let century = if (19..=21).contains(&raw.century) { raw.century } else { 20 };
+
+    DateTime::new(
+        century as u16 * 100 + raw.year as u16,
+        raw.month,
+        raw.day,
+        raw.hour,
+        raw.minute,
+        raw.second,
+    )
+}
+
+/// 從 CMOS 讀取目前時間
+///
+/// 等待更新結束後連續讀取兩次，直到結果一致，避免讀到更新一半的值
+pub fn rtc_read() -> DateTime {
+    let _guard = CMOS_LOCK.lock();
+
+    let mut raw = rtc_read_raw();
+    loop {
+        let again = rtc_read_raw();
+        if again == raw {
+            break;
+        }
+        raw = again;
+    }
+
+    rtc_decode(raw, cmos_read(RTC_STATUS_B))
+}
+
+/// 讀取 RTC 並記錄開機時刻，之後的牆上時間由時鐘源推算
+///
+/// 需在時鐘源註冊之後呼叫
+///
+/// # 返回
+/// 目前時間
+pub fn rtc_init() -> Result<DateTime, RtcError> {
+    let now = rtc_read();
+    if !now.is_valid() {
+        return Err(RtcError::InvalidTime(now));
+    }
+
+    let uptime = time::uptime_ns() / NANOS_PER_SEC;
+    RTC_BOOT_UNIX.store(now.to_unix().saturating_sub(uptime), Ordering::Relaxed);
+
+    Ok(now)
+}
+
+/// 目前的 UNIX 時間戳，`rtc_init` 之前為 0
+#[allow(dead_code)]
+pub fn rtc_unix_time() -> u64 {
+    match RTC_BOOT_UNIX.load(Ordering::Relaxed) {
+        0 => 0,
+        boot => boot + time::uptime_ns() / NANOS_PER_SEC,
+    }
+}
+
+/// 目前的牆上時間 (UTC)
+#[allow(dead_code)]
+pub fn rtc_now() -> DateTime {
+    DateTime::from_unix(rtc_unix_time())
+}
+
+/// 啟用 RTC 週期中斷
+///
+/// # 參數
+/// * `rate` - 速率選擇 (3-15)，頻率為 `32768 >> (rate - 1)` Hz（8192 Hz ~ 2 Hz）
+/// * `callback` - 每次中斷時執行的回調
+#[allow(dead_code)]
+pub fn rtc_enable_periodic(rate: u8, callback: Option<RtcCallback>) -> Result<(), RtcError> {
+    if !(3..=15).contains(&rate) {
+        return Err(RtcError::InvalidRate(rate));
+    }
+
+    if !RTC_IRQ_REGISTERED.load(Ordering::Relaxed) {
+        if !interrupt::register_irq_handler(RTC_IRQ, rtc_irq_handler) {
+            return Err(RtcError::IrqBusy);
+        }
+        RTC_IRQ_REGISTERED.store(true, Ordering::Relaxed);
+    }
+
+    let _guard = CMOS_LOCK.lock();
+    unsafe {
+        RTC_CALLBACK = callback;
+    }
+
+    let status_a = cmos_read(RTC_STATUS_A);
+    cmos_write(RTC_STATUS_A, (status_a & !STATUS_A_RATE_MASK) | rate);
+    let status_b = cmos_read(RTC_STATUS_B);
+    cmos_write(RTC_STATUS_B, status_b | STATUS_B_PIE);
+    // 清除未處理的中斷旗標，否則 RTC 不會再觸發 IRQ8
+    cmos_read(RTC_STATUS_C);
+
+    RTC_PERIODIC_HZ.store(RTC_BASE_FREQUENCY >> (rate - 1), Ordering::Relaxed);
+    Ok(())
+}
+
+/// 停用 RTC 週期中斷
+#[allow(dead_code)]
+pub fn rtc_disable_periodic() {
+    let _guard = CMOS_LOCK.lock();
+
+    let status_b = cmos_read(RTC_STATUS_B);
+    cmos_write(RTC_STATUS_B, status_b & !STATUS_B_PIE);
+    unsafe {
+        RTC_CALLBACK = None;
+    }
+
+    RTC_PERIODIC_HZ.store(0, Ordering::Relaxed);
+}
+
+/// 週期中斷的頻率 (Hz)，未啟用時為 0
+#[allow(dead_code)]
+pub fn rtc_periodic_frequency() -> u32 {
+    RTC_PERIODIC_HZ.load(Ordering::Relaxed)
+}
+
+/// 已處理的週期中斷次數
+#[allow(dead_code)]
+pub fn rtc_irq_count() -> u64 {
+    RTC_IRQ_COUNT.load(Ordering::Relaxed)
+}
+
+/// IRQ8 處理
+fn rtc_irq_handler(_frame: &mut TrapFrame) {
+    // 讀取狀態寄存器 C 作為應答
+    let status_c = {
+        let _guard = CMOS_LOCK.lock();
+        cmos_read(RTC_STATUS_C)
+    };
+
+    if status_c & STATUS_C_PF != 0 {
+        RTC_IRQ_COUNT.fetch_add(1, Ordering::Relaxed);
+        if let Some(callback) = unsafe { RTC_CALLBACK } {
+            callback();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RAW: RtcRaw = RtcRaw { second: 0x59, minute: 0x30, hour: 0x12, day: 0x18, month: 0x10, year: 0x26, century: 0x20 };
+
+    #[test_case]
+    fn decode_bcd_24h() {
+        assert_eq!(rtc_decode(RAW, STATUS_B_24H), DateTime::new(2026, 10, 18, 12, 30, 59));
+    }
+
+    #[test_case]
+    fn decode_12h() {
+        let pm = RtcRaw { hour: HOUR_PM | 0x01, ..RAW };
+        let midnight = RtcRaw { hour: 0x12, ..RAW };
+        let noon = RtcRaw { hour: HOUR_PM | 0x12, ..RAW };
+
+        assert_eq!(rtc_decode(pm, 0).hour, 13);
+        assert_eq!(rtc_decode(midnight, 0).hour, 0);
+        assert_eq!(rtc_decode(noon, 0).hour, 12);
+    }
+
+    #[test_case]
+    fn decode_binary_without_century() {
+        let raw = RtcRaw { second: 5, minute: 4, hour: 3, day: 2, month: 1, year: 30, century: 0xFF };
+        assert_eq!(rtc_decode(raw, STATUS_B_BINARY | STATUS_B_24H), DateTime::new(2030, 1, 2, 3, 4, 5));
+    }
+
+    #[test_case]
+    fn read_is_valid() {
+        assert!(rtc_read().is_valid());
+    }
+
+    #[test_case]
+    fn periodic_interrupt() {
+        assert_eq!(rtc_enable_periodic(2, None), Err(RtcError::InvalidRate(2)));
+
+        let start = rtc_irq_count();
+        rtc_enable_periodic(6, None).expect("failed to enable IRQ8");
+        assert_eq!(rtc_periodic_frequency(), 1024);
+
+        time::sleep_ms(50);
+        rtc_disable_periodic();
+        assert!(rtc_irq_count() - start >= 10);
+    }
+}
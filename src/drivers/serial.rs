@@ -0,0 +1,438 @@
+// src/drivers/serial.rs
+use core::fmt;
+use crate::hal::io;
+use crate::kernel::asm::x86::interrupt::{self, TrapFrame};
+use crate::kernel::asm::x86::pic;
+use crate::libs::sync::SpinLock;
+
+// 寄存器偏移（相對於基址端口）
+/// 接收緩衝 / 發送保持寄存器，DLAB=1 時為除數低位元組
+const UART_DATA: u16 = 0;
+/// 中斷啟用寄存器，DLAB=1 時為除數高位元組
+const UART_IER: u16 = 1;
+/// FIFO 控制寄存器（寫）
+const UART_FCR: u16 = 2;
+/// 線路控制寄存器
+const UART_LCR: u16 = 3;
+/// Modem 控制寄存器
+const UART_MCR: u16 = 4;
+/// 線路狀態寄存器
+const UART_LSR: u16 = 5;
+
+/// IER: 接收資料可用中斷
+const IER_RX_AVAILABLE: u8 = 0x01;
+/// LCR: 除數鎖存存取位元
+const LCR_DLAB: u8 = 0x80;
+/// FCR: 啟用並清空 FIFO，觸發門檻 14 位元組
+const FCR_ENABLE_CLEAR_14: u8 = 0xC7;
+/// MCR: DTR | RTS | OUT2（OUT2 控制 IRQ 線是否連接到 PIC）
+const MCR_NORMAL: u8 = 0x0B;
+/// MCR: 迴路測試模式
+const MCR_LOOPBACK: u8 = 0x1E;
+/// LSR: 接收資料就緒
+const LSR_DATA_READY: u8 = 0x01;
+/// LSR: 發送保持寄存器空
+const LSR_THR_EMPTY: u8 = 0x20;
+
+/// UART 時鐘對應的最大鮑率（除數為 1）
+const UART_BASE_BAUD: u32 = 115200;
+/// 每個端口的接收緩衝區大小
+const RX_BUFFER_SIZE: usize = 256;
+
+/// 串口
+#[allow(dead_code)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ComPort {
+    Com1,
+    Com2,
+    Com3,
+    Com4,
+}
+
+impl ComPort {
+    /// I/O 基址端口
+    pub fn base(self) -> u16 {
+        match self {
+            ComPort::Com1 => 0x3F8,
+            ComPort::Com2 => 0x2F8,
+            ComPort::Com3 => 0x3E8,
+            ComPort::Com4 => 0x2E8,
+        }
+    }
+
+    /// 傳統的 IRQ 線，COM1/COM3 與 COM2/COM4 共用
+    pub fn irq(self) -> u8 {
+        match self {
+            ComPort::Com1 | ComPort::Com3 => 4,
+            ComPort::Com2 | ComPort::Com4 => 3,
+        }
+    }
+
+    fn index(self) -> usize {
+        self as usize
+    }
+
+    /// 由編號 (0-3) 取得串口
+    pub fn from_index(index: usize) -> Option<Self> {
+        match index {
+            0 => Some(ComPort::Com1),
+            1 => Some(ComPort::Com2),
+            2 => Some(ComPort::Com3),
+            3 => Some(ComPort::Com4),
+            _ => None,
+        }
+    }
+}
+
+/// 資料位元數
+#[allow(dead_code)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DataBits {
+    Five = 0,
+    Six = 1,
+    Seven = 2,
+    Eight = 3,
+}
+
+/// 校驗方式
+#[allow(dead_code)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Parity {
+    None = 0x00,
+    Odd = 0x08,
+    Even = 0x18,
+    Mark = 0x28,
+    Space = 0x38,
+}
+
+/// 停止位元數
+#[allow(dead_code)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StopBits {
+    One = 0x00,
+    /// 資料位元為 5 時為 1.5 個停止位元
+    Two = 0x04,
+}
+
+/// 串口線路設定
+#[derive(Debug, Clone, Copy)]
+pub struct SerialConfig {
+    pub baud: u32,
+    pub data_bits: DataBits,
+    pub parity: Parity,
+    pub stop_bits: StopBits,
+}
+
+impl SerialConfig {
+    /// 115200 8N1
+    pub const fn new() -> Self {
+        Self {
+            baud: UART_BASE_BAUD,
+            data_bits: DataBits::Eight,
+            parity: Parity::None,
+            stop_bits: StopBits::One,
+        }
+    }
+
+    fn lcr(&self) -> u8 {
+        self.data_bits as u8 | self.parity as u8 | self.stop_bits as u8
+    }
+}
+
+/// 串口錯誤
+#[allow(dead_code)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SerialError {
+    /// 鮑率為 0 或無法由 115200 整除
+    InvalidBaud(u32),
+    /// 迴路測試失敗，端口不存在
+    NotPresent,
+    /// 端口尚未初始化
+    NotInitialized,
+}
+
+impl fmt::Display for SerialError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SerialError::InvalidBaud(baud) => write!(f, "unsupported baud rate {}", baud),
+            SerialError::NotPresent => write!(f, "no UART found"),
+            SerialError::NotInitialized => write!(f, "port not initialized"),
+        }
+    }
+}
+
+/// 單個 UART 的狀態
+struct Uart {
+    present: bool,
+    rx_buffer: [u8; RX_BUFFER_SIZE],
+    rx_head: usize,
+    rx_len: usize,
+    rx_dropped: usize,
+}
+
+impl Uart {
+    const fn new() -> Self {
+        Self {
+            present: false,
+            rx_buffer: [0; RX_BUFFER_SIZE],
+            rx_head: 0,
+            rx_len: 0,
+            rx_dropped: 0,
+        }
+    }
+
+    fn push_rx(&mut self, byte: u8) {
+        if self.rx_len == RX_BUFFER_SIZE {
+            self.rx_dropped += 1;
+            return;
+        }
+
+        self.rx_buffer[(self.rx_head + self.rx_len) % RX_BUFFER_SIZE] = byte;
+        self.rx_len += 1;
+    }
+
+    fn pop_rx(&mut self) -> Option<u8> {
+        if self.rx_len == 0 {
+            return None;
+        }
+
+        let byte = self.rx_buffer[self.rx_head];
+        self.rx_head = (self.rx_head + 1) % RX_BUFFER_SIZE;
+        self.rx_len -= 1;
+        Some(byte)
+    }
+}
+
+static SERIAL_PORTS: [SpinLock<Uart>; 4] = [
+    SpinLock::new(Uart::new()),
+    SpinLock::new(Uart::new()),
+    SpinLock::new(Uart::new()),
+    SpinLock::new(Uart::new()),
+];
+
+/// 控制台輸出鏡像到的串口
+static mut SERIAL_MIRROR: Option<ComPort> = None;
+
+/// 初始化串口
+///
+/// 設定鮑率與線路格式、啟用 FIFO，並以迴路模式確認 UART 存在。
+/// 初始化後即可輪詢發送；接收中斷需另外呼叫 `serial_irq_init`
+///
+/// # 參數
+/// * `port` - 串口
+/// * `config` - 線路設定
+pub fn serial_init(port: ComPort, config: SerialConfig) -> Result<(), SerialError> {
+    if config.baud == 0 || !UART_BASE_BAUD.is_multiple_of(config.baud) {
+        return Err(SerialError::InvalidBaud(config.baud));
+    }
+
+    let base = port.base();
+    let divisor = (UART_BASE_BAUD / config.baud) as u16;
+    let mut uart = SERIAL_PORTS[port.index()].lock();
+
+    io::io_port_wb(base + UART_IER, 0);
+
+    io::io_port_wb(base + UART_LCR, LCR_DLAB);
+    io::io_port_wb(base + UART_DATA, (divisor & 0xFF) as u8);
+    io::io_port_wb(base + UART_IER, (divisor >> 8) as u8);
+    io::io_port_wb(base + UART_LCR, config.lcr());
+
+    io::io_port_wb(base + UART_FCR, FCR_ENABLE_CLEAR_14);
+
+    // 迴路模式下寫入的資料會直接出現在接收緩衝
+    io::io_port_wb(base + UART_MCR, MCR_LOOPBACK);
+    io::io_port_wb(base + UART_DATA, 0xAE);
+    if io::io_port_rb(base + UART_DATA) != 0xAE {
+        uart.present = false;
+        return Err(SerialError::NotPresent);
+    }
+
+    io::io_port_wb(base + UART_MCR, MCR_NORMAL);
+
+    uart.present = true;
+    uart.rx_head = 0;
+    uart.rx_len = 0;
+
+    Ok(())
+}
+
+/// 註冊接收中斷
+///
+/// 需在 PIC 初始化之後呼叫，只對已初始化的串口啟用
+pub fn serial_irq_init() {
+    let mut irq_used = [false; 2];
+
+    for (index, uart) in SERIAL_PORTS.iter().enumerate() {
+        let port = ComPort::from_index(index).unwrap();
+        if !uart.lock().present {
+            continue;
+        }
+
+        let line = (port.irq() - 3) as usize;
+        if !irq_used[line] {
+            irq_used[line] = interrupt::register_irq_handler(port.irq(), serial_irq_handler);
+        }
+
+        io::io_port_wb(port.base() + UART_IER, IER_RX_AVAILABLE);
+    }
+}
+
+/// IRQ3/IRQ4 處理，收取共用該 IRQ 線的所有串口資料
+fn serial_irq_handler(frame: &mut TrapFrame) {
+    let irq = (frame.vector() - pic::PIC1_VECTOR_BASE as u32) as u8;
+
+    for (index, uart) in SERIAL_PORTS.iter().enumerate() {
+        let port = ComPort::from_index(index).unwrap();
+        if port.irq() != irq {
+            continue;
+        }
+
+        let mut uart = uart.lock();
+        if !uart.present {
+            continue;
+        }
+
+        while io::io_port_rb(port.base() + UART_LSR) & LSR_DATA_READY != 0 {
+            let byte = io::io_port_rb(port.base() + UART_DATA);
+            uart.push_rx(byte);
+        }
+    }
+}
+
+/// 輪詢發送一個位元組
+#[inline]
+fn write_byte_raw(base: u16, byte: u8) {
+    while io::io_port_rb(base + UART_LSR) & LSR_THR_EMPTY == 0 {
+        core::hint::spin_loop();
+    }
+    io::io_port_wb(base + UART_DATA, byte);
+}
+
+/// 發送一個位元組
+///
+/// # 參數
+/// * `port` - 串口
+/// * `byte` - 資料
+#[allow(dead_code)]
+pub fn serial_write_byte(port: ComPort, byte: u8) -> Result<(), SerialError> {
+    let uart = SERIAL_PORTS[port.index()].lock();
+    if !uart.present {
+        return Err(SerialError::NotInitialized);
+    }
+
+    write_byte_raw(port.base(), byte);
+    Ok(())
+}
+
+/// 發送字串，`\n` 轉換為 `\r\n`
+///
+/// 未初始化的串口會被忽略
+#[allow(dead_code)]
+pub fn serial_write_str(port: ComPort, s: &str) {
+    let uart = SERIAL_PORTS[port.index()].lock();
+    if !uart.present {
+        return;
+    }
+
+    for byte in s.bytes() {
+        if byte == b'\n' {
+            write_byte_raw(port.base(), b'\r');
+        }
+        write_byte_raw(port.base(), byte);
+    }
+}
+
+/// 從接收緩衝取出一個位元組
+///
+/// # 返回
+/// 緩衝區為空或串口未初始化時返回 `None`
+#[allow(dead_code)]
+pub fn serial_read_byte(port: ComPort) -> Option<u8> {
+    SERIAL_PORTS[port.index()].lock().pop_rx()
+}
+
+/// 接收緩衝已滿而被丟棄的位元組數
+#[allow(dead_code)]
+pub fn serial_rx_dropped(port: ComPort) -> usize {
+    SERIAL_PORTS[port.index()].lock().rx_dropped
+}
+
+/// 設置控制台輸出的串口鏡像
+///
+/// # 參數
+/// * `port` - 鏡像目標，`None` 關閉鏡像
+pub fn serial_set_mirror(port: Option<ComPort>) {
+    unsafe {
+        SERIAL_MIRROR = port;
+    }
+}
+
+/// 從內核命令列解析 `console=ttyS<n>`
+///
+/// # 返回
+/// 指定的串口，沒有或格式錯誤時返回 `None`
+pub fn serial_console_from_cmdline(cmdline: &str) -> Option<ComPort> {
+    cmdline
+        .split_whitespace()
+        .filter_map(|arg| arg.strip_prefix("console=ttyS"))
+        .filter_map(|n| n.split(',').next()?.parse::<usize>().ok())
+        .find_map(ComPort::from_index)
+}
+
+/// 將控制台輸出寫入鏡像串口（未啟用時不做任何事）
+pub fn serial_mirror_str(s: &str) {
+    if let Some(port) = unsafe { SERIAL_MIRROR } {
+        serial_write_str(port, s);
+    }
+}
+
+/// 強制釋放所有串口的鎖
+///
+/// # Safety
+/// 只能在持有者已不可能繼續執行時使用（panic 路徑）
+pub unsafe fn serial_force_unlock() {
+    for port in SERIAL_PORTS.iter() {
+        port.force_unlock();
+    }
+}
+
+/// COM1 的格式化輸出
+#[allow(dead_code)]
+pub struct SerialWriter;
+
+impl fmt::Write for SerialWriter {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        serial_write_str(ComPort::Com1, s);
+        Ok(())
+    }
+}
+
+#[allow(dead_code)]
+pub fn _serial_print(args: fmt::Arguments) {
+    use core::fmt::Write;
+    SerialWriter.write_fmt(args).unwrap();
+}
+
+#[macro_export]
+macro_rules! serial_print {
+    ($($arg:tt)*) => ($crate::drivers::serial::_serial_print(format_args!($($arg)*)));
+}
+
+#[macro_export]
+macro_rules! serial_println {
+    () => ($crate::serial_print!("\n"));
+    ($($arg:tt)*) => ($crate::serial_print!("{}\n", format_args!($($arg)*)));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test_case]
+    fn console_from_cmdline() {
+        assert_eq!(serial_console_from_cmdline("quiet console=ttyS1,115200n8"), Some(ComPort::Com2));
+        assert_eq!(serial_console_from_cmdline("console=ttyS0"), Some(ComPort::Com1));
+        assert_eq!(serial_console_from_cmdline("console=tty0"), None);
+        assert_eq!(serial_console_from_cmdline("console=ttyS7"), None);
+    }
+}
@@ -0,0 +1,128 @@
+// src/drivers/vga/font.rs
+use core::fmt;
+use crate::hal::io;
+use crate::kernel::tty::tty;
+use crate::mm;
+use super::psf::{self, PsfError};
+
+// 定序器 (Sequencer) 與圖形控制器 (Graphics Controller) 端口
+const VGA_SEQ_INDEX: u16 = 0x3C4;
+const VGA_SEQ_DATA: u16 = 0x3C5;
+const VGA_GC_INDEX: u16 = 0x3CE;
+const VGA_GC_DATA: u16 = 0x3CF;
+
+// 定序器寄存器
+const SEQ_RESET: u8 = 0x00;
+const SEQ_MAP_MASK: u8 = 0x02;
+const SEQ_MEMORY_MODE: u8 = 0x04;
+
+// 圖形控制器寄存器
+const GC_READ_MAP: u8 = 0x04;
+const GC_MODE: u8 = 0x05;
+const GC_MISC: u8 = 0x06;
+
+/// 存取字型平面時 VGA 記憶體映射到的物理地址
+const VGA_FONT_PADDR: usize = 0xA0000;
+/// 每個字形在字型平面中佔用的位元組
+const VGA_GLYPH_STRIDE: usize = 32;
+/// 字型平面可容納的字形數
+const VGA_FONT_GLYPHS: usize = 256;
+
+/// 80x25 文字模式的字元高度
+pub const VGA_FONT_HEIGHT: usize = 16;
+
+/// 字型載入錯誤
+#[allow(dead_code)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VgaFontError {
+    /// 字型檔格式錯誤
+    Psf(PsfError),
+    /// 字形高於文字模式的字元高度，附帶高度
+    TooTall(usize),
+}
+
+impl fmt::Display for VgaFontError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            VgaFontError::Psf(e) => write!(f, "{}", e),
+            VgaFontError::TooTall(h) => write!(f, "glyph height {} exceeds {}", h, VGA_FONT_HEIGHT),
+        }
+    }
+}
+
+impl From<PsfError> for VgaFontError {
+    fn from(e: PsfError) -> Self {
+        VgaFontError::Psf(e)
+    }
+}
+
+fn vga_seq_write(index: u8, value: u8) {
+    io::io_port_wb(VGA_SEQ_INDEX, index);
+    io::io_port_wb(VGA_SEQ_DATA, value);
+}
+
+fn vga_gc_write(index: u8, value: u8) {
+    io::io_port_wb(VGA_GC_INDEX, index);
+    io::io_port_wb(VGA_GC_DATA, value);
+}
+
+/// 將第 2 平面（字型平面）映射到 0xA0000 以便寫入
+fn vga_font_plane_begin() {
+    vga_seq_write(SEQ_RESET, 0x01);
+    vga_seq_write(SEQ_MAP_MASK, 0x04);
+    // 循序存取，關閉奇偶定址
+    vga_seq_write(SEQ_MEMORY_MODE, 0x07);
+    vga_seq_write(SEQ_RESET, 0x03);
+
+    vga_gc_write(GC_READ_MAP, 0x02);
+    vga_gc_write(GC_MODE, 0x00);
+    // 0xA0000-0xAFFFF，圖形模式關閉
+    vga_gc_write(GC_MISC, 0x04);
+}
+
+/// 還原模式 3 的文字模式設定
+fn vga_font_plane_end() {
+    vga_seq_write(SEQ_RESET, 0x01);
+    vga_seq_write(SEQ_MAP_MASK, 0x03);
+    vga_seq_write(SEQ_MEMORY_MODE, 0x03);
+    vga_seq_write(SEQ_RESET, 0x03);
+
+    vga_gc_write(GC_READ_MAP, 0x00);
+    vga_gc_write(GC_MODE, 0x10);
+    // 0xB8000-0xBFFFF，奇偶定址
+    vga_gc_write(GC_MISC, 0x0E);
+}
+
+/// 從 PSF 字型檔載入文字模式字型
+///
+/// 字形依代碼頁 437 排列，只載入前 256 個；矮於 16 行的字形下方補空白，
+/// 畫面維持 80x25。需在文字模式 3 下呼叫
+///
+/// # 參數
+/// * `data` - PSF1 或 PSF2 字型檔內容
+///
+/// # 返回
+/// 載入的字形數
+pub fn vga_font_load(data: &[u8]) -> Result<usize, VgaFontError> {
+    let font = psf::psf_parse(data)?;
+    if font.height() > VGA_FONT_HEIGHT {
+        return Err(VgaFontError::TooTall(font.height()));
+    }
+
+    let count = font.count().min(VGA_FONT_GLYPHS);
+    let plane = mm::phys_to_virt(VGA_FONT_PADDR) as *mut u8;
+
+    // 存取字型平面期間文字緩衝區不可用，持有 TTY 鎖阻止其他輸出
+    let _guard = tty::tty_lock();
+    vga_font_plane_begin();
+    for index in 0..count {
+        let glyph = font.glyph(index).unwrap_or(&[]);
+        for row in 0..VGA_GLYPH_STRIDE {
+            let bits = glyph.get(row).copied().unwrap_or(0);
+            unsafe { plane.add(index * VGA_GLYPH_STRIDE + row).write_volatile(bits) };
+        }
+    }
+    vga_font_plane_end();
+
+    Ok(count)
+}
@@ -0,0 +1,8 @@
+// src/drivers/vga/mod.rs
+
+#[cfg(not(feature = "std"))]
+mod font;
+pub mod psf;
+
+#[cfg(not(feature = "std"))]
+pub use font::*;
@@ -0,0 +1,161 @@
+// src/drivers/vga/psf.rs
+//! PC Screen Font（PSF1 與 PSF2）點陣字型解析
+use core::fmt;
+
+const PSF1_MAGIC: [u8; 2] = [0x36, 0x04];
+const PSF1_HEADER_SIZE: usize = 4;
+/// PSF1 模式位元：字型含 512 個字形
+const PSF1_MODE_512: u8 = 0x01;
+
+const PSF2_MAGIC: [u8; 4] = [0x72, 0xB5, 0x4A, 0x86];
+const PSF2_HEADER_SIZE: usize = 32;
+
+/// 字形最大寬度，VGA 文字模式每行只有 8 個像素
+pub const PSF_MAX_WIDTH: usize = 8;
+
+/// PSF 解析錯誤
+#[allow(dead_code)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PsfError {
+    /// 不是 PSF1 或 PSF2 字型
+    BadMagic,
+    /// 檔案比標頭描述的短
+    Truncated,
+    /// 標頭中的字形大小與高度不符
+    BadHeader,
+    /// 字形寬度超過 8 像素，附帶寬度
+    UnsupportedWidth(usize),
+}
+
+impl fmt::Display for PsfError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PsfError::BadMagic => write!(f, "not a PSF font"),
+            PsfError::Truncated => write!(f, "font data truncated"),
+            PsfError::BadHeader => write!(f, "inconsistent font header"),
+            PsfError::UnsupportedWidth(w) => write!(f, "unsupported glyph width {}", w),
+        }
+    }
+}
+
+/// 點陣字型，字形依代碼頁 437 排列，每行一個位元組
+#[allow(dead_code)]
+#[derive(Debug, Clone, Copy)]
+pub struct PsfFont<'a> {
+    glyphs: &'a [u8],
+    count: usize,
+    height: usize,
+    width: usize,
+}
+
+impl<'a> PsfFont<'a> {
+    /// 字形數量
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    /// 字形高度（像素）
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// 字形寬度（像素）
+    #[allow(dead_code)]
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// 第 `index` 個字形的點陣，每行一個位元組，最高位為最左邊的像素
+    pub fn glyph(&self, index: usize) -> Option<&'a [u8]> {
+        if index >= self.count {
+            return None;
+        }
+        self.glyphs.get(index * self.height..(index + 1) * self.height)
+    }
+}
+
+fn read_u32(data: &[u8], offset: usize) -> usize {
+    u32::from_le_bytes([data[offset], data[offset + 1], data[offset + 2], data[offset + 3]]) as usize
+}
+
+/// 解析 PSF1 或 PSF2 字型
+///
+/// Unicode 對照表被忽略，字形假定依代碼頁 437 排列
+///
+/// # 參數
+/// * `data` - 字型檔內容
+pub fn psf_parse(data: &[u8]) -> Result<PsfFont<'_>, PsfError> {
+    let (header_size, count, glyph_size, height, width) = if data.starts_with(&PSF1_MAGIC) {
+        if data.len() < PSF1_HEADER_SIZE {
+            return Err(PsfError::Truncated);
+        }
+        let count = if data[2] & PSF1_MODE_512 != 0 { 512 } else { 256 };
+        let height = data[3] as usize;
+        (PSF1_HEADER_SIZE, count, height, height, 8)
+    } else if data.starts_with(&PSF2_MAGIC) {
+        if data.len() < PSF2_HEADER_SIZE {
+            return Err(PsfError::Truncated);
+        }
+        (read_u32(data, 8), read_u32(data, 16), read_u32(data, 20), read_u32(data, 24), read_u32(data, 28))
+    } else {
+        return Err(PsfError::BadMagic);
+    };
+
+    if width > PSF_MAX_WIDTH {
+        return Err(PsfError::UnsupportedWidth(width));
+    }
+    // 寬度不超過 8 時每行一個位元組
+    if glyph_size != height {
+        return Err(PsfError::BadHeader);
+    }
+
+    let end = count
+        .checked_mul(glyph_size)
+        .and_then(|size| size.checked_add(header_size))
+        .ok_or(PsfError::Truncated)?;
+    let glyphs = data.get(header_size..end).ok_or(PsfError::Truncated)?;
+
+    Ok(PsfFont { glyphs, count, height, width })
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+
+    fn psf1(height: u8) -> Vec<u8> {
+        let mut data = vec![0x36, 0x04, 0x00, height];
+        for glyph in 0..256 {
+            data.extend(std::iter::repeat(glyph as u8).take(height as usize));
+        }
+        data
+    }
+
+    #[test]
+    fn parse_psf1() {
+        let data = psf1(16);
+        let font = psf_parse(&data).unwrap();
+        assert_eq!((font.count(), font.width(), font.height()), (256, 8, 16));
+        assert_eq!(font.glyph(0x41), Some(&[0x41; 16][..]));
+        assert_eq!(font.glyph(256), None);
+
+        assert_eq!(psf_parse(&data[..100]).unwrap_err(), PsfError::Truncated);
+    }
+
+    #[test]
+    fn parse_psf2() {
+        let mut data = Vec::from(PSF2_MAGIC);
+        // version, headersize, flags, length, charsize, height, width
+        for field in [0u32, 32, 0, 2, 14, 14, 7] {
+            data.extend(field.to_le_bytes());
+        }
+        data.extend([0xAA; 28]);
+
+        let font = psf_parse(&data).unwrap();
+        assert_eq!((font.count(), font.width(), font.height()), (2, 7, 14));
+        assert_eq!(font.glyph(1), Some(&[0xAA; 14][..]));
+
+        data[28] = 12;
+        assert_eq!(psf_parse(&data).unwrap_err(), PsfError::UnsupportedWidth(12));
+        assert_eq!(psf_parse(b"font").unwrap_err(), PsfError::BadMagic);
+    }
+}
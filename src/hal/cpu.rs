@@ -11,11 +11,14 @@ use x86::controlregs::{
     Cr0, 
     Cr4
 };
+use x86::debugregs::{dr6, dr6_write, Dr6};
 use x86::time::rdtsc;
+use x86::tlb;
 use x86::cpuid;
 use core::hint::spin_loop;
 use x86::irq::{enable, disable};
 use x86::halt;
+use x86::bits32::eflags::{self, EFlags};
 use core::arch::asm;
 
 /// 32 位元暫存器類型
@@ -26,17 +29,21 @@ pub type Reg32 = u32;
 pub type Reg16 = u16;
 
 /// 通用目的暫存器結構
+/// 
+/// 欄位順序與 `pushal` 的壓棧結果一致（低地址在前）
 #[allow(dead_code)]
-#[repr(C, packed)]
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
 pub struct GpRegs {
-    pub eax: Reg32,
-    pub ebx: Reg32,
-    pub ecx: Reg32,
-    pub edx: Reg32,
     pub edi: Reg32,
-    pub ebp: Reg32,
     pub esi: Reg32,
+    pub ebp: Reg32,
+    /// `pushal` 時的 ESP，`popal` 會忽略此值
     pub esp: Reg32,
+    pub ebx: Reg32,
+    pub edx: Reg32,
+    pub ecx: Reg32,
+    pub eax: Reg32,
 }
 
 /// 段暫存器結構
@@ -120,6 +127,70 @@ pub fn cpu_w_cr4(val: Cr4) {
     unsafe { cr4_write(val); }
 }
 
+/// 讀取 DR6 除錯狀態暫存器
+#[allow(dead_code)]
+#[inline]
+pub fn cpu_r_dr6() -> Dr6 {
+    unsafe { dr6() }
+}
+
+/// 寫入 DR6 除錯狀態暫存器
+///
+/// CPU 不會自動清除 DR6 的狀態位，#DB 處理完畢後需要手動清除
+#[allow(dead_code)]
+#[inline]
+pub fn cpu_w_dr6(val: Dr6) {
+    unsafe { dr6_write(val); }
+}
+
+/// 讀取 EFLAGS 暫存器
+#[allow(dead_code)]
+#[inline]
+pub fn cpu_r_eflags() -> EFlags {
+    unsafe { eflags::read() }
+}
+
+/// 讀取當前的 EBP
+///
+/// 內聯到呼叫者中，反映的是呼叫者的堆疊框架
+#[allow(dead_code)]
+#[inline(always)]
+pub fn cpu_r_ebp() -> Reg32 {
+    let value: Reg32;
+    unsafe {
+        asm!("mov {0:e}, ebp", out(reg) value, options(nomem, nostack, preserves_flags));
+    }
+    value
+}
+
+/// 讀取當前的 ESP
+#[allow(dead_code)]
+#[inline(always)]
+pub fn cpu_r_esp() -> Reg32 {
+    let value: Reg32;
+    unsafe {
+        asm!("mov {0:e}, esp", out(reg) value, options(nomem, nostack, preserves_flags));
+    }
+    value
+}
+
+/// 使指定虛擬地址的 TLB 條目失效
+/// 
+/// # 參數
+/// * `addr` - 虛擬地址
+#[allow(dead_code)]
+#[inline]
+pub fn cpu_invlpg(addr: usize) {
+    unsafe { tlb::flush(addr); }
+}
+
+/// 重新載入 CR3 以清空整個 TLB
+#[allow(dead_code)]
+#[inline]
+pub fn cpu_flush_tlb() {
+    unsafe { tlb::flush_all(); }
+}
+
 /// 獲取 CPU 型號
 /// 
 /// # 參數
@@ -244,4 +315,32 @@ pub fn cpu_disable_interrupts() {
     unsafe {
         disable();
     }
+}
+
+/// 檢查中斷是否已啟用 (EFLAGS.IF)
+#[allow(dead_code)]
+#[inline]
+pub fn cpu_interrupts_enabled() -> bool {
+    unsafe { eflags::read().contains(EFlags::FLAGS_IF) }
+}
+
+/// 禁用中斷並返回之前的中斷狀態
+/// 
+/// # 返回
+/// 呼叫前中斷是否啟用，交給 `cpu_restore_interrupts` 還原
+#[allow(dead_code)]
+#[inline]
+pub fn cpu_save_interrupts() -> bool {
+    let enabled = cpu_interrupts_enabled();
+    cpu_disable_interrupts();
+    enabled
+}
+
+/// 還原由 `cpu_save_interrupts` 保存的中斷狀態
+#[allow(dead_code)]
+#[inline]
+pub fn cpu_restore_interrupts(enabled: bool) {
+    if enabled {
+        cpu_enable_interrupts();
+    }
 }
\ No newline at end of file
@@ -85,4 +85,13 @@ pub fn io_port_rw(port: u16) -> u16 {
     unsafe {
         x86::io::inw(port)
     }
-}
\ No newline at end of file
+}
+
+/// 短暫延遲，等待較慢的舊式設備（如 8259 PIC）處理完上一個 I/O 操作
+/// 
+/// 透過寫入未使用的 POST 診斷端口 0x80 實現
+#[allow(dead_code)]
+#[inline]
+pub fn io_delay() {
+    io_port_wb(0x80, 0);
+}
@@ -0,0 +1,43 @@
+// src/hal/mock/cpu.rs
+//! 主機端測試使用的模擬 CPU 操作
+//!
+//! 只提供與硬體無關的代碼所需的部分，中斷標誌以每個執行緒的變數模擬
+use std::cell::Cell;
+
+std::thread_local! {
+    static INTERRUPTS: Cell<bool> = const { Cell::new(false) };
+}
+
+pub fn cpu_pause() {
+    core::hint::spin_loop();
+}
+
+pub fn cpu_halt() {
+    std::thread::yield_now();
+}
+
+pub fn cpu_idle() {
+    std::thread::yield_now();
+}
+
+pub fn cpu_enable_interrupts() {
+    INTERRUPTS.with(|f| f.set(true));
+}
+
+pub fn cpu_disable_interrupts() {
+    INTERRUPTS.with(|f| f.set(false));
+}
+
+pub fn cpu_interrupts_enabled() -> bool {
+    INTERRUPTS.with(|f| f.get())
+}
+
+pub fn cpu_save_interrupts() -> bool {
+    INTERRUPTS.with(|f| f.replace(false))
+}
+
+pub fn cpu_restore_interrupts(enabled: bool) {
+    if enabled {
+        cpu_enable_interrupts();
+    }
+}
@@ -0,0 +1,74 @@
+// src/hal/mock/io.rs
+//! 主機端測試使用的模擬端口 I/O
+//!
+//! 寫入的值依序記錄下來，讀取時從預先放入的隊列取值，隊列為空時返回全 1
+//! （與讀取不存在的設備一致）。狀態為每個執行緒獨立，測試可以並行執行
+use std::cell::RefCell;
+use std::collections::{HashMap, VecDeque};
+use std::vec::Vec;
+
+#[derive(Default)]
+struct MockPorts {
+    writes: Vec<(u16, u32)>,
+    reads: HashMap<u16, VecDeque<u32>>,
+}
+
+std::thread_local! {
+    static PORTS: RefCell<MockPorts> = RefCell::new(MockPorts::default());
+}
+
+fn port_write(port: u16, value: u32) {
+    PORTS.with(|p| p.borrow_mut().writes.push((port, value)));
+}
+
+fn port_read(port: u16) -> Option<u32> {
+    PORTS.with(|p| p.borrow_mut().reads.get_mut(&port).and_then(|q| q.pop_front()))
+}
+
+/// 清空寫入記錄與讀取隊列
+pub fn mock_io_reset() {
+    PORTS.with(|p| *p.borrow_mut() = MockPorts::default());
+}
+
+/// 放入下一次從端口讀取時返回的值
+///
+/// # 參數
+/// * `port` - 端口號
+/// * `value` - 讀取時返回的值
+pub fn mock_io_push_read(port: u16, value: u32) {
+    PORTS.with(|p| p.borrow_mut().reads.entry(port).or_default().push_back(value));
+}
+
+/// 取出目前為止的寫入記錄
+///
+/// # 返回
+/// 依寫入順序排列的 `(端口號, 值)`
+pub fn mock_io_take_writes() -> Vec<(u16, u32)> {
+    PORTS.with(|p| std::mem::take(&mut p.borrow_mut().writes))
+}
+
+pub fn io_port_wb(port: u16, value: u8) {
+    port_write(port, value as u32);
+}
+
+pub fn io_port_wl(port: u16, value: u32) {
+    port_write(port, value);
+}
+
+pub fn io_port_rb(port: u16) -> u8 {
+    port_read(port).map_or(0xFF, |v| v as u8)
+}
+
+pub fn io_port_rl(port: u16) -> u32 {
+    port_read(port).unwrap_or(0xFFFF_FFFF)
+}
+
+pub fn io_port_ww(port: u16, value: u16) {
+    port_write(port, value as u32);
+}
+
+pub fn io_port_rw(port: u16) -> u16 {
+    port_read(port).map_or(0xFFFF, |v| v as u16)
+}
+
+pub fn io_delay() {}
@@ -1,4 +1,16 @@
+#[cfg(not(feature = "std"))]
 pub mod io;
+#[cfg(not(feature = "std"))]
+pub mod cpu;
+#[cfg(not(feature = "std"))]
+pub mod qemu;
+
+// 主機端測試以模擬實作替換端口 I/O 與 CPU 操作
+#[cfg(feature = "std")]
+#[path = "mock/io.rs"]
+pub mod io;
+#[cfg(feature = "std")]
+#[path = "mock/cpu.rs"]
 pub mod cpu;
 
 pub use io::io_port_wb;
@@ -6,15 +18,25 @@ pub use io::io_port_wl;
 pub use io::io_port_rb;
 pub use io::io_port_rl;
 
+#[cfg(not(feature = "std"))]
 pub use cpu::cpu_r_cr0;
+#[cfg(not(feature = "std"))]
 pub use cpu::cpu_r_cr2;
+#[cfg(not(feature = "std"))]
 pub use cpu::cpu_r_cr3;
+#[cfg(not(feature = "std"))]
 pub use cpu::cpu_w_cr0;
+#[cfg(not(feature = "std"))]
 pub use cpu::cpu_w_cr2;
+#[cfg(not(feature = "std"))]
 pub use cpu::cpu_w_cr3;
+#[cfg(not(feature = "std"))]
 pub use cpu::cpu_get_model;
+#[cfg(not(feature = "std"))]
 pub use cpu::cpu_brand_string_supported;
+#[cfg(not(feature = "std"))]
 pub use cpu::cpu_get_brand;
+#[cfg(not(feature = "std"))]
 pub use cpu::cpu_rdtsc;
 pub use cpu::cpu_pause;
 pub use cpu::cpu_halt;
@@ -0,0 +1,32 @@
+// src/hal/qemu.rs
+use crate::hal::{cpu, io};
+
+/// QEMU `isa-debug-exit` 裝置的 I/O 端口
+pub const QEMU_EXIT_PORT: u16 = 0xf4;
+
+/// 退出碼
+///
+/// QEMU 的實際退出狀態為 `(code << 1) | 1`
+#[allow(dead_code)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u32)]
+pub enum QemuExitCode {
+    Success = 0x10,
+    Failed = 0x11,
+}
+
+/// 通過 `isa-debug-exit` 裝置關閉 QEMU
+///
+/// 未掛載該裝置（例如實體機）時寫入無效，之後停機
+///
+/// # 參數
+/// * `code` - 退出碼
+#[allow(dead_code)]
+pub fn qemu_exit(code: QemuExitCode) -> ! {
+    io::io_port_wl(QEMU_EXIT_PORT, code as u32);
+
+    loop {
+        cpu::cpu_disable_interrupts();
+        cpu::cpu_halt();
+    }
+}
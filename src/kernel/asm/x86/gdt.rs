@@ -2,9 +2,11 @@
 use core::mem;
 use core::ptr;
 use x86::segmentation::{self, Descriptor, DataSegmentType, CodeSegmentType, SegmentSelector};
-use x86::segmentation::{SegmentDescriptorBuilder, BuildDescriptor};
+use x86::segmentation::{DescriptorBuilder, SegmentDescriptorBuilder, GateDescriptorBuilder, BuildDescriptor};
 use x86::dtables::{DescriptorTablePointer, lgdt};
 use x86::Ring;
+#[cfg(not(feature = "std"))]
+use crate::kernel::asm::x86::tss;
 
 #[allow(dead_code)]
 pub const SEG_DATA_RD: u64 = 0x00; // Read-Only
@@ -39,7 +41,7 @@ pub const SEG_CODE_EXRDC: u64 = 0x0E; // Execute/Read, conforming
 #[allow(dead_code)]
 pub const SEG_CODE_EXRDCA: u64 = 0x0F; // Execute/Read, conforming, accessed
 
-pub const GDT_ENTRY_COUNT: usize = 5;
+pub const GDT_ENTRY_COUNT: usize = 7;
 
 #[no_mangle]
 pub static mut _GDT: [Descriptor; GDT_ENTRY_COUNT] = [Descriptor::NULL; GDT_ENTRY_COUNT];
@@ -47,57 +49,92 @@ pub static mut _GDT: [Descriptor; GDT_ENTRY_COUNT] = [Descriptor::NULL; GDT_ENTR
 #[no_mangle]
 pub static mut _GDT_LIMIT: u16 = (mem::size_of::<[Descriptor; GDT_ENTRY_COUNT]>() - 1) as u16;
 
+/// 建立 TSS 的 GDT 描述符
+///
+/// # 參數
+/// * `base` - TSS 的線性地址
+/// * `limit` - TSS 的大小減 1
+pub fn gdt_tss_descriptor(base: u32, limit: u32) -> Descriptor {
+    <DescriptorBuilder as GateDescriptorBuilder<u32>>::tss_descriptor(base as u64, limit as u64, true)
+        .present()
+        .dpl(Ring::Ring0)
+        .finish()
+}
+
+/// 建立平坦模型的 GDT
+///
+/// 與硬體無關，`_init_gdt` 以它填寫 `_GDT`
+///
+/// # 參數
+/// * `kernel_tss` - 內核 TSS 描述符
+/// * `double_fault_tss` - 雙重錯誤 TSS 描述符
+/// # 返回
+/// 依 `segment` 中選擇子順序排列的描述符
+pub fn gdt_build(kernel_tss: Descriptor, double_fault_tss: Descriptor) -> [Descriptor; GDT_ENTRY_COUNT] {
+    let mut gdt = [Descriptor::NULL; GDT_ENTRY_COUNT];
+
+    gdt[1] = segmentation::DescriptorBuilder::code_descriptor(
+            0,
+            0xFFFFF,
+            CodeSegmentType::ExecuteRead
+        )
+        .present()
+        .dpl(Ring::Ring0)
+        .db()
+        .limit_granularity_4kb()
+        .finish();
+
+    gdt[2] = segmentation::DescriptorBuilder::data_descriptor(
+            0,
+            0xFFFFF,
+            DataSegmentType::ReadWrite
+        )
+        .present()
+        .dpl(Ring::Ring0) 
+        .db() 
+        .limit_granularity_4kb() 
+        .finish();
+    
+    gdt[3] = segmentation::DescriptorBuilder::code_descriptor(
+            0,
+            0xFFFFF,
+            CodeSegmentType::ExecuteRead
+        )
+        .present() 
+        .dpl(Ring::Ring3)
+        .db()
+        .limit_granularity_4kb()
+        .finish();
+    
+    gdt[4] = segmentation::DescriptorBuilder::data_descriptor(
+            0,
+            0xFFFFF,
+            DataSegmentType::ReadWrite
+        )
+        .present()
+        .dpl(Ring::Ring3)
+        .db()
+        .limit_granularity_4kb()
+        .finish();
+
+    // 內核 TSS 與雙重錯誤 TSS
+    gdt[5] = kernel_tss;
+    gdt[6] = double_fault_tss;
+
+    gdt
+}
+
+#[cfg(not(feature = "std"))]
 #[no_mangle]
 pub extern "C" fn _init_gdt() {
+    let (kernel_tss, double_fault_tss) = tss::tss_init();
+
     unsafe {
-        _GDT[0] = Descriptor::NULL;
-        
-        _GDT[1] = segmentation::DescriptorBuilder::code_descriptor(
-                0,
-                0xFFFFF,
-                CodeSegmentType::ExecuteRead
-            )
-            .present()
-            .dpl(Ring::Ring0)
-            .db()
-            .limit_granularity_4kb()
-            .finish();
-
-        _GDT[2] = segmentation::DescriptorBuilder::data_descriptor(
-                0,
-                0xFFFFF,
-                DataSegmentType::ReadWrite
-            )
-            .present()
-            .dpl(Ring::Ring0) 
-            .db() 
-            .limit_granularity_4kb() 
-            .finish();
-        
-        _GDT[3] = segmentation::DescriptorBuilder::code_descriptor(
-                0,
-                0xFFFFF,
-                CodeSegmentType::ExecuteRead
-            )
-            .present() 
-            .dpl(Ring::Ring3)
-            .db()
-            .limit_granularity_4kb()
-            .finish();
-        
-        _GDT[4] = segmentation::DescriptorBuilder::data_descriptor(
-                0,
-                0xFFFFF,
-                DataSegmentType::ReadWrite
-            )
-            .present()
-            .dpl(Ring::Ring3)
-            .db()
-            .limit_granularity_4kb()
-            .finish();
+        _GDT = gdt_build(kernel_tss, double_fault_tss);
     }
 }
 
+#[cfg(not(feature = "std"))]
 #[no_mangle]
 pub extern "C" fn _load_gdt() {
     unsafe {
@@ -109,4 +146,37 @@ pub extern "C" fn _load_gdt() {
         };
         lgdt(&gdtr);
     }
-}
\ No newline at end of file
+
+    tss::tss_load();
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn flat_segments() {
+        let gdt = gdt_build(Descriptor::NULL, Descriptor::NULL);
+
+        assert_eq!(gdt[0].as_u64(), 0);
+        assert_eq!(gdt[1].as_u64(), 0x00CF_9A00_0000_FFFF);
+        assert_eq!(gdt[2].as_u64(), 0x00CF_9200_0000_FFFF);
+        assert_eq!(gdt[3].as_u64(), 0x00CF_FA00_0000_FFFF);
+        assert_eq!(gdt[4].as_u64(), 0x00CF_F200_0000_FFFF);
+    }
+
+    #[test]
+    fn tss_descriptor_layout() {
+        let tss = gdt_tss_descriptor(0x1234_5678, 0x67);
+        // 32 位可用 TSS（類型 0x9），存在，DPL 0，位元組粒度
+        assert_eq!(tss.as_u64(), 0x1200_8934_5678_0067);
+
+        let gdt = gdt_build(tss, Descriptor::NULL);
+        assert_eq!(gdt[5].as_u64(), tss.as_u64());
+    }
+
+    #[test]
+    fn limit_covers_table() {
+        assert_eq!(unsafe { _GDT_LIMIT } as usize + 1, GDT_ENTRY_COUNT * 8);
+    }
+}
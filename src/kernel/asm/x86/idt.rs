@@ -14,7 +14,7 @@ use x86::Ring;
 use x86::irq::{self, EXCEPTIONS};
 use x86::segmentation::GateDescriptorBuilder;
 use x86::segmentation::TaskGateDescriptorBuilder;
-use crate::kernel::asm::x86::{interrupt, segment};
+use crate::kernel::asm::x86::{interrupt, pic, segment};
 
 pub const IDT_ENTRY_COUNT: usize = 256;
 
@@ -30,7 +30,7 @@ pub type ExceptionHandlerWithErrorCode = fn(error_code: u32) -> ();
 #[no_mangle]
 pub fn _set_interrupt_handler(vector: u8, selector: SegmentSelector, handler: InterruptHandler, dpl: Ring) {
     unsafe {
-        let desc = DescriptorBuilder::interrupt_descriptor(selector, handler as u32)
+        let desc = DescriptorBuilder::interrupt_descriptor(selector, handler as usize as u32)
             .present() 
             .dpl(dpl)
             .finish();
@@ -42,7 +42,7 @@ pub fn _set_interrupt_handler(vector: u8, selector: SegmentSelector, handler: In
 #[no_mangle]
 pub fn _set_interrupt_err_handler(vector: u8, selector: SegmentSelector, handler: ExceptionHandlerWithErrorCode, dpl: Ring) {
     unsafe {
-        let desc = DescriptorBuilder::interrupt_descriptor(selector, handler as u32)
+        let desc = DescriptorBuilder::interrupt_descriptor(selector, handler as usize as u32)
             .present() 
             .dpl(dpl)
             .finish();
@@ -54,7 +54,7 @@ pub fn _set_interrupt_err_handler(vector: u8, selector: SegmentSelector, handler
 #[no_mangle]
 pub fn _set_trap_handler(vector: u8, selector: SegmentSelector, handler: InterruptHandler, dpl: Ring) {
     unsafe {
-        let desc = DescriptorBuilder::trap_gate_descriptor(selector, handler as u32)
+        let desc = DescriptorBuilder::trap_gate_descriptor(selector, handler as usize as u32)
             .present() 
             .dpl(dpl)
             .finish();
@@ -96,6 +96,7 @@ pub fn _load_idt() {
         lidt(&idtr);
 
         _setup_idt();
+        pic::pic_init();
     }
 }
 
@@ -134,6 +135,22 @@ pub fn _setup_idt() {
         fn _asm_isr29();
         fn _asm_isr30();
         fn _asm_isr31();
+        fn _asm_isr32();
+        fn _asm_isr33();
+        fn _asm_isr34();
+        fn _asm_isr35();
+        fn _asm_isr36();
+        fn _asm_isr37();
+        fn _asm_isr38();
+        fn _asm_isr39();
+        fn _asm_isr40();
+        fn _asm_isr41();
+        fn _asm_isr42();
+        fn _asm_isr43();
+        fn _asm_isr44();
+        fn _asm_isr45();
+        fn _asm_isr46();
+        fn _asm_isr47();
     }
 
     _set_interrupt_handler(irq::DIVIDE_ERROR_VECTOR, segment::KERNEL_CODE_SELECTOR, _asm_isr0, Ring::Ring0);
@@ -144,7 +161,8 @@ pub fn _setup_idt() {
     _set_interrupt_handler(irq::BOUND_RANGE_EXCEEDED_VECTOR, segment::KERNEL_CODE_SELECTOR, _asm_isr5, Ring::Ring0);
     _set_interrupt_handler(irq::INVALID_OPCODE_VECTOR, segment::KERNEL_CODE_SELECTOR, _asm_isr6, Ring::Ring0);
     _set_interrupt_handler(irq::DEVICE_NOT_AVAILABLE_VECTOR, segment::KERNEL_CODE_SELECTOR, _asm_isr7, Ring::Ring0);
-    _set_interrupt_handler(irq::DOUBLE_FAULT_VECTOR, segment::KERNEL_CODE_SELECTOR, _asm_isr8, Ring::Ring0);
+    // 雙重錯誤通過任務門切換到獨立的 TSS 與堆疊，即使內核堆疊已損壞也能執行
+    _set_task_gate(irq::DOUBLE_FAULT_VECTOR, segment::DOUBLE_FAULT_TSS_SELECTOR, Ring::Ring0);
     _set_interrupt_handler(irq::COPROCESSOR_SEGMENT_OVERRUN_VECTOR, segment::KERNEL_CODE_SELECTOR, _asm_isr9, Ring::Ring0);
     _set_interrupt_handler(irq::INVALID_TSS_VECTOR, segment::KERNEL_CODE_SELECTOR, _asm_isr10, Ring::Ring0);
     _set_interrupt_handler(irq::SEGMENT_NOT_PRESENT_VECTOR, segment::KERNEL_CODE_SELECTOR, _asm_isr11, Ring::Ring0);
@@ -170,8 +188,27 @@ pub fn _setup_idt() {
     _set_interrupt_handler(30, segment::KERNEL_CODE_SELECTOR, _asm_isr30, Ring::Ring0);
     _set_interrupt_handler(31, segment::KERNEL_CODE_SELECTOR, _asm_isr31, Ring::Ring0);
 
-
-    // for i in 32..IDT_ENTRY_COUNT as u8 {
+    // IRQ 0-7 (主 PIC)
+    _set_interrupt_handler(pic::PIC1_VECTOR_BASE, segment::KERNEL_CODE_SELECTOR, _asm_isr32, Ring::Ring0);
+    _set_interrupt_handler(pic::PIC1_VECTOR_BASE + 1, segment::KERNEL_CODE_SELECTOR, _asm_isr33, Ring::Ring0);
+    _set_interrupt_handler(pic::PIC1_VECTOR_BASE + 2, segment::KERNEL_CODE_SELECTOR, _asm_isr34, Ring::Ring0);
+    _set_interrupt_handler(pic::PIC1_VECTOR_BASE + 3, segment::KERNEL_CODE_SELECTOR, _asm_isr35, Ring::Ring0);
+    _set_interrupt_handler(pic::PIC1_VECTOR_BASE + 4, segment::KERNEL_CODE_SELECTOR, _asm_isr36, Ring::Ring0);
+    _set_interrupt_handler(pic::PIC1_VECTOR_BASE + 5, segment::KERNEL_CODE_SELECTOR, _asm_isr37, Ring::Ring0);
+    _set_interrupt_handler(pic::PIC1_VECTOR_BASE + 6, segment::KERNEL_CODE_SELECTOR, _asm_isr38, Ring::Ring0);
+    _set_interrupt_handler(pic::PIC1_VECTOR_BASE + 7, segment::KERNEL_CODE_SELECTOR, _asm_isr39, Ring::Ring0);
+
+    // IRQ 8-15 (從 PIC)
+    _set_interrupt_handler(pic::PIC2_VECTOR_BASE, segment::KERNEL_CODE_SELECTOR, _asm_isr40, Ring::Ring0);
+    _set_interrupt_handler(pic::PIC2_VECTOR_BASE + 1, segment::KERNEL_CODE_SELECTOR, _asm_isr41, Ring::Ring0);
+    _set_interrupt_handler(pic::PIC2_VECTOR_BASE + 2, segment::KERNEL_CODE_SELECTOR, _asm_isr42, Ring::Ring0);
+    _set_interrupt_handler(pic::PIC2_VECTOR_BASE + 3, segment::KERNEL_CODE_SELECTOR, _asm_isr43, Ring::Ring0);
+    _set_interrupt_handler(pic::PIC2_VECTOR_BASE + 4, segment::KERNEL_CODE_SELECTOR, _asm_isr44, Ring::Ring0);
+    _set_interrupt_handler(pic::PIC2_VECTOR_BASE + 5, segment::KERNEL_CODE_SELECTOR, _asm_isr45, Ring::Ring0);
+    _set_interrupt_handler(pic::PIC2_VECTOR_BASE + 6, segment::KERNEL_CODE_SELECTOR, _asm_isr46, Ring::Ring0);
+    _set_interrupt_handler(pic::PIC2_VECTOR_BASE + 7, segment::KERNEL_CODE_SELECTOR, _asm_isr47, Ring::Ring0);
+
+    // for i in 48..IDT_ENTRY_COUNT as u8 {
     //     _set_interrupt_handler(i, segment::KERNEL_CODE_SELECTOR, interrupt::default_interrupt_handler, Ring::Ring0);
     // }
     
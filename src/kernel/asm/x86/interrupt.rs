@@ -2,31 +2,46 @@ use core::mem;
 use core::ptr;
 use x86::segmentation::{SegmentSelector, Descriptor};
 use x86::Ring;
+use x86::bits32::eflags::EFlags;
+use x86::debugregs::Dr6;
 use x86::irq::{self, PageFaultError, EXCEPTIONS};
 use crate::{print, println};
-use crate::hal::cpu;
-
-#[repr(C, packed)]
-pub struct IsrParam {
-    vector: u32,
-    err_code: u32,
-    eip: u32,
-    cs: u32,
-    eflags: u32,
-    esp: u32,
-    ss: u32,
+use crate::drivers::serial;
+use crate::hal::cpu::{self, GpRegs};
+use crate::kernel::asm::x86::pic;
+use crate::kernel::ksym::Ksym;
+use crate::kernel::panic;
+use crate::kernel::tty::tty;
+use crate::mm::paging;
+
+/// 中斷發生時由 `interrupt_wrapper` 保存的完整上下文
+///
+/// 處理函數對欄位的修改會在 `iret` 時生效
+#[repr(C)]
+pub struct TrapFrame {
+    pub gs: u32,
+    pub fs: u32,
+    pub es: u32,
+    pub ds: u32,
+    pub regs: GpRegs,
+    pub vector: u32,
+    pub err_code: u32,
+    pub eip: u32,
+    pub cs: u32,
+    pub eflags: u32,
+    /// 僅在從用戶態 (ring 3) 進入時由 CPU 壓入
+    pub user_esp: u32,
+    /// 僅在從用戶態 (ring 3) 進入時由 CPU 壓入
+    pub user_ss: u32,
 }
 
-impl IsrParam {
+#[allow(dead_code)]
+impl TrapFrame {
     pub fn vector(&self) -> u32 {
         return self.vector;
     }
 
     pub fn err_code(&self) -> u32 {
-        // unsafe { 
-        //     let ptr = ptr::addr_of!(self.err_code);
-        //     ptr::read_unaligned(ptr)
-        // }
         return self.err_code;
     }
     
@@ -35,68 +50,194 @@ impl IsrParam {
     }
     
     pub fn cs(&self) -> u32 {
-        return self.cs;
+        return self.cs & 0xFFFF;
     }
     
     pub fn eflags(&self) -> u32 {
         return self.eflags;
     }
+
+    /// 中斷是否發生在用戶態
+    pub fn is_user(&self) -> bool {
+        self.cs & 0x3 == 0x3
+    }
+
+    /// 中斷發生時的堆疊指標
+    pub fn esp(&self) -> u32 {
+        if self.is_user() {
+            self.user_esp
+        } else {
+            // 同特權級中斷不切換堆疊，向量號、錯誤碼與 EIP/CS/EFLAGS 之上即為原堆疊
+            self.regs.esp + 2 * 4 + 3 * 4
+        }
+    }
+
+    /// 輸出所有暫存器
+    pub fn dump(&self) {
+        let r = &self.regs;
+        println!("EAX: 0x{:08x} EBX: 0x{:08x} ECX: 0x{:08x} EDX: 0x{:08x}", r.eax, r.ebx, r.ecx, r.edx);
+        println!("ESI: 0x{:08x} EDI: 0x{:08x} EBP: 0x{:08x} ESP: 0x{:08x}", r.esi, r.edi, r.ebp, self.esp());
+        println!("EIP: 0x{:08x} EFLAGS: 0x{:08x} {}", self.eip, self.eflags, Ksym(self.eip as usize));
+        println!(
+            "CS: 0x{:04x} DS: 0x{:04x} ES: 0x{:04x} FS: 0x{:04x} GS: 0x{:04x}",
+            self.cs(), self.ds & 0xFFFF, self.es & 0xFFFF, self.fs & 0xFFFF, self.gs & 0xFFFF
+        );
+    }
+}
+
+/// IRQ 處理函數類型
+pub type IrqHandler = fn(frame: &mut TrapFrame);
+
+static mut IRQ_HANDLERS: [Option<IrqHandler>; pic::PIC_IRQ_COUNT as usize] = [None; pic::PIC_IRQ_COUNT as usize];
+static mut SPURIOUS_IRQ_COUNT: u32 = 0;
+
+/// 註冊 IRQ 處理函數並取消屏蔽該 IRQ 線
+///
+/// 處理函數返回後會自動發送 EOI
+///
+/// # 參數
+/// * `irq` - IRQ 號 (0-15)，級聯線 IRQ2 不可註冊
+/// * `handler` - 處理函數
+/// # 返回
+/// IRQ 號無效或已被註冊時返回 `false`
+#[allow(dead_code)]
+pub fn register_irq_handler(irq: u8, handler: IrqHandler) -> bool {
+    if irq >= pic::PIC_IRQ_COUNT || irq == pic::PIC_CASCADE_IRQ {
+        return false;
+    }
+
+    let irq_enabled = cpu::cpu_save_interrupts();
+    let registered = unsafe {
+        if IRQ_HANDLERS[irq as usize].is_some() {
+            false
+        } else {
+            IRQ_HANDLERS[irq as usize] = Some(handler);
+            true
+        }
+    };
+
+    if registered {
+        pic::pic_unmask(irq);
+    }
+    cpu::cpu_restore_interrupts(irq_enabled);
+
+    registered
+}
+
+/// 移除 IRQ 處理函數並屏蔽該 IRQ 線
+#[allow(dead_code)]
+pub fn unregister_irq_handler(irq: u8) {
+    if irq >= pic::PIC_IRQ_COUNT || irq == pic::PIC_CASCADE_IRQ {
+        return;
+    }
+
+    let irq_enabled = cpu::cpu_save_interrupts();
+    pic::pic_mask(irq);
+    unsafe {
+        IRQ_HANDLERS[irq as usize] = None;
+    }
+    cpu::cpu_restore_interrupts(irq_enabled);
+}
+
+/// 獲取偽中斷 (IRQ7/IRQ15) 發生的次數
+#[allow(dead_code)]
+pub fn spurious_irq_count() -> u32 {
+    unsafe { SPURIOUS_IRQ_COUNT }
+}
+
+/// 硬體 IRQ 分派
+fn irq_handler(frame: &mut TrapFrame) {
+    let irq = (frame.vector() - pic::PIC1_VECTOR_BASE as u32) as u8;
+
+    if pic::pic_handle_spurious(irq) {
+        unsafe {
+            SPURIOUS_IRQ_COUNT += 1;
+        }
+        return;
+    }
+
+    if let Some(handler) = unsafe { IRQ_HANDLERS[irq as usize] } {
+        handler(frame);
+    }
+
+    pic::pic_send_eoi(irq);
 }
 
 #[no_mangle]
-pub extern "C" fn interrupt_handler(param: *const IsrParam) {
-    // param.as_ref().expect("中斷參數為 null")
-    let param_ref = unsafe { param.as_ref().unwrap() };
+pub extern "C" fn interrupt_handler(frame: *mut TrapFrame) {
+    let frame = unsafe { frame.as_mut().unwrap() };
     
-    match param_ref.vector {
-        0 => divide_error_handler(param_ref),
-        1 => debug_error_handler(param_ref),
-        2 => nonmaskable_interrupt_handler(param_ref),
-        3 => breakpoint_handler(param_ref),
-        4 => overflow_handler(param_ref),
-
-        5 => bound_range_exceeded_handler(param_ref),
-        6 => invalid_opcode_handler(param_ref),
-        7 => device_not_available_handler(param_ref),
-        8 => double_fault_handler(param_ref),
-        9 => coprocessor_segment_overrun_handler(param_ref),
+    match frame.vector {
+        0 => divide_error_handler(frame),
+        1 => debug_error_handler(frame),
+        2 => nonmaskable_interrupt_handler(frame),
+        3 => breakpoint_handler(frame),
+        4 => overflow_handler(frame),
+
+        5 => bound_range_exceeded_handler(frame),
+        6 => invalid_opcode_handler(frame),
+        7 => device_not_available_handler(frame),
+        9 => coprocessor_segment_overrun_handler(frame),
     
-        10 => invalid_tss_handler(param_ref),
-        11 => segment_not_present_handler(param_ref),
-        12 => stack_segment_fault_handler(param_ref),
-        13 => general_protection_handler(param_ref),
-        14 => page_fault_handler(param_ref),
-        // 15 => reserved_handler(param_ref),
-        16 => x87_fpu_floating_point_handler(param_ref),
-        17 => alignment_check_handler(param_ref),
-        18 => machine_check_handler(param_ref),
-        19 => simd_floating_point_handler(param_ref),
-        20 => virtualization_handler(param_ref),
-        // 21 => reserved_handler(param_ref),
-        // 22 => reserved_handler(param_ref),
-        // 23 => reserved_handler(param_ref),
-        // 24 => reserved_handler(param_ref),
-        // 25 => reserved_handler(param_ref),
-        // 26 => reserved_handler(param_ref),
-        // 27 => reserved_handler(param_ref),
-        // 28 => reserved_handler(param_ref),
-        // 29 => reserved_handler(param_ref),
-        // 30 => reserved_handler(param_ref),
-        // 31 => reserved_handler(param_ref),
-
-        _ => reserved_handler(param_ref),
-    }
-}
-
-fn print_exception(param: &IsrParam) {
-    let vector = param.vector();
+        10 => invalid_tss_handler(frame),
+        11 => segment_not_present_handler(frame),
+        12 => stack_segment_fault_handler(frame),
+        13 => general_protection_handler(frame),
+        14 => page_fault_handler(frame),
+        // 15 => reserved_handler(frame),
+        16 => x87_fpu_floating_point_handler(frame),
+        17 => alignment_check_handler(frame),
+        18 => machine_check_handler(frame),
+        19 => simd_floating_point_handler(frame),
+        20 => virtualization_handler(frame),
+        // 21 => reserved_handler(frame),
+        // 22 => reserved_handler(frame),
+        // 23 => reserved_handler(frame),
+        // 24 => reserved_handler(frame),
+        // 25 => reserved_handler(frame),
+        // 26 => reserved_handler(frame),
+        // 27 => reserved_handler(frame),
+        // 28 => reserved_handler(frame),
+        // 29 => reserved_handler(frame),
+        // 30 => reserved_handler(frame),
+        // 31 => reserved_handler(frame),
+
+        32..=47 => irq_handler(frame),
+
+        _ => reserved_handler(frame),
+    }
+}
+
+/// 用戶態異常處理函數類型
+///
+/// 由任務管理註冊，負責終止觸發異常的任務並切換到其他任務，
+/// 不可返回到觸發異常的指令
+pub type UserFaultHandler = fn(frame: &mut TrapFrame) -> !;
+
+static mut USER_FAULT_HANDLER: Option<UserFaultHandler> = None;
+
+/// 註冊用戶態異常處理函數
+///
+/// # 參數
+/// * `handler` - 處理函數，觸發異常的上下文不會再被恢復
+#[allow(dead_code)]
+pub fn set_user_fault_handler(handler: UserFaultHandler) {
+    let irq_enabled = cpu::cpu_save_interrupts();
+    unsafe {
+        USER_FAULT_HANDLER = Some(handler);
+    }
+    cpu::cpu_restore_interrupts(irq_enabled);
+}
+
+fn print_exception(frame: &TrapFrame) {
+    let vector = frame.vector();
 
     if vector < 32 {
         let ex = &EXCEPTIONS[vector as usize];
         println!("CPU Exception: {}", ex);
-        println!("EIP: 0x{:x}, CS: 0x{:x}, EFLAGS: 0x{:x}", param.eip(), param.cs(), param.eflags());
+        frame.dump();
 
-        let error_code = param.err_code();
+        let error_code = frame.err_code();
         
         if error_code != 0 {
             println!("Error code: 0x{:x}", error_code);
@@ -109,140 +250,213 @@ fn print_exception(param: &IsrParam) {
                 println!("Fault details:\n{}", pf_error);
             }
         }
-        
-        // if let Some(addr) = fault_addr {
-        //     println!("Fault address: 0x{:x}", addr);
-        // }
+
+        // 用戶態的堆疊不可信，只回溯內核框架
+        if !frame.is_user() {
+            panic::print_backtrace(frame.regs.ebp as usize);
+        }
     } else {
         println!("Unhandled interrupt: Vector {}", vector);
     }
-    
-    loop {}
+}
+
+/// 無法在內核中恢復的異常
+fn kernel_fault(frame: &TrapFrame) -> ! {
+    // 異常可能發生在持有輸出鎖期間，先強制釋放以免輸出時死鎖
+    unsafe {
+        tty::tty_force_unlock();
+        serial::serial_force_unlock();
+    }
+
+    print_exception(frame);
+
+    let vector = frame.vector();
+    if vector < 32 {
+        panic!("unrecoverable {} at 0x{:08x}", EXCEPTIONS[vector as usize].mnemonic, frame.eip());
+    } else {
+        panic!("unexpected interrupt {} at 0x{:08x}", vector, frame.eip());
+    }
+}
+
+/// 未被處理的異常
+///
+/// 來自用戶態的異常只終止觸發的任務，來自內核的異常進入 panic
+fn unhandled_exception(frame: &mut TrapFrame) {
+    if !frame.is_user() {
+        kernel_fault(frame);
+    }
+
+    match unsafe { USER_FAULT_HANDLER } {
+        Some(handler) => {
+            print_exception(frame);
+            handler(frame);
+        }
+        // 尚未有任務管理時沒有任務可以終止
+        None => kernel_fault(frame),
+    }
 }
 
 /// isr0
 #[no_mangle]
-pub fn divide_error_handler(param: &IsrParam) {
-    print_exception(param)
+pub fn divide_error_handler(frame: &mut TrapFrame) {
+    unhandled_exception(frame)
 }
 
 /// isr1
+///
+/// 單步與硬體斷點屬於除錯陷阱，記錄後繼續執行
 #[no_mangle]
-pub fn debug_error_handler(param: &IsrParam) {
-    print_exception(param)
+pub fn debug_error_handler(frame: &mut TrapFrame) {
+    let dr6 = cpu::cpu_r_dr6();
+    println!("Debug trap at 0x{:08x}, DR6: {:?}", frame.eip(), dr6);
+
+    // DR6 的狀態位不會自動清除；設置 RF 避免指令斷點在返回後立即再次觸發
+    cpu::cpu_w_dr6(Dr6::empty());
+    frame.eflags |= EFlags::FLAGS_RF.bits();
 }
 
 /// isr2
+///
+/// NMI 與當前執行的指令無關，記錄後繼續執行
 #[no_mangle]
-pub fn nonmaskable_interrupt_handler(param: &IsrParam) {
-    print_exception(param)
+pub fn nonmaskable_interrupt_handler(frame: &mut TrapFrame) {
+    println!("Non-maskable interrupt at 0x{:08x}", frame.eip());
 }
 
 /// isr3
+///
+/// `int3` 屬於陷阱，EIP 已指向下一條指令，直接返回即可繼續執行
 #[no_mangle]
-pub fn breakpoint_handler(param: &IsrParam) {
-    print_exception(param)
+pub fn breakpoint_handler(frame: &mut TrapFrame) {
+    println!("Breakpoint at 0x{:08x}", frame.eip().wrapping_sub(1));
+    frame.dump();
 }
 
 /// isr4
 #[no_mangle]
-pub fn overflow_handler(param: &IsrParam) {
-    print_exception(param)
+pub fn overflow_handler(frame: &mut TrapFrame) {
+    unhandled_exception(frame)
 }
 
 /// isr5
 #[no_mangle]
-pub fn bound_range_exceeded_handler(param: &IsrParam) {
-    print_exception(param)
+pub fn bound_range_exceeded_handler(frame: &mut TrapFrame) {
+    unhandled_exception(frame)
 }
 
 /// isr6
 #[no_mangle]
-pub fn invalid_opcode_handler(param: &IsrParam) {
-    print_exception(param)
+pub fn invalid_opcode_handler(frame: &mut TrapFrame) {
+    unhandled_exception(frame)
 }
 
 /// isr7
 #[no_mangle]
-pub fn device_not_available_handler(param: &IsrParam) {
-    print_exception(param)
-}
-
-/// isr8
-#[no_mangle]
-pub fn double_fault_handler(param: &IsrParam) {
-    print_exception(param)
+pub fn device_not_available_handler(frame: &mut TrapFrame) {
+    unhandled_exception(frame)
 }
 
 /// isr9
 #[no_mangle]
-pub fn coprocessor_segment_overrun_handler(param: &IsrParam) {
-    print_exception(param)
+pub fn coprocessor_segment_overrun_handler(frame: &mut TrapFrame) {
+    unhandled_exception(frame)
 }
 
 /// isr10
 #[no_mangle]
-pub fn invalid_tss_handler(param: &IsrParam) {
-    print_exception(param)
+pub fn invalid_tss_handler(frame: &mut TrapFrame) {
+    unhandled_exception(frame)
 }
 
 /// isr11
 #[no_mangle]
-pub fn segment_not_present_handler(param: &IsrParam) {
-    print_exception(param)
+pub fn segment_not_present_handler(frame: &mut TrapFrame) {
+    unhandled_exception(frame)
 }
 
 /// isr12
 #[no_mangle]
-pub fn stack_segment_fault_handler(param: &IsrParam) {
-    print_exception(param)
+pub fn stack_segment_fault_handler(frame: &mut TrapFrame) {
+    unhandled_exception(frame)
 }
 
 /// isr13
 #[no_mangle]
-pub fn general_protection_handler(param: &IsrParam) {
-    print_exception(param)
+pub fn general_protection_handler(frame: &mut TrapFrame) {
+    unhandled_exception(frame)
 }
 
 /// isr14
+///
+/// 先交給分頁子系統，無法修復時才視為未處理的異常
 #[no_mangle]
-pub fn page_fault_handler(param: &IsrParam) {    
-    print_exception(param)
+pub fn page_fault_handler(frame: &mut TrapFrame) {
+    let addr = cpu::cpu_r_cr2();
+    let error = PageFaultError::from_bits_truncate(frame.err_code());
+
+    if !paging::handle_page_fault(addr, error) {
+        unhandled_exception(frame);
+    }
 }
 
 /// isr16
 #[no_mangle]
-pub fn x87_fpu_floating_point_handler(param: &IsrParam) {
-    print_exception(param)
+pub fn x87_fpu_floating_point_handler(frame: &mut TrapFrame) {
+    unhandled_exception(frame)
 }
 
 /// isr17
 #[no_mangle]
-pub fn alignment_check_handler(param: &IsrParam) {
-    print_exception(param)
+pub fn alignment_check_handler(frame: &mut TrapFrame) {
+    unhandled_exception(frame)
 }
 
 /// isr18
+///
+/// 不論來源都無法恢復
 #[no_mangle]
-pub fn machine_check_handler(param: &IsrParam) {
-    print_exception(param)
+pub fn machine_check_handler(frame: &mut TrapFrame) {
+    kernel_fault(frame)
 }
 
 /// isr19
 #[no_mangle]
-pub fn simd_floating_point_handler(param: &IsrParam) {
-    print_exception(param)
+pub fn simd_floating_point_handler(frame: &mut TrapFrame) {
+    unhandled_exception(frame)
 }
 
 /// isr20
 #[no_mangle]
-pub fn virtualization_handler(param: &IsrParam) {
-    print_exception(param)
+pub fn virtualization_handler(frame: &mut TrapFrame) {
+    unhandled_exception(frame)
 }
 
 
 /// isr15, 21-31
 #[no_mangle]
-pub fn reserved_handler(param: &IsrParam) {
-    print_exception(param)
-}
\ No newline at end of file
+pub fn reserved_handler(frame: &mut TrapFrame) {
+    unhandled_exception(frame)
+}
+#[cfg(test)]
+mod tests {
+    use crate::kernel::test::ShouldPanic;
+    use crate::kernel::tty::tty;
+
+    #[test_case]
+    fn breakpoint_resumes() {
+        unsafe {
+            core::arch::asm!("int3");
+        }
+    }
+
+    #[test_case]
+    static INVALID_OPCODE_PANICS: ShouldPanic = ShouldPanic("invalid_opcode_panics", || unsafe {
+        core::arch::asm!("ud2");
+    });
+
+    #[test_case]
+    static FAULT_HOLDING_TTY_LOCK_PANICS: ShouldPanic = ShouldPanic("fault_holding_tty_lock_panics", || unsafe {
+        let _guard = tty::tty_lock();
+        core::arch::asm!("ud2");
+    });
+}
@@ -1,4 +1,12 @@
 pub mod gdt;
+#[cfg(not(feature = "std"))]
 pub mod idt;
+#[cfg(not(feature = "std"))]
 pub mod interrupt;
-pub mod segment;
\ No newline at end of file
+#[cfg(not(feature = "std"))]
+pub mod pic;
+pub mod segment;
+#[cfg(not(feature = "std"))]
+pub mod tsc;
+#[cfg(not(feature = "std"))]
+pub mod tss;
\ No newline at end of file
@@ -0,0 +1,121 @@
+// src/kernel/asm/x86/pic.rs
+use crate::hal::io;
+
+/// 主 PIC 命令端口
+const PIC1_CMD: u16 = 0x20;
+/// 主 PIC 資料端口
+const PIC1_DATA: u16 = 0x21;
+/// 從 PIC 命令端口
+const PIC2_CMD: u16 = 0xA0;
+/// 從 PIC 資料端口
+const PIC2_DATA: u16 = 0xA1;
+
+/// ICW1: 需要 ICW4 | 開始初始化
+const ICW1_INIT: u8 = 0x11;
+/// ICW4: 8086/88 模式
+const ICW4_8086: u8 = 0x01;
+/// OCW2: 非特定 EOI
+const PIC_EOI: u8 = 0x20;
+/// OCW3: 下一次讀取命令端口返回 ISR
+const PIC_READ_ISR: u8 = 0x0B;
+
+/// 從 PIC 連接在主 PIC 的 IRQ 線
+pub const PIC_CASCADE_IRQ: u8 = 2;
+/// IRQ 數量
+pub const PIC_IRQ_COUNT: u8 = 16;
+/// 主 PIC 重新映射後的起始向量
+pub const PIC1_VECTOR_BASE: u8 = 0x20;
+/// 從 PIC 重新映射後的起始向量
+pub const PIC2_VECTOR_BASE: u8 = 0x28;
+
+/// 將主從 PIC 重新映射到 0x20-0x2F，避免與 CPU 異常向量衝突
+///
+/// 初始化後除了級聯線 IRQ2 以外的所有 IRQ 都被屏蔽，由 `pic_unmask` 個別開啟
+#[no_mangle]
+pub fn pic_init() {
+    io::io_port_wb(PIC1_CMD, ICW1_INIT);
+    io::io_delay();
+    io::io_port_wb(PIC2_CMD, ICW1_INIT);
+    io::io_delay();
+
+    // ICW2: 向量偏移
+    io::io_port_wb(PIC1_DATA, PIC1_VECTOR_BASE);
+    io::io_delay();
+    io::io_port_wb(PIC2_DATA, PIC2_VECTOR_BASE);
+    io::io_delay();
+
+    // ICW3: 主 PIC 的 IRQ2 接從 PIC，從 PIC 的級聯 ID 為 2
+    io::io_port_wb(PIC1_DATA, 1 << PIC_CASCADE_IRQ);
+    io::io_delay();
+    io::io_port_wb(PIC2_DATA, PIC_CASCADE_IRQ);
+    io::io_delay();
+
+    io::io_port_wb(PIC1_DATA, ICW4_8086);
+    io::io_delay();
+    io::io_port_wb(PIC2_DATA, ICW4_8086);
+    io::io_delay();
+
+    io::io_port_wb(PIC1_DATA, !(1 << PIC_CASCADE_IRQ));
+    io::io_port_wb(PIC2_DATA, 0xFF);
+}
+
+/// 發送中斷結束信號 (EOI)
+///
+/// # 參數
+/// * `irq` - IRQ 號 (0-15)，來自從 PIC 的 IRQ 需要同時通知兩片 PIC
+#[no_mangle]
+pub fn pic_send_eoi(irq: u8) {
+    if irq >= 8 {
+        io::io_port_wb(PIC2_CMD, PIC_EOI);
+    }
+    io::io_port_wb(PIC1_CMD, PIC_EOI);
+}
+
+/// 屏蔽 IRQ 線
+#[no_mangle]
+pub fn pic_mask(irq: u8) {
+    let (port, line) = if irq < 8 { (PIC1_DATA, irq) } else { (PIC2_DATA, irq - 8) };
+    io::io_port_wb(port, io::io_port_rb(port) | (1 << line));
+}
+
+/// 取消屏蔽 IRQ 線
+#[no_mangle]
+pub fn pic_unmask(irq: u8) {
+    let (port, line) = if irq < 8 { (PIC1_DATA, irq) } else { (PIC2_DATA, irq - 8) };
+    io::io_port_wb(port, io::io_port_rb(port) & !(1 << line));
+}
+
+/// 讀取兩片 PIC 的 In-Service Register
+///
+/// # 返回
+/// 低 8 位為主 PIC，高 8 位為從 PIC
+#[no_mangle]
+pub fn pic_read_isr() -> u16 {
+    io::io_port_wb(PIC1_CMD, PIC_READ_ISR);
+    io::io_port_wb(PIC2_CMD, PIC_READ_ISR);
+    ((io::io_port_rb(PIC2_CMD) as u16) << 8) | io::io_port_rb(PIC1_CMD) as u16
+}
+
+/// 檢查 IRQ7/IRQ15 是否為偽中斷
+///
+/// 偽中斷的 ISR 位不會被設置，此時不能向對應的 PIC 發送 EOI；
+/// 但從 PIC 的偽 IRQ15 仍需要向主 PIC 發送 EOI（級聯線確實被觸發了）
+///
+/// # 返回
+/// 如果是偽中斷返回 `true`（已完成必要的 EOI 處理）
+#[no_mangle]
+pub fn pic_handle_spurious(irq: u8) -> bool {
+    if irq != 7 && irq != 15 {
+        return false;
+    }
+
+    if pic_read_isr() & (1 << irq) != 0 {
+        return false;
+    }
+
+    if irq == 15 {
+        io::io_port_wb(PIC1_CMD, PIC_EOI);
+    }
+
+    true
+}
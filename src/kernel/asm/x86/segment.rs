@@ -6,6 +6,8 @@ pub const KERNEL_CODE_SELECTOR: SegmentSelector = SegmentSelector::new(1, Ring::
 pub const KERNEL_DATA_SELECTOR: SegmentSelector = SegmentSelector::new(2, Ring::Ring0);
 pub const USER_CODE_SELECTOR: SegmentSelector = SegmentSelector::new(3, Ring::Ring3);
 pub const USER_DATA_SELECTOR: SegmentSelector = SegmentSelector::new(4, Ring::Ring3);
+pub const KERNEL_TSS_SELECTOR: SegmentSelector = SegmentSelector::new(5, Ring::Ring0);
+pub const DOUBLE_FAULT_TSS_SELECTOR: SegmentSelector = SegmentSelector::new(6, Ring::Ring0);
 
 #[no_mangle]
 pub static NULL_SEL: u16 = NULL_SELECTOR.bits();
@@ -0,0 +1,197 @@
+// src/kernel/asm/x86/tsc.rs
+use core::fmt;
+use core::ptr;
+use core::sync::atomic::{AtomicU64, Ordering};
+use x86::cpuid::CpuId;
+use crate::drivers::pit;
+use crate::hal::cpu;
+use crate::kernel::time::{self, Clocksource};
+
+/// 以 PIT 校準時每輪的測量時間 (ms)
+const CALIBRATE_MS: u64 = 10;
+/// 以 PIT 校準的輪數，取最小值以排除被打斷的測量
+const CALIBRATE_ROUNDS: usize = 3;
+
+/// TSC 頻率的來源
+#[allow(dead_code)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TscSource {
+    /// CPUID 0x15：晶振頻率與 TSC 比例
+    Cpuid15,
+    /// CPUID 0x16：處理器基準頻率
+    Cpuid16,
+    /// 以 PIT 計數校準
+    Pit,
+}
+
+impl fmt::Display for TscSource {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TscSource::Cpuid15 => write!(f, "CPUID 0x15"),
+            TscSource::Cpuid16 => write!(f, "CPUID 0x16"),
+            TscSource::Pit => write!(f, "PIT calibration"),
+        }
+    }
+}
+
+/// TSC 錯誤
+#[allow(dead_code)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TscError {
+    /// CPU 沒有 TSC
+    NotSupported,
+    /// 無法取得頻率（PIT 尚未初始化或測量結果為 0）
+    CalibrationFailed,
+}
+
+impl fmt::Display for TscError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TscError::NotSupported => write!(f, "no time stamp counter"),
+            TscError::CalibrationFailed => write!(f, "calibration failed"),
+        }
+    }
+}
+
+/// TSC 頻率 (Hz)，0 表示尚未校準
+static TSC_HZ: AtomicU64 = AtomicU64::new(0);
+/// 註冊時鐘源時的 TSC 讀數
+static TSC_BASE: AtomicU64 = AtomicU64::new(0);
+
+/// 優先級在 `tsc_init` 中依是否為不變 TSC 設定
+static mut TSC_CLOCKSOURCE: Clocksource = Clocksource {
+    name: "tsc",
+    rating: 0,
+    read_ns: tsc_read_ns,
+    irq_driven: false,
+};
+
+/// CPU 是否有 TSC
+pub fn tsc_supported() -> bool {
+    CpuId::new().get_feature_info().is_some_and(|info| info.has_tsc())
+}
+
+/// TSC 是否為不變 TSC（頻率不隨 P/C 狀態改變）
+pub fn tsc_invariant() -> bool {
+    CpuId::new()
+        .get_advanced_power_mgmt_info()
+        .is_some_and(|info| info.has_invariant_tsc())
+}
+
+/// 由 CPUID 0x15/0x16 取得 TSC 頻率
+fn tsc_frequency_from_cpuid() -> Option<(u64, TscSource)> {
+    let cpuid = CpuId::new();
+
+    if let Some(hz) = cpuid.get_tsc_info().and_then(|info| info.tsc_frequency()) {
+        return Some((hz, TscSource::Cpuid15));
+    }
+
+    match cpuid.get_processor_frequency_info().map(|info| info.processor_base_frequency()) {
+        Some(mhz) if mhz != 0 => Some((mhz as u64 * 1_000_000, TscSource::Cpuid16)),
+        _ => None,
+    }
+}
+
+/// 以 PIT 計數校準 TSC 頻率
+///
+/// 關閉中斷輪詢 PIT，需在 `pit_init` 之後呼叫
+fn tsc_calibrate_pit() -> Option<u64> {
+    if pit::pit_frequency() == 0 {
+        return None;
+    }
+
+    let irq_enabled = cpu::cpu_save_interrupts();
+    let mut best = u64::MAX;
+
+    for _ in 0..CALIBRATE_ROUNDS {
+        let start = cpu::cpu_rdtsc();
+        pit::pit_poll_wait_ns(CALIBRATE_MS * 1_000_000);
+        best = best.min(cpu::cpu_rdtsc() - start);
+    }
+
+    cpu::cpu_restore_interrupts(irq_enabled);
+
+    match best * (1000 / CALIBRATE_MS) {
+        0 => None,
+        hz => Some(hz),
+    }
+}
+
+/// 取得 TSC 頻率並註冊 TSC 時鐘源
+///
+/// 優先使用 CPUID 提供的頻率，否則以 PIT 校準，因此需在 `pit_init` 之後呼叫。
+/// TSC 的優先級高於 PIT，非不變 TSC 的優先級低於不變 TSC
+///
+/// # 返回
+/// 頻率的來源
+pub fn tsc_init() -> Result<TscSource, TscError> {
+    if !tsc_supported() {
+        return Err(TscError::NotSupported);
+    }
+
+    let (hz, source) = match tsc_frequency_from_cpuid() {
+        Some(found) => found,
+        None => (tsc_calibrate_pit().ok_or(TscError::CalibrationFailed)?, TscSource::Pit),
+    };
+
+    TSC_HZ.store(hz, Ordering::Relaxed);
+    TSC_BASE.store(cpu::cpu_rdtsc(), Ordering::Relaxed);
+
+    unsafe {
+        let cs = &mut *ptr::addr_of_mut!(TSC_CLOCKSOURCE);
+        cs.rating = if tsc_invariant() { 300 } else { 200 };
+        time::clocksource_register(&*ptr::addr_of!(TSC_CLOCKSOURCE));
+    }
+
+    Ok(source)
+}
+
+/// TSC 頻率 (Hz)，尚未校準時為 0
+#[allow(dead_code)]
+pub fn tsc_frequency() -> u64 {
+    TSC_HZ.load(Ordering::Relaxed)
+}
+
+/// 將 TSC 週期數換算為納秒，用於效能測量
+///
+/// # 返回
+/// 尚未校準時為 0
+#[allow(dead_code)]
+pub fn tsc_cycles_to_ns(cycles: u64) -> u64 {
+    match TSC_HZ.load(Ordering::Relaxed) {
+        0 => 0,
+        hz => time::cycles_to_ns(cycles, hz),
+    }
+}
+
+/// 時鐘源讀取函數
+fn tsc_read_ns() -> u64 {
+    tsc_cycles_to_ns(cpu::cpu_rdtsc().wrapping_sub(TSC_BASE.load(Ordering::Relaxed)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::kernel::time::NANOS_PER_SEC;
+
+    #[test_case]
+    fn frequency_is_plausible() {
+        // 在沒有 TSC 的 CPU 上 tsc_init 會失敗，頻率保持 0
+        let hz = tsc_frequency();
+        assert!(hz == 0 || (hz > 100_000_000 && hz < 10 * NANOS_PER_SEC));
+    }
+
+    #[test_case]
+    fn agrees_with_pit() {
+        if tsc_frequency() == 0 {
+            return;
+        }
+
+        let start = tsc_read_ns();
+        pit::pit_poll_wait_ns(20_000_000);
+        let elapsed = tsc_read_ns() - start;
+
+        // 輪詢開銷只會讓測量偏長，允許偏短 10%、偏長 20%
+        assert!(elapsed >= 18_000_000 && elapsed <= 24_000_000);
+    }
+}
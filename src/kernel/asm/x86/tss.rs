@@ -0,0 +1,158 @@
+// src/kernel/asm/x86/tss.rs
+use core::mem;
+use core::ptr;
+use x86::bits32::task::TaskStateSegment;
+use x86::segmentation::Descriptor;
+use x86::task;
+use crate::drivers::serial;
+use crate::hal::cpu;
+use crate::kernel::asm::x86::{gdt, segment};
+use crate::kernel::ksym::Ksym;
+use crate::kernel::tty::tty;
+use crate::println;
+
+/// 雙重錯誤任務的堆疊大小
+pub const DOUBLE_FAULT_STACK_SIZE: usize = 16 * 1024;
+
+#[repr(C, align(16))]
+struct Stack([u8; DOUBLE_FAULT_STACK_SIZE]);
+
+/// 內核任務的 TSS
+///
+/// 提供特權級切換時使用的 SS0/ESP0，並在切換到雙重錯誤任務時保存出錯的上下文
+#[no_mangle]
+pub static mut _KERNEL_TSS: TaskStateSegment = TaskStateSegment::new();
+
+/// 雙重錯誤任務的 TSS
+#[no_mangle]
+pub static mut _DOUBLE_FAULT_TSS: TaskStateSegment = TaskStateSegment::new();
+
+static mut DOUBLE_FAULT_STACK: Stack = Stack([0; DOUBLE_FAULT_STACK_SIZE]);
+
+extern "C" {
+    /// boot.S 中的內核堆疊頂端
+    static stack_top: u8;
+    /// interrupt.S 中的雙重錯誤任務入口
+    fn _asm_double_fault_task();
+}
+
+/// 建立 TSS 的 GDT 描述符
+fn tss_descriptor(tss: *const TaskStateSegment) -> Descriptor {
+    gdt::gdt_tss_descriptor(tss as u32, (mem::size_of::<TaskStateSegment>() - 1) as u32)
+}
+
+/// 填寫兩個 TSS 並返回它們的 GDT 描述符
+///
+/// # 返回
+/// `(內核 TSS 描述符, 雙重錯誤 TSS 描述符)`
+#[no_mangle]
+pub fn tss_init() -> (Descriptor, Descriptor) {
+    let kernel_data = segment::KERNEL_DATA_SELECTOR.bits();
+    let kernel_code = segment::KERNEL_CODE_SELECTOR.bits();
+    // 不使用 I/O 權限位圖，偏移指向 TSS 之外
+    let no_iomap = mem::size_of::<TaskStateSegment>() as u16;
+
+    unsafe {
+        let tss = &mut *ptr::addr_of_mut!(_KERNEL_TSS);
+        tss.ss0 = kernel_data;
+        tss.esp0 = ptr::addr_of!(stack_top) as u32;
+        tss.iobp_offset = no_iomap;
+
+        let df = &mut *ptr::addr_of_mut!(_DOUBLE_FAULT_TSS);
+        df.cr3 = cpu::cpu_r_cr3() as u32;
+        df.eip = (_asm_double_fault_task as unsafe extern "C" fn()) as usize as u32;
+        df.esp = ptr::addr_of!(DOUBLE_FAULT_STACK) as u32 + DOUBLE_FAULT_STACK_SIZE as u32;
+        // IF 關閉，bit 1 固定為 1
+        df.eflags = 0x2;
+        df.cs = kernel_code;
+        df.ss = kernel_data;
+        df.ds = kernel_data;
+        df.es = kernel_data;
+        df.fs = kernel_data;
+        df.gs = kernel_data;
+        df.ss0 = kernel_data;
+        df.esp0 = df.esp;
+        df.iobp_offset = no_iomap;
+
+        (
+            tss_descriptor(ptr::addr_of!(_KERNEL_TSS)),
+            tss_descriptor(ptr::addr_of!(_DOUBLE_FAULT_TSS)),
+        )
+    }
+}
+
+/// 將內核 TSS 載入 TR
+///
+/// 需在 GDT 載入之後呼叫
+#[no_mangle]
+pub fn tss_load() {
+    unsafe {
+        task::load_tr(segment::KERNEL_TSS_SELECTOR);
+    }
+}
+
+/// 設置從用戶態進入內核時使用的堆疊
+///
+/// # 參數
+/// * `esp0` - 內核堆疊頂端
+#[allow(dead_code)]
+pub fn tss_set_kernel_stack(esp0: u32) {
+    unsafe {
+        (*ptr::addr_of_mut!(_KERNEL_TSS)).esp0 = esp0;
+    }
+}
+
+/// 雙重錯誤任務
+///
+/// 由任務門切換進入，CPU 已將出錯時的狀態保存在內核 TSS 中。
+/// 輸出出錯時的暫存器後進入 panic
+///
+/// # 參數
+/// * `err_code` - CPU 壓入的錯誤碼（固定為 0）
+#[no_mangle]
+pub extern "C" fn double_fault_task(err_code: u32) -> ! {
+    // 堆疊溢位等錯誤可能發生在持有輸出鎖期間
+    unsafe {
+        tty::tty_force_unlock();
+        serial::serial_force_unlock();
+    }
+
+    let tss = unsafe { ptr::read(ptr::addr_of!(_KERNEL_TSS)) };
+    let link = unsafe { (*ptr::addr_of!(_DOUBLE_FAULT_TSS)).link };
+
+    // packed 結構的欄位需先複製出來才能格式化
+    let (eax, ebx, ecx, edx) = (tss.eax, tss.ebx, tss.ecx, tss.edx);
+    let (esi, edi, ebp, esp) = (tss.esi, tss.edi, tss.ebp, tss.esp);
+    let (eip, eflags) = (tss.eip, tss.eflags);
+    let (cs, ss, ds, es, fs, gs) = (tss.cs, tss.ss, tss.ds, tss.es, tss.fs, tss.gs);
+
+    println!("CPU Exception: #DF Double Fault (error code 0x{:x})", err_code);
+    println!("Faulting task: TSS selector 0x{:04x}", link);
+    println!("EAX: 0x{:08x} EBX: 0x{:08x} ECX: 0x{:08x} EDX: 0x{:08x}", eax, ebx, ecx, edx);
+    println!("ESI: 0x{:08x} EDI: 0x{:08x} EBP: 0x{:08x} ESP: 0x{:08x}", esi, edi, ebp, esp);
+    println!("EIP: 0x{:08x} EFLAGS: 0x{:08x} {}", eip, eflags, Ksym(eip as usize));
+    println!(
+        "CS: 0x{:04x} SS: 0x{:04x} DS: 0x{:04x} ES: 0x{:04x} FS: 0x{:04x} GS: 0x{:04x}",
+        cs, ss, ds, es, fs, gs
+    );
+    println!("CR2: 0x{:08x} CR3: 0x{:08x}", cpu::cpu_r_cr2(), cpu::cpu_r_cr3());
+
+    panic!("double fault");
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::kernel::test::ShouldPanicWith;
+
+    #[allow(unconditional_recursion)]
+    fn overflow(depth: usize) -> usize {
+        let frame = core::hint::black_box([depth; 64]);
+        overflow(depth + 1) + frame[0]
+    }
+
+    // 內核堆疊溢位碰到保護頁後應進入雙重錯誤任務，而不是三重錯誤
+    #[test_case]
+    static STACK_OVERFLOW_DOUBLE_FAULTS: ShouldPanicWith = ShouldPanicWith("stack_overflow_double_faults", "double fault", || {
+        overflow(0);
+    });
+}
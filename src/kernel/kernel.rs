@@ -1,16 +1,62 @@
 // src/kernel/kernel.rs
 use core::arch::asm;
-use crate::kernel::tty::tty;
+use crate::kernel::tty::{ldisc, tty};
 use crate::hal::cpu;
-use crate::{print, println};
+use crate::boot;
+use crate::drivers::{i8042, keyboard, mouse, pit, rtc, vga};
+use crate::kernel::asm::x86::tsc;
+use crate::kernel::time;
+use crate::drivers::serial::{self, ComPort, SerialConfig};
+use crate::mm::{self, pmm, paging, heap};
+use crate::println;
+
+/// 內核 shell 使用的控制台
+const SHELL_CONSOLE: usize = 0;
 
 #[no_mangle]
-pub extern "C" fn _kernel_init() {
+pub extern "C" fn _kernel_init(mb_magic: u32, mb_info: u32) {
     // TODO: 加載 GDT OK
     // TODO: 加載 IDT OK
-    // TODO: 啟用分頁
-    tty::tty_init(tty::VGA_BUFFER_PADDR);
+    // TODO: 啟用分頁 OK
+    tty::tty_init(mm::phys_to_virt(tty::VGA_BUFFER_PADDR));
     tty::tty_set_theme(tty::VGA_COLOR_WHITE, tty::VGA_COLOR_BLACK);
+
+    if let Err(e) = boot::multiboot_init(mb_magic, mb_info as usize) {
+        println!("Multiboot: {}", e);
+    }
+
+    // COM1 供 serial_print! 使用，命令列的 console=ttyS<n> 另外將控制台鏡像到指定串口
+    let _ = serial::serial_init(ComPort::Com1, SerialConfig::new());
+    let console = boot::boot_info()
+        .and_then(|info| info.cmdline())
+        .and_then(serial::serial_console_from_cmdline);
+    if let Some(port) = console {
+        match serial::serial_init(port, SerialConfig::new()) {
+            Ok(()) => serial::serial_set_mirror(Some(port)),
+            Err(e) => println!("Serial: {:?}: {}", port, e),
+        }
+    }
+    // console=tty<n> 將內核日誌綁定到指定的虛擬控制台
+    let log_console = boot::boot_info()
+        .and_then(|info| info.cmdline())
+        .and_then(tty::tty_console_from_cmdline);
+    if let Some(index) = log_console {
+        tty::tty_set_log_console(index);
+    }
+    let scrollback = boot::boot_info()
+        .and_then(|info| info.cmdline())
+        .and_then(tty::tty_scrollback_from_cmdline);
+    if let Some(lines) = scrollback {
+        tty::tty_set_scrollback_limit(lines);
+    }
+
+    pmm::pmm_init(boot::boot_info());
+
+    paging::paging_init();
+
+    if !heap::heap_init() {
+        println!("Heap: failed to map the initial kernel heap");
+    }
 }
 
 #[no_mangle]
@@ -27,6 +73,42 @@ pub extern "C" fn _kernel_main() {
     
     println!("Welcome to Cure OS!");
 
+    if let Some(info) = boot::boot_info() {
+        if let Some(name) = info.bootloader_name() {
+            println!("Bootloader: {}", name);
+        }
+        if let Some(cmdline) = info.cmdline() {
+            println!("Command line: {}", cmdline);
+        }
+        for entry in info.memory_map() {
+            println!("  [0x{:08x} - 0x{:08x}] {}", entry.base, entry.end(), entry.kind);
+        }
+        println!("Available memory: {} KiB", info.total_available() / 1024);
+    }
+
+    pmm::pmm_print_stats();
+    heap::heap_print_stats();
+
+    // 以 .psf 結尾的引導模組作為控制台字型
+    let font = boot::boot_info().and_then(|info| {
+        info.modules()
+            .iter()
+            .find(|module| module.cmdline().split_whitespace().next().is_some_and(|path| path.ends_with(".psf")))
+    });
+    if let Some(module) = font {
+        if module.end as usize > mm::KERNEL_DIRECT_MAP_SIZE {
+            println!("Font: {} is outside the direct map", module.cmdline());
+        } else {
+            let data = unsafe {
+                core::slice::from_raw_parts(mm::phys_to_virt(module.start as usize) as *const u8, module.size())
+            };
+            match vga::vga_font_load(data) {
+                Ok(count) => println!("Font: {} ({} glyphs)", module.cmdline(), count),
+                Err(e) => println!("Font: {}: {}", module.cmdline(), e),
+            }
+        }
+    }
+
     println!("{0} + {1} = {0}", 1, 2);
 
     let name = "111";
@@ -63,7 +145,58 @@ pub extern "C" fn _kernel_main() {
         );
     }
 
-    // cpu::cpu_enable_interrupts();
+    serial::serial_irq_init();
+
+    match pit::pit_init(pit::PIT_DEFAULT_FREQUENCY) {
+        Ok(()) => println!("PIT: {} Hz", pit::pit_frequency()),
+        Err(e) => println!("PIT: {}", e),
+    }
+
+    match tsc::tsc_init() {
+        Ok(source) => println!(
+            "TSC: {} kHz ({}){}",
+            tsc::tsc_frequency() / 1000,
+            source,
+            if tsc::tsc_invariant() { ", invariant" } else { "" }
+        ),
+        Err(e) => println!("TSC: {}", e),
+    }
+    if let Some(cs) = time::clocksource_current() {
+        println!("Clocksource: {}", cs.name);
+    }
+
+    match rtc::rtc_init() {
+        Ok(now) => println!("RTC: {} UTC", now),
+        Err(e) => println!("RTC: {}", e),
+    }
+
+    match i8042::i8042_init() {
+        Ok(()) => {
+            match keyboard::keyboard_init() {
+                Ok(()) => println!("Keyboard: PS/2 ({})", keyboard::keyboard_layout().name),
+                Err(e) => println!("Keyboard: {}", e),
+            }
+            match mouse::mouse_init() {
+                Ok(kind) => {
+                    println!("Mouse: PS/2 ({})", kind);
+                    // mousecursor 顯示文字模式的方塊游標
+                    let text_cursor = boot::boot_info()
+                        .and_then(|info| info.cmdline())
+                        .is_some_and(mouse::mouse_cursor_from_cmdline);
+                    if text_cursor {
+                        mouse::mouse_set_text_cursor(true);
+                    }
+                }
+                Err(e) => println!("Mouse: {}", e),
+            }
+        }
+        Err(e) => println!("PS/2: {}", e),
+    }
+
+    cpu::cpu_enable_interrupts();
+
+    #[cfg(test)]
+    crate::test_main();
 
     // unsafe {
     //     core::arch::asm!(
@@ -73,6 +206,9 @@ pub extern "C" fn _kernel_main() {
     // }
 
     loop {
-        cpu::cpu_idle();
+        tty::tty_console_write(SHELL_CONSOLE, "> ");
+        if ldisc::tty_read_line(SHELL_CONSOLE).is_none() {
+            tty::tty_console_write(SHELL_CONSOLE, "\n");
+        }
     }
 }
\ No newline at end of file
@@ -0,0 +1,77 @@
+// src/kernel/ksym.rs
+use core::ffi::CStr;
+use core::fmt;
+use core::mem;
+use core::ptr;
+use core::slice;
+
+/// 符號表項，格式見 scripts/gen-ksymtab.sh
+#[repr(C)]
+struct KsymEntry {
+    addr: u32,
+    /// 符號大小，匯編標籤沒有大小時為 0
+    size: u32,
+    /// 名稱在字串區中的偏移
+    name: u32,
+}
+
+// linker.ld 中 .ksymtab 段的邊界，第一次連結時為空
+extern "C" {
+    static __ksymtab_start: u8;
+    static __ksymtab_end: u8;
+}
+
+/// 符號表項與字串區
+fn ksymtab() -> Option<(&'static [KsymEntry], *const u8)> {
+    let start = ptr::addr_of!(__ksymtab_start) as usize;
+    let end = ptr::addr_of!(__ksymtab_end) as usize;
+
+    if end - start < mem::size_of::<u32>() {
+        return None;
+    }
+
+    unsafe {
+        let count = *(start as *const u32) as usize;
+        let entries = (start + mem::size_of::<u32>()) as *const KsymEntry;
+        let names = entries.add(count) as *const u8;
+
+        if names as usize > end {
+            return None;
+        }
+
+        Some((slice::from_raw_parts(entries, count), names))
+    }
+}
+
+/// 查找地址所屬的內核符號
+///
+/// # 參數
+/// * `addr` - 內核虛擬地址
+/// # 返回
+/// `(符號名稱, 地址相對符號起始的偏移)`，找不到或未嵌入符號表時返回 `None`
+pub fn lookup(addr: usize) -> Option<(&'static str, usize)> {
+    let (entries, names) = ksymtab()?;
+
+    let idx = entries.partition_point(|e| e.addr as usize <= addr).checked_sub(1)?;
+    let entry = &entries[idx];
+    let offset = addr - entry.addr as usize;
+
+    if entry.size != 0 && offset >= entry.size as usize {
+        return None;
+    }
+
+    let name = unsafe { CStr::from_ptr(names.add(entry.name as usize).cast()) };
+    Some((name.to_str().ok()?, offset))
+}
+
+/// 以 `名稱+0x偏移` 格式輸出地址的包裝
+pub struct Ksym(pub usize);
+
+impl fmt::Display for Ksym {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match lookup(self.0) {
+            Some((name, offset)) => write!(f, "{}+0x{:x}", name, offset),
+            None => write!(f, "??"),
+        }
+    }
+}
@@ -1,4 +1,13 @@
+#[cfg(not(feature = "std"))]
 pub mod kernel;
+#[cfg(not(feature = "std"))]
+pub mod ksym;
+#[cfg(not(feature = "std"))]
+pub mod panic;
+#[cfg(all(test, not(feature = "std")))]
+pub mod test;
+#[cfg(not(feature = "std"))]
+pub mod time;
 pub mod tty;
 pub mod asm;
 
@@ -0,0 +1,119 @@
+// src/kernel/panic.rs
+use core::panic::PanicInfo;
+use crate::drivers::serial;
+use crate::hal::cpu;
+use crate::kernel::ksym::Ksym;
+use crate::kernel::tty::tty::{self, VGA_COLOR_LIGHT_RED, VGA_COLOR_RED, VGA_COLOR_WHITE};
+use crate::mm::{self, paging};
+use crate::{print, println};
+
+/// 回溯的最大深度
+const BACKTRACE_MAX_DEPTH: usize = 32;
+
+static mut PANICKING: bool = false;
+
+/// 停機，或在啟用 `qemu-exit` 時以失敗狀態退出 QEMU
+fn panic_halt() -> ! {
+    #[cfg(feature = "qemu-exit")]
+    crate::hal::qemu::qemu_exit(crate::hal::qemu::QemuExitCode::Failed);
+
+    #[cfg(not(feature = "qemu-exit"))]
+    loop {
+        cpu::cpu_disable_interrupts();
+        cpu::cpu_halt();
+    }
+}
+
+/// 堆疊框架地址是否可以安全讀取
+fn frame_readable(ebp: usize) -> bool {
+    ebp >= mm::KERNEL_VIRT_BASE
+        && ebp.is_multiple_of(4)
+        && paging::translate(ebp).is_some()
+        && paging::translate(ebp + 4).is_some()
+}
+
+/// 沿著 EBP 鏈輸出呼叫堆疊
+///
+/// 依賴 `-C force-frame-pointers=yes`，每個框架的 `[ebp]` 為上一層的 EBP，
+/// `[ebp + 4]` 為返回地址
+///
+/// # 參數
+/// * `ebp` - 起始框架的 EBP
+pub fn print_backtrace(mut ebp: usize) {
+    println!("Backtrace:");
+
+    for depth in 0..BACKTRACE_MAX_DEPTH {
+        if !frame_readable(ebp) {
+            return;
+        }
+
+        let (next, ret) = unsafe { (*(ebp as *const usize), *((ebp + 4) as *const usize)) };
+        if ret == 0 {
+            return;
+        }
+
+        println!("  #{:<2} 0x{:08x} {}", depth, ret, Ksym(ret));
+
+        // 堆疊向低地址增長，上一層框架必定在更高的地址
+        if next <= ebp {
+            return;
+        }
+        ebp = next;
+    }
+
+    println!("  ...");
+}
+
+/// 輸出 panic 時的堆疊與控制暫存器
+///
+/// 通用暫存器在編譯後的程式碼中只是編譯器留下的暫存值，不輸出；
+/// 異常引起的 panic 已由異常處理輸出出錯時的完整暫存器
+fn dump_registers(ebp: u32, esp: u32) {
+    println!("EBP: 0x{:08x} ESP: 0x{:08x} EFLAGS: 0x{:08x}", ebp, esp, cpu::cpu_r_eflags().bits());
+    println!(
+        "CR0: 0x{:08x} CR2: 0x{:08x} CR3: 0x{:08x} CR4: 0x{:08x}",
+        cpu::cpu_r_cr0().bits(),
+        cpu::cpu_r_cr2(),
+        cpu::cpu_r_cr3(),
+        cpu::cpu_r_cr4().bits()
+    );
+}
+
+/// 內核 panic 處理
+///
+/// 關閉中斷、強制釋放 TTY 鎖後輸出 panic 資訊、暫存器與回溯，然後停機
+#[cfg_attr(test, allow(dead_code))]
+pub fn kernel_panic(info: &PanicInfo) -> ! {
+    cpu::cpu_disable_interrupts();
+    let (ebp, esp) = (cpu::cpu_r_ebp(), cpu::cpu_r_esp());
+
+    unsafe {
+        // 輸出過程中再次 panic 時不再嘗試輸出
+        if PANICKING {
+            panic_halt();
+        }
+        PANICKING = true;
+
+        tty::tty_force_unlock();
+        serial::serial_force_unlock();
+    }
+    tty::tty_switch(tty::tty_log_console());
+
+    let theme = (tty::tty_get_theme() >> 8) as u8;
+
+    tty::tty_set_theme(VGA_COLOR_WHITE, VGA_COLOR_RED);
+    print!("KERNEL PANIC");
+    if let Some(location) = info.location() {
+        print!(" at {}:{}:{}", location.file(), location.line(), location.column());
+    }
+    println!();
+
+    tty::tty_set_theme(VGA_COLOR_LIGHT_RED, theme >> 4);
+    println!("{}", info.message());
+
+    tty::tty_set_theme(theme & 0x0F, theme >> 4);
+    dump_registers(ebp, esp);
+    print_backtrace(ebp as usize);
+
+    panic_halt()
+}
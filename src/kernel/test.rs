@@ -0,0 +1,164 @@
+// src/kernel/test.rs
+use core::any;
+use core::mem;
+use core::panic::PanicInfo;
+use core::ptr;
+use crate::drivers::serial;
+use crate::hal::cpu;
+use crate::hal::qemu::{self, QemuExitCode};
+use crate::kernel::tty::tty;
+use crate::{serial_print, serial_println};
+
+/// 可由 `#[test_case]` 收集的測試
+pub trait Testable {
+    /// 測試名稱
+    fn name(&self) -> &'static str;
+
+    /// 執行測試，返回即視為通過
+    fn run(&self);
+
+    /// 是否預期 panic
+    fn should_panic(&self) -> bool {
+        false
+    }
+
+    /// 預期的 panic 訊息，`None` 表示任何 panic 皆可
+    fn expected_panic(&self) -> Option<&'static str> {
+        None
+    }
+}
+
+impl<T: Fn()> Testable for T {
+    fn name(&self) -> &'static str {
+        any::type_name::<T>()
+    }
+
+    fn run(&self) {
+        self()
+    }
+}
+
+/// 預期會 panic 的測試
+///
+/// ```ignore
+/// #[test_case]
+/// static INVALID_OPCODE_PANICS: ShouldPanic = ShouldPanic("invalid_opcode_panics", || unsafe {
+///     core::arch::asm!("ud2");
+/// });
+/// ```
+pub struct ShouldPanic(pub &'static str, pub fn());
+
+impl Testable for ShouldPanic {
+    fn name(&self) -> &'static str {
+        self.0
+    }
+
+    fn run(&self) {
+        (self.1)();
+        serial_println!("[failed]");
+        serial_println!("Error: test did not panic");
+        qemu::qemu_exit(QemuExitCode::Failed);
+    }
+
+    fn should_panic(&self) -> bool {
+        true
+    }
+}
+
+/// 預期以指定訊息 panic 的測試
+///
+/// 訊息需為不含格式化參數的字面值
+///
+/// ```ignore
+/// #[test_case]
+/// static OOPS_PANICS: ShouldPanicWith = ShouldPanicWith("oops_panics", "oops", || panic!("oops"));
+/// ```
+pub struct ShouldPanicWith(pub &'static str, pub &'static str, pub fn());
+
+impl Testable for ShouldPanicWith {
+    fn name(&self) -> &'static str {
+        self.0
+    }
+
+    fn run(&self) {
+        ShouldPanic(self.0, self.2).run()
+    }
+
+    fn should_panic(&self) -> bool {
+        true
+    }
+
+    fn expected_panic(&self) -> Option<&'static str> {
+        Some(self.1)
+    }
+}
+
+static mut TESTS: &[&dyn Testable] = &[];
+static mut CURRENT_TEST: usize = 0;
+
+/// 從指定索引開始執行剩餘的測試，全部通過後以成功狀態退出
+fn run_tests_from(index: usize) -> ! {
+    let tests = unsafe { *ptr::addr_of!(TESTS) };
+
+    for (i, test) in tests.iter().enumerate().skip(index) {
+        unsafe {
+            CURRENT_TEST = i;
+        }
+
+        serial_print!("{}...\t", test.name());
+        test.run();
+        serial_println!("[ok]");
+    }
+
+    serial_println!("All {} tests passed", tests.len());
+    qemu::qemu_exit(QemuExitCode::Success)
+}
+
+/// 測試執行器，由編譯器產生的 `test_main` 呼叫
+///
+/// `make test` 以測試模式建置內核，`_kernel_main` 在初始化完成後呼叫 `test_main`，
+/// 結果輸出到 COM1，最後經由 `isa-debug-exit` 以成功或失敗狀態關閉 QEMU
+pub fn test_runner(tests: &[&dyn Testable]) {
+    serial_println!("Running {} tests", tests.len());
+
+    // 測試列表位於 `test_main` 的堆疊框架中，而 `run_tests_from` 不會返回，
+    // 之後從 panic 處理繼續執行時該框架也仍然存在
+    unsafe {
+        TESTS = mem::transmute::<&[&dyn Testable], &'static [&'static dyn Testable]>(tests);
+    }
+
+    run_tests_from(0)
+}
+
+/// 測試模式下的 panic 處理
+///
+/// 預期 panic 的測試視為通過並繼續執行下一個測試（panic 時的堆疊不會被回收），
+/// 其他 panic 則輸出訊息並以失敗狀態退出
+pub fn test_panic(info: &PanicInfo) -> ! {
+    cpu::cpu_disable_interrupts();
+
+    unsafe {
+        tty::tty_force_unlock();
+        serial::serial_force_unlock();
+    }
+
+    let (tests, current) = unsafe { (*ptr::addr_of!(TESTS), CURRENT_TEST) };
+
+    let expected = |test: &&dyn Testable| {
+        test.should_panic() && test.expected_panic().is_none_or(|msg| info.message().as_str() == Some(msg))
+    };
+    if tests.get(current).is_some_and(expected) {
+        serial_println!("[ok]");
+        cpu::cpu_enable_interrupts();
+        run_tests_from(current + 1);
+    }
+
+    serial_println!("[failed]");
+    serial_print!("Error: ");
+    if let Some(location) = info.location() {
+        serial_print!("{}:{}:{}: ", location.file(), location.line(), location.column());
+    }
+    serial_println!("{}", info.message());
+
+    qemu::qemu_exit(QemuExitCode::Failed)
+}
@@ -0,0 +1,285 @@
+// src/kernel/time.rs
+use core::ptr;
+use core::sync::atomic::{AtomicU32, AtomicU64, Ordering};
+use crate::drivers::pit;
+use crate::hal::cpu;
+use crate::libs::sync::SpinLock;
+
+/// 同時存在的單次計時器上限
+pub const MAX_TIMERS: usize = 32;
+
+pub const NANOS_PER_SEC: u64 = 1_000_000_000;
+const NANOS_PER_MILLI: u64 = 1_000_000;
+const NANOS_PER_MICRO: u64 = 1_000;
+
+/// 時鐘源
+pub struct Clocksource {
+    pub name: &'static str,
+    /// 優先級，註冊時選用最高者
+    pub rating: u32,
+    /// 讀取時鐘源自身起點以來的時間 (ns)
+    pub read_ns: fn() -> u64,
+    /// 是否依賴時鐘中斷推進，中斷關閉時不會前進
+    pub irq_driven: bool,
+}
+
+/// 計時器回調，在時鐘中斷中執行，不可睡眠
+///
+/// # 參數
+/// * `data` - 註冊時傳入的值
+pub type TimerCallback = fn(data: usize);
+
+/// 計時器編號，用於取消
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TimerId(u32);
+
+#[derive(Clone, Copy)]
+struct Timer {
+    id: u32,
+    /// 到期時間（開機以來的 ns）
+    deadline: u64,
+    callback: TimerCallback,
+    data: usize,
+}
+
+/// 開機以來的時鐘中斷次數
+static TICKS: AtomicU64 = AtomicU64::new(0);
+static NEXT_TIMER_ID: AtomicU32 = AtomicU32::new(1);
+static TIMERS: SpinLock<[Option<Timer>; MAX_TIMERS]> = SpinLock::new([None; MAX_TIMERS]);
+
+static mut CLOCKSOURCE: Option<&'static Clocksource> = None;
+/// 加到時鐘源讀數上的偏移，切換時鐘源時保持 uptime 連續
+static mut CLOCKSOURCE_OFFSET: u64 = 0;
+
+/// 將計數器週期數換算為納秒
+///
+/// # 參數
+/// * `cycles` - 週期數
+/// * `hz` - 計數器頻率
+pub fn cycles_to_ns(cycles: u64, hz: u64) -> u64 {
+    cycles / hz * NANOS_PER_SEC + cycles % hz * NANOS_PER_SEC / hz
+}
+
+/// 將納秒換算為計數器週期數
+///
+/// # 參數
+/// * `ns` - 時間
+/// * `hz` - 計數器頻率
+pub fn ns_to_cycles(ns: u64, hz: u64) -> u64 {
+    ns / NANOS_PER_SEC * hz + ns % NANOS_PER_SEC * hz / NANOS_PER_SEC
+}
+
+/// 註冊時鐘源
+///
+/// 優先級高於目前的時鐘源時切換過去，切換前後 uptime 保持連續
+pub fn clocksource_register(cs: &'static Clocksource) {
+    let irq_enabled = cpu::cpu_save_interrupts();
+
+    unsafe {
+        let current = *ptr::addr_of!(CLOCKSOURCE);
+        if current.is_none_or(|c| cs.rating > c.rating) {
+            let now = uptime_ns();
+            CLOCKSOURCE_OFFSET = now.wrapping_sub((cs.read_ns)());
+            CLOCKSOURCE = Some(cs);
+        }
+    }
+
+    cpu::cpu_restore_interrupts(irq_enabled);
+}
+
+/// 目前使用的時鐘源
+pub fn clocksource_current() -> Option<&'static Clocksource> {
+    unsafe { *ptr::addr_of!(CLOCKSOURCE) }
+}
+
+/// 時鐘中斷的通用處理，由時鐘驅動在每次中斷時呼叫
+///
+/// 累加計數並執行所有已到期的計時器
+pub fn time_tick() {
+    TICKS.fetch_add(1, Ordering::Relaxed);
+
+    let now = uptime_ns();
+    let mut expired = [None; MAX_TIMERS];
+
+    // 先取出到期的計時器再執行回調，回調中可以重新註冊計時器
+    {
+        let mut timers = TIMERS.lock();
+        for (slot, out) in timers.iter_mut().zip(expired.iter_mut()) {
+            if slot.is_some_and(|t| t.deadline <= now) {
+                *out = slot.take();
+            }
+        }
+    }
+
+    for timer in expired.iter().flatten() {
+        (timer.callback)(timer.data);
+    }
+}
+
+/// 開機以來的時鐘中斷次數
+#[allow(dead_code)]
+pub fn ticks() -> u64 {
+    TICKS.load(Ordering::Relaxed)
+}
+
+/// 開機以來經過的時間 (ns)
+///
+/// 解析度取決於目前的時鐘源，尚無時鐘源時為 0
+pub fn uptime_ns() -> u64 {
+    unsafe {
+        match *ptr::addr_of!(CLOCKSOURCE) {
+            Some(cs) => (cs.read_ns)().wrapping_add(CLOCKSOURCE_OFFSET),
+            None => 0,
+        }
+    }
+}
+
+/// 開機以來經過的時間 (ms)
+#[allow(dead_code)]
+pub fn uptime_ms() -> u64 {
+    uptime_ns() / NANOS_PER_MILLI
+}
+
+/// 忙等待指定的納秒數
+///
+/// 時鐘源依賴中斷而中斷已關閉時，改為輪詢 PIT 計數值
+///
+/// # 參數
+/// * `ns` - 等待時間
+pub fn delay_ns(ns: u64) {
+    let cs = clocksource_current();
+
+    if cs.is_none_or(|cs| cs.irq_driven && !cpu::cpu_interrupts_enabled()) {
+        pit::pit_poll_wait_ns(ns);
+        return;
+    }
+
+    let deadline = uptime_ns() + ns;
+    while uptime_ns() < deadline {
+        cpu::cpu_pause();
+    }
+}
+
+/// 忙等待指定的微秒數
+#[allow(dead_code)]
+pub fn delay_us(us: u64) {
+    delay_ns(us * NANOS_PER_MICRO);
+}
+
+/// 睡眠指定的毫秒數
+///
+/// 中斷啟用時在時鐘中斷之間停機等待，中斷關閉時改為忙等待
+///
+/// # 參數
+/// * `ms` - 睡眠時間
+#[allow(dead_code)]
+pub fn sleep_ms(ms: u64) {
+    if !cpu::cpu_interrupts_enabled() {
+        delay_ns(ms * NANOS_PER_MILLI);
+        return;
+    }
+
+    let deadline = uptime_ns() + ms * NANOS_PER_MILLI;
+    while uptime_ns() < deadline {
+        cpu::cpu_idle();
+    }
+}
+
+/// 註冊單次計時器
+///
+/// # 參數
+/// * `delay_ms` - 延遲時間，精度為一個時鐘中斷週期
+/// * `callback` - 到期時在時鐘中斷中執行的回調
+/// * `data` - 傳給回調的值
+/// # 返回
+/// 計時器編號，已達上限時返回 `None`
+#[allow(dead_code)]
+pub fn timer_add(delay_ms: u64, callback: TimerCallback, data: usize) -> Option<TimerId> {
+    let deadline = uptime_ns() + delay_ms * NANOS_PER_MILLI;
+    let mut timers = TIMERS.lock();
+
+    let slot = timers.iter_mut().find(|slot| slot.is_none())?;
+    let id = NEXT_TIMER_ID.fetch_add(1, Ordering::Relaxed);
+    *slot = Some(Timer { id, deadline, callback, data });
+
+    Some(TimerId(id))
+}
+
+/// 取消尚未到期的計時器
+///
+/// # 返回
+/// 計時器已到期或不存在時返回 `false`
+#[allow(dead_code)]
+pub fn timer_cancel(id: TimerId) -> bool {
+    let mut timers = TIMERS.lock();
+
+    match timers.iter_mut().find(|slot| slot.is_some_and(|t| t.id == id.0)) {
+        Some(slot) => {
+            *slot = None;
+            true
+        }
+        None => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::sync::atomic::AtomicUsize;
+
+    static FIRED: AtomicUsize = AtomicUsize::new(0);
+
+    fn record(data: usize) {
+        FIRED.fetch_add(data, Ordering::Relaxed);
+    }
+
+    #[test_case]
+    fn cycle_conversion() {
+        assert_eq!(cycles_to_ns(1_193_182, 1_193_182), NANOS_PER_SEC);
+        assert_eq!(cycles_to_ns(1193, 1_193_182), 999_847);
+        assert_eq!(ns_to_cycles(NANOS_PER_SEC + 500, 2_000_000_000), 2_000_001_000);
+
+        // 一年的週期數也不會溢出
+        let year = 365 * 24 * 3600;
+        assert_eq!(cycles_to_ns(3_000_000_000 * year, 3_000_000_000), year * NANOS_PER_SEC);
+    }
+
+    #[test_case]
+    fn uptime_is_monotonic() {
+        let mut last = uptime_ns();
+        for _ in 0..1000 {
+            let now = uptime_ns();
+            assert!(now >= last);
+            last = now;
+        }
+    }
+
+    #[test_case]
+    fn busy_wait_delay() {
+        let start = uptime_ns();
+        delay_us(500);
+        assert!(uptime_ns() - start >= 500 * NANOS_PER_MICRO);
+    }
+
+    #[test_case]
+    fn sleep_advances_uptime() {
+        let start_ticks = ticks();
+        let start = uptime_ms();
+        sleep_ms(20);
+
+        assert!(uptime_ms() - start >= 20);
+        assert!(ticks() > start_ticks);
+    }
+
+    #[test_case]
+    fn one_shot_timers() {
+        FIRED.store(0, Ordering::Relaxed);
+        timer_add(5, record, 1).expect("no free timer");
+        let cancelled = timer_add(5, record, 100).expect("no free timer");
+        assert!(timer_cancel(cancelled));
+
+        sleep_ms(20);
+        assert_eq!(FIRED.load(Ordering::Relaxed), 1);
+        assert!(!timer_cancel(cancelled));
+    }
+}
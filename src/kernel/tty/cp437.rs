@@ -0,0 +1,109 @@
+// src/kernel/tty/cp437.rs
+//! Unicode 與 VGA 內建字型（代碼頁 437）之間的轉換
+
+/// 無法顯示的字元以此字形（`■`）代替
+pub const CP437_REPLACEMENT: u8 = 0xFE;
+
+/// 0x00-0x1F 的字形，0x00 顯示為空白
+const CP437_LOW: [char; 32] = [
+    ' ', '☺', '☻', '♥', '♦', '♣', '♠', '•', '◘', '○', '◙', '♂', '♀', '♪', '♫', '☼',
+    '►', '◄', '↕', '‼', '¶', '§', '▬', '↨', '↑', '↓', '→', '←', '∟', '↔', '▲', '▼',
+];
+
+/// 0x7F 的字形
+const CP437_DEL: char = '⌂';
+
+/// 0x80-0xFF 的字形
+const CP437_HIGH: [char; 128] = [
+    'Ç', 'ü', 'é', 'â', 'ä', 'à', 'å', 'ç', 'ê', 'ë', 'è', 'ï', 'î', 'ì', 'Ä', 'Å',
+    'É', 'æ', 'Æ', 'ô', 'ö', 'ò', 'û', 'ù', 'ÿ', 'Ö', 'Ü', '¢', '£', '¥', '₧', 'ƒ',
+    'á', 'í', 'ó', 'ú', 'ñ', 'Ñ', 'ª', 'º', '¿', '⌐', '¬', '½', '¼', '¡', '«', '»',
+    '░', '▒', '▓', '│', '┤', '╡', '╢', '╖', '╕', '╣', '║', '╗', '╝', '╜', '╛', '┐',
+    '└', '┴', '┬', '├', '─', '┼', '╞', '╟', '╚', '╔', '╩', '╦', '╠', '═', '╬', '╧',
+    '╨', '╤', '╥', '╙', '╘', '╒', '╓', '╫', '╪', '┘', '┌', '█', '▄', '▌', '▐', '▀',
+    'α', 'ß', 'Γ', 'π', 'Σ', 'σ', 'µ', 'τ', 'Φ', 'Θ', 'Ω', 'δ', '∞', 'φ', 'ε', '∩',
+    '≡', '±', '≥', '≤', '⌠', '⌡', '÷', '≈', '°', '∙', '·', '√', 'ⁿ', '²', '■', '\u{a0}',
+];
+
+/// 外形相同或相近、沒有獨立字形的字元
+const CP437_ALIASES: [(char, u8); 18] = [
+    ('β', 0xE1),
+    ('\u{3bc}', 0xE6),
+    ('\u{2126}', 0xEA),
+    ('ϕ', 0xED),
+    ('∅', 0xED),
+    ('∈', 0xEE),
+    ('∑', 0xE4),
+    ('▪', 0xFE),
+    ('‘', b'\''),
+    ('’', b'\''),
+    ('“', b'"'),
+    ('”', b'"'),
+    ('–', b'-'),
+    ('—', b'-'),
+    ('、', b','),
+    ('。', b'.'),
+    ('\u{3000}', b' '),
+    ('\u{fffd}', CP437_REPLACEMENT),
+];
+
+/// 將字元轉換為代碼頁 437 的字形
+///
+/// 全形 ASCII（`！`-`～`）轉換為對應的半形字元
+///
+/// # 返回
+/// 字形編號，沒有對應的字形時返回 `None`
+pub fn cp437_from_char(chr: char) -> Option<u8> {
+    match chr {
+        ' '..='~' => return Some(chr as u8),
+        CP437_DEL => return Some(0x7F),
+        '\u{ff01}'..='\u{ff5e}' => return Some((chr as u32 - 0xFEE0) as u8),
+        _ => {}
+    }
+
+    if let Some(index) = CP437_HIGH.iter().position(|&c| c == chr) {
+        return Some(0x80 + index as u8);
+    }
+    if let Some(index) = CP437_LOW[1..].iter().position(|&c| c == chr) {
+        return Some(1 + index as u8);
+    }
+    CP437_ALIASES.iter().find(|&&(c, _)| c == chr).map(|&(_, glyph)| glyph)
+}
+
+/// 代碼頁 437 字形對應的字元
+#[allow(dead_code)]
+pub fn cp437_to_char(glyph: u8) -> char {
+    match glyph {
+        0x00..=0x1F => CP437_LOW[glyph as usize],
+        0x7F => CP437_DEL,
+        0x80..=0xFF => CP437_HIGH[glyph as usize - 0x80],
+        _ => glyph as char,
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        for glyph in 1..=0xFF {
+            assert_eq!(cp437_from_char(cp437_to_char(glyph)), Some(glyph), "0x{:02x}", glyph);
+        }
+    }
+
+    #[test]
+    fn box_drawing_and_aliases() {
+        let glyphs: Vec<_> = "┌─┐│└┘╔═╗░▓█".chars().map(cp437_from_char).collect();
+        assert_eq!(glyphs, [0xDA, 0xC4, 0xBF, 0xB3, 0xC0, 0xD9, 0xC9, 0xCD, 0xBB, 0xB0, 0xB2, 0xDB].map(Some));
+        assert_eq!(cp437_from_char('β'), cp437_from_char('ß'));
+        assert_eq!(cp437_from_char('”'), Some(b'"'));
+    }
+
+    #[test]
+    fn fullwidth_and_unmappable() {
+        assert_eq!("（Ａ）：".chars().filter_map(cp437_from_char).collect::<Vec<_>>(), b"(A):");
+        assert_eq!(cp437_from_char('中'), None);
+        assert_eq!(cp437_from_char('\u{fffd}'), Some(CP437_REPLACEMENT));
+    }
+}
@@ -0,0 +1,589 @@
+// src/kernel/tty/ldisc.rs
+use alloc::collections::VecDeque;
+use alloc::string::String;
+use alloc::vec::Vec;
+use crate::drivers::keyboard::event::KeyEvent;
+use crate::drivers::keyboard::scancode::KeyCode;
+use super::tty::{self, TTY_WIDTH};
+
+/// 一行最多的字元數
+pub const LINE_MAX: usize = 256;
+/// 歷史記錄保留的行數
+pub const HISTORY_SIZE: usize = 32;
+
+// 編輯用的控制字元
+const CTRL_A: char = '\x01';
+const CTRL_C: char = '\x03';
+const CTRL_D: char = '\x04';
+const CTRL_E: char = '\x05';
+const CTRL_K: char = '\x0b';
+const CTRL_U: char = '\x15';
+const CTRL_W: char = '\x17';
+const BACKSPACE: char = '\x08';
+const DEL: char = '\x7f';
+
+/// 行規程的輸入
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Input {
+    /// 字元，包括控制字元
+    Char(char),
+    Left,
+    Right,
+    Up,
+    Down,
+    Home,
+    End,
+    Delete,
+}
+
+impl Input {
+    /// 由按鍵事件轉換，放開按鍵與不產生輸入的按鍵返回 `None`
+    pub fn from_key(event: &KeyEvent) -> Option<Self> {
+        if !event.pressed {
+            return None;
+        }
+
+        match event.code {
+            KeyCode::LEFT => Some(Input::Left),
+            KeyCode::RIGHT => Some(Input::Right),
+            KeyCode::UP => Some(Input::Up),
+            KeyCode::DOWN => Some(Input::Down),
+            KeyCode::HOME => Some(Input::Home),
+            KeyCode::END => Some(Input::End),
+            KeyCode::DELETE => Some(Input::Delete),
+            _ => event.ch.map(Input::Char),
+        }
+    }
+}
+
+/// 行規程模式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LdiscMode {
+    /// 規範模式：輸入以行為單位，提交前可以編輯；否則逐字元讀取
+    pub canonical: bool,
+    /// 是否將輸入回顯到螢幕
+    pub echo: bool,
+}
+
+impl LdiscMode {
+    pub const CANONICAL: LdiscMode = LdiscMode { canonical: true, echo: true };
+    #[allow(dead_code)]
+    pub const RAW: LdiscMode = LdiscMode { canonical: false, echo: false };
+}
+
+/// 輸入一個編輯操作後的結果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LineStatus {
+    /// 仍在編輯
+    Pending,
+    /// 按下 Enter，返回整行（不含換行）
+    Line(String),
+    /// 在空行按下 Ctrl+D
+    Eof,
+}
+
+/// 行編輯器
+///
+/// 編輯內容直接重繪在 TTY 上，從開始編輯時的游標位置起算；
+/// 行超出螢幕底部造成捲動時會調整起點
+pub struct LineEditor {
+    line: Vec<char>,
+    cursor: usize,
+    /// 行的起點在螢幕上的位置
+    origin: (usize, usize),
+    /// 是否已記錄起點
+    active: bool,
+    echo: bool,
+    history: VecDeque<String>,
+    /// 正在瀏覽的歷史項目，`None` 表示正在編輯新行
+    history_index: Option<usize>,
+    /// 開始瀏覽歷史前正在編輯的內容
+    saved: Vec<char>,
+}
+
+impl LineEditor {
+    pub const fn new() -> Self {
+        Self {
+            line: Vec::new(),
+            cursor: 0,
+            origin: (0, 0),
+            active: false,
+            echo: true,
+            history: VecDeque::new(),
+            history_index: None,
+            saved: Vec::new(),
+        }
+    }
+
+    pub fn set_echo(&mut self, echo: bool) {
+        self.echo = echo;
+    }
+
+    /// 目前正在編輯的內容
+    #[allow(dead_code)]
+    pub fn line(&self) -> String {
+        self.line.iter().collect()
+    }
+
+    /// 游標在行內的位置
+    #[allow(dead_code)]
+    pub fn cursor(&self) -> usize {
+        self.cursor
+    }
+
+    /// 歷史記錄，0 為最舊
+    #[allow(dead_code)]
+    pub fn history(&self) -> impl Iterator<Item = &str> {
+        self.history.iter().map(String::as_str)
+    }
+
+    /// 輸入一個編輯操作
+    pub fn feed(&mut self, input: Input) -> LineStatus {
+        if !self.active {
+            self.origin = tty::tty_get_cpos();
+            self.active = true;
+        }
+        let old_len = self.line.len();
+
+        match input {
+            Input::Char('\n') => return self.submit(),
+            Input::Char(CTRL_C) => {
+                self.cursor = self.line.len();
+                self.redraw(old_len);
+                self.echo_str("^C\n");
+                self.reset_line();
+            }
+            Input::Char(CTRL_D) if self.line.is_empty() => {
+                self.reset_line();
+                return LineStatus::Eof;
+            }
+            Input::Char(CTRL_D) | Input::Delete => {
+                if self.cursor < self.line.len() {
+                    self.line.remove(self.cursor);
+                }
+            }
+            Input::Char(BACKSPACE) | Input::Char(DEL) => {
+                if self.cursor > 0 {
+                    self.cursor -= 1;
+                    self.line.remove(self.cursor);
+                }
+            }
+            Input::Char(CTRL_U) => {
+                self.line.drain(..self.cursor);
+                self.cursor = 0;
+            }
+            Input::Char(CTRL_K) => self.line.truncate(self.cursor),
+            Input::Char(CTRL_W) => {
+                let end = self.cursor;
+                while self.cursor > 0 && self.line[self.cursor - 1] == ' ' {
+                    self.cursor -= 1;
+                }
+                while self.cursor > 0 && self.line[self.cursor - 1] != ' ' {
+                    self.cursor -= 1;
+                }
+                self.line.drain(self.cursor..end);
+            }
+            Input::Char(CTRL_A) | Input::Home => self.cursor = 0,
+            Input::Char(CTRL_E) | Input::End => self.cursor = self.line.len(),
+            Input::Left => self.cursor = self.cursor.saturating_sub(1),
+            Input::Right => self.cursor = (self.cursor + 1).min(self.line.len()),
+            Input::Up => self.history_prev(),
+            Input::Down => self.history_next(),
+            Input::Char(c) if c.is_control() => {}
+            Input::Char(c) => {
+                if self.line.len() < LINE_MAX {
+                    self.line.insert(self.cursor, c);
+                    self.cursor += 1;
+                }
+            }
+        }
+
+        if self.active {
+            self.redraw(old_len);
+        }
+        LineStatus::Pending
+    }
+
+    /// 提交目前的行並加入歷史記錄
+    fn submit(&mut self) -> LineStatus {
+        let line: String = self.line.iter().collect();
+        self.cursor = self.line.len();
+        self.redraw(self.line.len());
+        self.echo_str("\n");
+
+        if !line.is_empty() && self.history.back() != Some(&line) {
+            if self.history.len() == HISTORY_SIZE {
+                self.history.pop_front();
+            }
+            self.history.push_back(line.clone());
+        }
+
+        self.reset_line();
+        LineStatus::Line(line)
+    }
+
+    fn reset_line(&mut self) {
+        self.line.clear();
+        self.saved.clear();
+        self.cursor = 0;
+        self.history_index = None;
+        self.active = false;
+    }
+
+    fn history_prev(&mut self) {
+        let index = match self.history_index {
+            None if self.history.is_empty() => return,
+            None => {
+                self.saved = core::mem::take(&mut self.line);
+                self.history.len() - 1
+            }
+            Some(0) => return,
+            Some(i) => i - 1,
+        };
+        self.history_index = Some(index);
+        self.line = self.history[index].chars().collect();
+        self.cursor = self.line.len();
+    }
+
+    fn history_next(&mut self) {
+        match self.history_index {
+            None => return,
+            Some(i) if i + 1 < self.history.len() => {
+                self.history_index = Some(i + 1);
+                self.line = self.history[i + 1].chars().collect();
+            }
+            Some(_) => {
+                self.history_index = None;
+                self.line = core::mem::take(&mut self.saved);
+            }
+        }
+        self.cursor = self.line.len();
+    }
+
+    fn echo_str(&self, s: &str) {
+        if self.echo {
+            tty::tty_put_str(s);
+        }
+    }
+
+    /// 重繪整行並將 TTY 游標移到編輯位置
+    ///
+    /// # 參數
+    /// * `old_len` - 修改前的行長度，多出的部分以空白覆蓋
+    fn redraw(&mut self, old_len: usize) {
+        if !self.echo {
+            return;
+        }
+
+        let (x, y) = self.origin;
+        tty::tty_set_cpos(x, y);
+        for &c in &self.line {
+            tty::tty_put_char(c);
+        }
+        for _ in self.line.len()..old_len {
+            tty::tty_put_char(' ');
+        }
+
+        // 寫到螢幕底部時 TTY 會向上捲動，起點跟著上移
+        let end = x + self.line.len().max(old_len);
+        let expected_y = y + end / TTY_WIDTH;
+        let (_, actual_y) = tty::tty_get_cpos();
+        self.origin.1 = y.saturating_sub(expected_y.saturating_sub(actual_y));
+
+        let offset = self.origin.0 + self.cursor;
+        tty::tty_set_cpos(offset % TTY_WIDTH, self.origin.1 + offset / TTY_WIDTH);
+    }
+}
+
+#[cfg(not(feature = "std"))]
+mod console {
+    use alloc::string::String;
+    use crate::drivers::keyboard::{self, event::KeyEvent, scancode::KeyCode};
+    use crate::hal::cpu;
+    use crate::libs::ring::RingBuffer;
+    use crate::libs::sync::SpinLock;
+    use super::super::tty::{self, TTY_COUNT, TTY_HEIGHT};
+    use super::{Input, LdiscMode, LineEditor, LineStatus};
+
+    /// Shift+PgUp/PgDn 每次捲動的行數
+    const SCROLLBACK_PAGE: usize = TTY_HEIGHT / 2;
+    /// 每個控制台的輸入佇列大小
+    const INPUT_QUEUE_SIZE: usize = 64;
+
+    /// 每個虛擬控制台的行規程
+    struct Ldisc {
+        mode: LdiscMode,
+        editor: LineEditor,
+        /// 規範模式下已提交、尚未被 `tty_read_char` 讀完的行
+        pending: String,
+        /// 控制台在前台時收到、尚未讀取的輸入
+        input: RingBuffer<Input, INPUT_QUEUE_SIZE>,
+    }
+
+    impl Ldisc {
+        const fn new() -> Self {
+            Self {
+                mode: LdiscMode::CANONICAL,
+                editor: LineEditor::new(),
+                pending: String::new(),
+                input: RingBuffer::new(),
+            }
+        }
+    }
+
+    static LDISCS: [SpinLock<Ldisc>; TTY_COUNT] = [const { SpinLock::new(Ldisc::new()) }; TTY_COUNT];
+
+    /// 處理由控制台本身處理的按鍵：Alt+F1..F6 切換控制台，Shift+PgUp/PgDn 檢視回捲歷史
+    ///
+    /// # 返回
+    /// 按鍵是否已被處理
+    fn console_key(event: &KeyEvent) -> bool {
+        if !event.pressed {
+            return false;
+        }
+
+        if event.modifiers.alt {
+            if let Some(n) = event.code.function_key() {
+                let _guard = tty::tty_lock();
+                tty::tty_switch(n as usize - 1);
+                return true;
+            }
+        }
+
+        if event.modifiers.shift {
+            let _guard = tty::tty_lock_console(tty::tty_active_console());
+            match event.code {
+                KeyCode::PAGE_UP => tty::tty_scrollback_up(SCROLLBACK_PAGE),
+                KeyCode::PAGE_DOWN => tty::tty_scrollback_down(SCROLLBACK_PAGE),
+                _ => return false,
+            }
+            return true;
+        }
+
+        false
+    }
+
+    /// 將鍵盤事件分派到前台控制台的輸入佇列
+    ///
+    /// 其他按鍵會讓前台控制台返回即時畫面
+    fn dispatch_keys() {
+        while let Some(event) = keyboard::keyboard_read_event() {
+            if console_key(&event) {
+                continue;
+            }
+            let Some(input) = Input::from_key(&event) else {
+                continue;
+            };
+
+            let active = tty::tty_active_console();
+            {
+                let _guard = tty::tty_lock_console(active);
+                tty::tty_scrollback_reset();
+            }
+            // 佇列已滿時丟棄，與鍵盤驅動的行為一致
+            LDISCS[active].lock().input.push(input);
+        }
+    }
+
+    /// 等待控制台的下一個輸入，沒有輸入時讓 CPU 休眠
+    fn wait_input(console: usize) -> Input {
+        loop {
+            dispatch_keys();
+            let input = LDISCS[console].lock().input.pop();
+            if let Some(input) = input {
+                return input;
+            }
+            cpu::cpu_idle();
+        }
+    }
+
+    /// 設定行規程模式
+    ///
+    /// # 參數
+    /// * `console` - 控制台編號，需小於 `TTY_COUNT`
+    /// * `mode` - 新的模式
+    #[allow(dead_code)]
+    pub fn tty_set_mode(console: usize, mode: LdiscMode) {
+        let mut ldisc = LDISCS[console].lock();
+        ldisc.mode = mode;
+        ldisc.editor.set_echo(mode.echo);
+    }
+
+    /// 目前的行規程模式
+    #[allow(dead_code)]
+    pub fn tty_get_mode(console: usize) -> LdiscMode {
+        LDISCS[console].lock().mode
+    }
+
+    /// 從控制台讀取一行，阻塞直到按下 Enter
+    ///
+    /// 不論模式為何都使用行編輯器；回顯依模式設定。只有控制台在前台時才會收到輸入
+    ///
+    /// # 參數
+    /// * `console` - 控制台編號，需小於 `TTY_COUNT`
+    ///
+    /// # 返回
+    /// 不含換行的一行，在空行按下 Ctrl+D 時返回 `None`
+    pub fn tty_read_line(console: usize) -> Option<String> {
+        loop {
+            let input = wait_input(console);
+
+            let _guard = tty::tty_lock_console(console);
+            match LDISCS[console].lock().editor.feed(input) {
+                LineStatus::Pending => {}
+                LineStatus::Line(line) => return Some(line),
+                LineStatus::Eof => return None,
+            }
+        }
+    }
+
+    /// 從控制台讀取一個字元，阻塞直到有輸入
+    ///
+    /// 規範模式下先讀入並編輯整行，再逐字元返回（行尾為 `'\n'`）；
+    /// 原始模式下直接返回按鍵字元，方向鍵等不產生字元的按鍵被忽略
+    ///
+    /// # 參數
+    /// * `console` - 控制台編號，需小於 `TTY_COUNT`
+    ///
+    /// # 返回
+    /// 規範模式下在空行按下 Ctrl+D 時返回 `None`
+    #[allow(dead_code)]
+    pub fn tty_read_char(console: usize) -> Option<char> {
+        let mode = {
+            let mut ldisc = LDISCS[console].lock();
+            if !ldisc.pending.is_empty() {
+                return Some(ldisc.pending.remove(0));
+            }
+            ldisc.mode
+        };
+
+        if mode.canonical {
+            let mut line = tty_read_line(console)?;
+            line.push('\n');
+            let mut ldisc = LDISCS[console].lock();
+            ldisc.pending = line;
+            return Some(ldisc.pending.remove(0));
+        }
+
+        loop {
+            if let Input::Char(c) = wait_input(console) {
+                if mode.echo {
+                    let _guard = tty::tty_lock_console(console);
+                    tty::tty_put_char(c);
+                }
+                return Some(c);
+            }
+        }
+    }
+}
+
+#[cfg(not(feature = "std"))]
+pub use console::*;
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+    use crate::kernel::tty::mock::MockVga;
+    use crate::kernel::tty::tty::TTY_HEIGHT;
+
+    fn type_str(editor: &mut LineEditor, s: &str) -> LineStatus {
+        let mut status = LineStatus::Pending;
+        for c in s.chars() {
+            status = editor.feed(Input::Char(c));
+        }
+        status
+    }
+
+    #[test]
+    fn edits_and_submits() {
+        let vga = MockVga::new();
+        let mut editor = LineEditor::new();
+        tty::tty_put_str("> ");
+
+        assert_eq!(type_str(&mut editor, "helo"), LineStatus::Pending);
+        editor.feed(Input::Left);
+        editor.feed(Input::Char('l'));
+        assert_eq!(vga.line(0), "> hello");
+        assert_eq!(tty::tty_get_cpos(), (6, 0));
+
+        editor.feed(Input::End);
+        assert_eq!(type_str(&mut editor, "x\x08 world\n"), LineStatus::Line("hello world".into()));
+        assert_eq!(vga.line(0), "> hello world");
+        assert_eq!(tty::tty_get_cpos(), (0, 1));
+    }
+
+    #[test]
+    fn kill_and_word_erase() {
+        let vga = MockVga::new();
+        let mut editor = LineEditor::new();
+
+        type_str(&mut editor, "one two  three");
+        editor.feed(Input::Char(CTRL_W));
+        assert_eq!(editor.line(), "one two  ");
+        editor.feed(Input::Char(CTRL_W));
+        assert_eq!(editor.line(), "one ");
+        assert_eq!(vga.line(0), "one");
+
+        type_str(&mut editor, "four");
+        editor.feed(Input::Home);
+        editor.feed(Input::Right);
+        editor.feed(Input::Char(CTRL_K));
+        assert_eq!(editor.line(), "o");
+        editor.feed(Input::End);
+        editor.feed(Input::Char(CTRL_U));
+        assert_eq!((editor.line().as_str(), editor.cursor()), ("", 0));
+        assert_eq!(vga.line(0), "");
+    }
+
+    #[test]
+    fn history_navigation() {
+        let _vga = MockVga::new();
+        let mut editor = LineEditor::new();
+        type_str(&mut editor, "first\nsecond\nsecond\n");
+        assert_eq!(editor.history().collect::<Vec<_>>(), ["first", "second"]);
+
+        type_str(&mut editor, "draft");
+        editor.feed(Input::Up);
+        assert_eq!(editor.line(), "second");
+        editor.feed(Input::Up);
+        editor.feed(Input::Up);
+        assert_eq!(editor.line(), "first");
+        editor.feed(Input::Down);
+        editor.feed(Input::Down);
+        assert_eq!(editor.line(), "draft");
+        editor.feed(Input::Up);
+        assert_eq!(type_str(&mut editor, "!\n"), LineStatus::Line("second!".into()));
+    }
+
+    #[test]
+    fn interrupt_eof_and_echo() {
+        let vga = MockVga::new();
+        let mut editor = LineEditor::new();
+
+        type_str(&mut editor, "abc\x03");
+        assert_eq!(vga.line(0), "abc^C");
+        assert_eq!(editor.feed(Input::Char(CTRL_D)), LineStatus::Eof);
+
+        editor.set_echo(false);
+        assert_eq!(type_str(&mut editor, "secret\n"), LineStatus::Line("secret".into()));
+        assert_eq!(vga.line(1), "");
+    }
+
+    #[test]
+    fn long_line_scrolls_origin() {
+        let vga = MockVga::new();
+        let mut editor = LineEditor::new();
+        tty::tty_set_cpos(70, TTY_HEIGHT - 1);
+
+        let text: String = core::iter::repeat('x').take(20).collect();
+        type_str(&mut editor, &text);
+        assert_eq!(vga.line(TTY_HEIGHT - 2).len(), 80);
+        assert_eq!(vga.line(TTY_HEIGHT - 1), "xxxxxxxxxx");
+
+        // 起點已隨捲動上移，回到行首應落在上一行的第 70 欄
+        editor.feed(Input::Home);
+        assert_eq!(tty::tty_get_cpos(), (70, TTY_HEIGHT - 2));
+        editor.feed(Input::Char(CTRL_K));
+        assert_eq!(vga.line(TTY_HEIGHT - 1), "");
+    }
+}
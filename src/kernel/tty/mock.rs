@@ -0,0 +1,52 @@
+// src/kernel/tty/mock.rs
+//! 主機端測試使用的模擬 VGA 緩衝區
+use std::boxed::Box;
+use std::string::String;
+use std::sync::{Mutex, MutexGuard};
+use super::cp437;
+use super::tty::{self, VgaAttribute, TTY_HEIGHT, TTY_WIDTH, VGA_COLOR_BLACK, VGA_COLOR_LIGHT_GREY};
+
+/// TTY 狀態是全局的，使用它的測試需要串行執行
+static TTY_TEST_LOCK: Mutex<()> = Mutex::new(());
+
+/// 模擬 VGA 文本緩衝區
+///
+/// 建立時獨佔全局 TTY 狀態，並以淺灰底黑的主題將 TTY 指向它；
+/// 釋放時解除 TTY 與緩衝區的關聯
+pub struct MockVga {
+    buffer: Box<[VgaAttribute; TTY_WIDTH * TTY_HEIGHT]>,
+    _guard: MutexGuard<'static, ()>,
+}
+
+impl MockVga {
+    pub fn new() -> Self {
+        // 前一個測試失敗不影響其他測試
+        let guard = TTY_TEST_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let mut buffer = Box::new([0; TTY_WIDTH * TTY_HEIGHT]);
+
+        tty::tty_set_theme(VGA_COLOR_LIGHT_GREY, VGA_COLOR_BLACK);
+        tty::tty_init(buffer.as_mut_ptr() as usize);
+
+        Self { buffer, _guard: guard }
+    }
+
+    /// 讀取指定位置的字符與屬性
+    pub fn cell(&self, x: usize, y: usize) -> VgaAttribute {
+        self.buffer[y * TTY_WIDTH + x]
+    }
+
+    /// 讀取指定行的文字（由代碼頁 437 轉換），去除行尾空白
+    pub fn line(&self, y: usize) -> String {
+        let text: String = (0..TTY_WIDTH)
+            .map(|x| cp437::cp437_to_char((self.cell(x, y) & 0xFF) as u8))
+            .collect();
+        String::from(text.trim_end())
+    }
+}
+
+impl Drop for MockVga {
+    fn drop(&mut self) {
+        tty::tty_set_mouse_cursor(None);
+        tty::tty_set_buffer(0);
+    }
+}
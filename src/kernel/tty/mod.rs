@@ -1,6 +1,10 @@
 // src/kernel/tty/mod.rs
 
+pub mod cp437;
+pub mod ldisc;
 pub mod tty;
+#[cfg(all(test, feature = "std"))]
+pub mod mock;
 
 pub use tty::tty_init;
 pub use tty::tty_set_buffer;
@@ -12,4 +16,4 @@ pub use tty::tty_clear;
 pub use tty::tty_clear_line;
 pub use tty::tty_set_cpos;
 pub use tty::tty_get_cpos;
-pub use tty::tty_get_theme;
\ No newline at end of file
+pub use tty::tty_get_theme;
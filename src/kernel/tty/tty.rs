@@ -1,7 +1,10 @@
 // src/kernel/tty/tty.rs
 
 use crate::hal::io;
-use core::ptr::NonNull;
+use core::ptr::{self, NonNull};
+use crate::libs::ring::RingBuffer;
+use crate::libs::sync::{SpinLock, SpinLockGuard};
+use super::cp437;
 
 /// VGA 屬性類型 (16位)
 #[allow(dead_code)]
@@ -44,54 +47,344 @@ pub const VGA_COLOR_WHITE: u8 = 15;
 pub const VGA_BUFFER_PADDR: usize = 0xB8000;
 
 #[allow(dead_code)]
-const TTY_WIDTH: usize = 80;
+pub const TTY_WIDTH: usize = 80;
 #[allow(dead_code)]
-const TTY_HEIGHT: usize = 25;
+pub const TTY_HEIGHT: usize = 25;
 #[allow(dead_code)]
 const VGA_CTRL_PORT: u16 = 0x3D4;
 #[allow(dead_code)]
 const VGA_DATA_PORT: u16 = 0x3D5;
 
-// 全局 TTY 狀態
+// CRTC 寄存器
+const VGA_CRTC_CURSOR_START: u8 = 0x0A;
+const VGA_CRTC_CURSOR_END: u8 = 0x0B;
+const VGA_CRTC_CURSOR_HIGH: u8 = 0x0E;
+const VGA_CRTC_CURSOR_LOW: u8 = 0x0F;
+/// 游標起始寄存器中的關閉位
+const VGA_CURSOR_DISABLE: u8 = 0x20;
+
+/// 底線游標的起始與結束掃描線
+pub const TTY_CURSOR_UNDERLINE: (u8, u8) = (14, 15);
+/// 方塊游標的起始與結束掃描線
+#[allow(dead_code)]
+pub const TTY_CURSOR_BLOCK: (u8, u8) = (0, 15);
+
+/// 回捲緩衝區最多保留的行數
+pub const TTY_SCROLLBACK_MAX: usize = 512;
+
+/// 虛擬控制台數量
+pub const TTY_COUNT: usize = 6;
+
+/// CSI 序列最多保留的參數個數
+const CSI_MAX_PARAMS: usize = 16;
+
+/// ANSI 顏色編號 (0-7) 對應的 VGA 顏色
+const ANSI_TO_VGA: [u8; 8] = [
+    VGA_COLOR_BLACK,
+    VGA_COLOR_RED,
+    VGA_COLOR_GREEN,
+    VGA_COLOR_BROWN,
+    VGA_COLOR_BLUE,
+    VGA_COLOR_MAGENTA,
+    VGA_COLOR_CYAN,
+    VGA_COLOR_LIGHT_GREY,
+];
+
+/// SGR（Select Graphic Rendition）屬性
+#[derive(Clone, Copy)]
+struct Sgr {
+    fg: u8,
+    bg: u8,
+    /// 粗體以高亮前景色顯示
+    bold: bool,
+    reverse: bool,
+}
+
+impl Sgr {
+    const fn new(fg: u8, bg: u8) -> Self {
+        Self { fg, bg, bold: false, reverse: false }
+    }
+
+    /// 對應的 VGA 屬性（高 8 位）
+    const fn attribute(&self) -> VgaAttribute {
+        let fg = if self.bold { self.fg | 0x08 } else { self.fg };
+        let (fg, bg) = if self.reverse { (self.bg, fg) } else { (fg, self.bg) };
+        ((bg << 4 | fg) as VgaAttribute) << 8
+    }
+}
+
+/// ESC 7 / CSI s 保存的游標
+#[derive(Clone, Copy)]
+struct SavedCursor {
+    x: usize,
+    y: usize,
+    sgr: Sgr,
+}
+
+/// 轉義序列解析狀態
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum EscapeState {
+    Ground,
+    /// 收到 ESC
+    Escape,
+    /// 收到 ESC [
+    Csi,
+}
+
+/// CSI 序列解析器
+struct CsiParser {
+    state: EscapeState,
+    params: [u16; CSI_MAX_PARAMS],
+    /// 已開始的參數個數
+    count: usize,
+    /// `ESC [ ?` 開頭的私有序列
+    private: bool,
+    /// 含有不支援的參數字元，結束時不執行
+    unsupported: bool,
+}
+
+impl CsiParser {
+    const fn new() -> Self {
+        Self {
+            state: EscapeState::Ground,
+            params: [0; CSI_MAX_PARAMS],
+            count: 0,
+            private: false,
+            unsupported: false,
+        }
+    }
+
+    fn start(&mut self) {
+        self.state = EscapeState::Csi;
+        self.params = [0; CSI_MAX_PARAMS];
+        self.count = 0;
+        self.private = false;
+        self.unsupported = false;
+    }
+
+    fn digit(&mut self, d: u16) {
+        if self.count == 0 {
+            self.count = 1;
+        }
+        let param = &mut self.params[self.count - 1];
+        *param = param.saturating_mul(10).saturating_add(d);
+    }
+
+    fn separator(&mut self) {
+        if self.count == 0 {
+            self.count = 1;
+        }
+        if self.count < CSI_MAX_PARAMS {
+            self.count += 1;
+        }
+    }
+
+    /// 第 `index` 個參數，省略或為 0 時返回 `default`
+    fn param(&self, index: usize, default: u16) -> u16 {
+        match self.params[index] {
+            p if p != 0 && index < self.count => p,
+            _ => default,
+        }
+    }
+}
+
+// 虛擬控制台狀態
 pub struct TTYState {
+    /// 輸出目標：前台控制台為 VGA 緩衝區，其餘為 `back_buffer`
     vga_buffer: Option<NonNull<VgaAttribute>>,
+    /// 不在前台時的畫面內容
+    back_buffer: [VgaAttribute; TTY_WIDTH * TTY_HEIGHT],
+    /// 是否為前台控制台，只有前台控制台會更新硬體游標
+    active: bool,
+    /// 目前的字元屬性，由 `sgr` 計算
     theme_color: VgaAttribute,
     x: usize,
     y: usize,
+    /// `tty_set_theme` 設定的預設顏色，SGR 0 還原為此顏色
+    default_fg: u8,
+    default_bg: u8,
+    sgr: Sgr,
+    /// 捲動區域的首行與末行（含）
+    scroll_top: usize,
+    scroll_bottom: usize,
+    saved: SavedCursor,
+    parser: CsiParser,
+    /// 硬體游標是否顯示
+    cursor_visible: bool,
+    /// 硬體游標的起始與結束掃描線
+    cursor_shape: (u8, u8),
+    /// 最後寫入 CRTC 的游標位置，`None` 表示需要重新寫入
+    cursor_pos: Option<usize>,
+    /// 捲出畫面頂部的行，最舊的在前
+    scrollback: RingBuffer<[VgaAttribute; TTY_WIDTH], TTY_SCROLLBACK_MAX>,
+    scrollback_limit: usize,
+    /// 往回捲動的行數，0 表示顯示即時畫面
+    view_offset: usize,
+    /// 檢視回捲歷史時保存的即時畫面
+    live_screen: [VgaAttribute; TTY_WIDTH * TTY_HEIGHT],
 }
 
 impl TTYState {
+    /// 全零的初始狀態，使 `TTY_STATES` 落在 .bss 而非 .data；
+    /// 顏色、捲動區域與游標形狀由 `tty_init` 設定
     const fn new() -> Self {
         Self {
             vga_buffer: None,
-            theme_color: (VGA_COLOR_BLACK as VgaAttribute) << 8,
+            back_buffer: [0; TTY_WIDTH * TTY_HEIGHT],
+            active: false,
+            theme_color: 0,
             x: 0,
             y: 0,
+            default_fg: 0,
+            default_bg: 0,
+            sgr: Sgr::new(0, 0),
+            scroll_top: 0,
+            scroll_bottom: 0,
+            saved: SavedCursor { x: 0, y: 0, sgr: Sgr::new(0, 0) },
+            parser: CsiParser::new(),
+            cursor_visible: false,
+            cursor_shape: (0, 0),
+            cursor_pos: None,
+            scrollback: RingBuffer::new(),
+            scrollback_limit: 0,
+            view_offset: 0,
+            live_screen: [0; TTY_WIDTH * TTY_HEIGHT],
         }
     }
 }
 
-static mut TTY_STATE: TTYState = TTYState::new();
+static mut TTY_STATES: [TTYState; TTY_COUNT] = [const { TTYState::new() }; TTY_COUNT];
+
+/// VGA 文本緩衝區
+static mut TTY_VGA: Option<NonNull<VgaAttribute>> = None;
+/// 前台控制台
+static mut TTY_ACTIVE: usize = 0;
+/// 內核日誌（`print!`）輸出的控制台
+static mut TTY_LOG: usize = 0;
+/// `tty_*` 函數輸出的控制台，持有 `TTY_LOCK` 時才能改變，釋放後還原為 `TTY_LOG`
+static mut TTY_TARGET: usize = 0;
+
+/// 滑鼠游標所在的字元格
+static mut MOUSE_CURSOR: Option<(usize, usize)> = None;
+/// 滑鼠游標目前是否已畫在 VGA 緩衝區中
+static mut MOUSE_DRAWN: bool = false;
 
-// #[no_mangle]
-// fn update_cursor() {
-//     unsafe {
-//         let pos = TTY_STATE.y * TTY_WIDTH + TTY_STATE.x;
-        
-//         io::io_port_wb(VGA_CTRL_PORT, 0x0F);
-//         io::io_port_wb(VGA_DATA_PORT, (pos & 0xFF) as u8);
-//         io::io_port_wb(VGA_CTRL_PORT, 0x0E);
-//         io::io_port_wb(VGA_DATA_PORT, ((pos >> 8) & 0xFF) as u8);
-//     }
-// }
+/// 序列化 TTY 輸出，避免多段輸出交錯
+static TTY_LOCK: SpinLock<()> = SpinLock::new(());
+
+/// TTY 輸出鎖，持有期間 `tty_*` 函數輸出到選定的控制台
+pub struct TtyGuard {
+    _guard: SpinLockGuard<'static, ()>,
+}
+
+impl Drop for TtyGuard {
+    fn drop(&mut self) {
+        unsafe {
+            TTY_TARGET = TTY_LOG;
+        }
+    }
+}
+
+/// 目前輸出目標的控制台狀態
+unsafe fn tty_state() -> &'static mut TTYState {
+    &mut *ptr::addr_of_mut!(TTY_STATES[TTY_TARGET])
+}
+
+/// 取得 TTY 輸出鎖，輸出到內核日誌控制台
+pub fn tty_lock() -> TtyGuard {
+    let guard = TTY_LOCK.lock();
+    unsafe {
+        TTY_TARGET = TTY_LOG;
+    }
+    TtyGuard { _guard: guard }
+}
+
+/// 取得 TTY 輸出鎖，輸出到指定的控制台
+///
+/// # 參數
+/// * `index` - 控制台編號，超出範圍時輸出到內核日誌控制台
+pub fn tty_lock_console(index: usize) -> TtyGuard {
+    let guard = tty_lock();
+    if index < TTY_COUNT {
+        unsafe {
+            TTY_TARGET = index;
+        }
+    }
+    guard
+}
+
+/// 強制釋放 TTY 輸出鎖，並將輸出目標還原為內核日誌控制台
+///
+/// # Safety
+/// 只能在持有者已不可能繼續執行時使用（panic 路徑）
+pub unsafe fn tty_force_unlock() {
+    TTY_LOCK.force_unlock();
+    TTY_TARGET = TTY_LOG;
+}
+
+/// 寫入 CRTC 寄存器
+fn vga_crtc_write(index: u8, value: u8) {
+    io::io_port_wb(VGA_CTRL_PORT, index);
+    io::io_port_wb(VGA_DATA_PORT, value);
+}
+
+/// 反轉字元格的前景色與背景色，再次呼叫即還原
+unsafe fn invert_cell(vga_ptr: NonNull<VgaAttribute>, x: usize, y: usize) {
+    let cell = vga_ptr.as_ptr().add(x + y * TTY_WIDTH);
+    let value = *cell;
+    *cell = (value & 0x00FF) | ((value & 0x0F00) << 4) | ((value & 0xF000) >> 4);
+}
+
+/// 暫時移除滑鼠游標，修改緩衝區前呼叫
+unsafe fn mouse_cursor_hide() {
+    if let (Some(vga_ptr), Some((x, y)), true) = (TTY_VGA, MOUSE_CURSOR, MOUSE_DRAWN) {
+        invert_cell(vga_ptr, x, y);
+    }
+    MOUSE_DRAWN = false;
+}
+
+/// 重新畫出滑鼠游標，修改緩衝區後呼叫
+unsafe fn mouse_cursor_show() {
+    if let (Some(vga_ptr), Some((x, y)), false) = (TTY_VGA, MOUSE_CURSOR, MOUSE_DRAWN) {
+        invert_cell(vga_ptr, x, y);
+        MOUSE_DRAWN = true;
+    }
+}
 
 /// 初始化 TTY
+///
+/// 重置所有虛擬控制台並清空畫面，第一個控制台成為前台與內核日誌控制台
 // vga_buf: *mut u8
 #[no_mangle]
 pub fn tty_init(vga_buf: usize) {
     unsafe {
-        TTY_STATE.vga_buffer = NonNull::new(vga_buf as *mut VgaAttribute);
-        tty_clear();
+        MOUSE_DRAWN = false;
+        TTY_VGA = NonNull::new(vga_buf as *mut VgaAttribute);
+        TTY_ACTIVE = 0;
+        TTY_LOG = 0;
+        TTY_TARGET = 0;
+
+        for (index, state) in (*ptr::addr_of_mut!(TTY_STATES)).iter_mut().enumerate() {
+            let sgr = Sgr::new(VGA_COLOR_LIGHT_GREY, VGA_COLOR_BLACK);
+            state.default_fg = sgr.fg;
+            state.default_bg = sgr.bg;
+            state.sgr = sgr;
+            state.theme_color = sgr.attribute();
+            state.saved = SavedCursor { x: 0, y: 0, sgr };
+            state.parser.state = EscapeState::Ground;
+            state.scroll_top = 0;
+            state.scroll_bottom = TTY_HEIGHT - 1;
+            state.scrollback.clear();
+            state.scrollback_limit = TTY_SCROLLBACK_MAX;
+            state.view_offset = 0;
+            state.cursor_visible = true;
+            state.cursor_shape = TTY_CURSOR_UNDERLINE;
+            state.cursor_pos = None;
+            state.active = index == 0;
+            state.vga_buffer = if state.active { TTY_VGA } else { NonNull::new(state.back_buffer.as_mut_ptr()) };
+            state.sync_cursor_shape();
+            state.clear();
+        }
     }
 }
 
@@ -99,97 +392,594 @@ pub fn tty_init(vga_buf: usize) {
 #[no_mangle]
 pub fn tty_set_buffer(vga_buf: usize) {
     unsafe {
-        TTY_STATE.vga_buffer = NonNull::new(vga_buf as *mut VgaAttribute);
+        let state = &mut *ptr::addr_of_mut!(TTY_STATES[TTY_ACTIVE]);
+        mouse_cursor_hide();
+        state.set_view(0);
+        TTY_VGA = NonNull::new(vga_buf as *mut VgaAttribute);
+        state.vga_buffer = TTY_VGA;
+        state.cursor_pos = None;
+        state.sync_cursor();
+        mouse_cursor_show();
     }
 }
 
 /// 設置主題顏色（前景色和背景色）
+///
+/// 同時作為 SGR 的預設顏色，並清除粗體與反白
 #[no_mangle]
 pub fn tty_set_theme(fg: u8, bg: u8) {
     unsafe {
-        TTY_STATE.theme_color = ((bg << 4 | fg) as VgaAttribute) << 8;
+        tty_state().set_theme(fg, bg);
     }
 }
 
-/// 向 TTY 輸出單個字符
-/// 
-/// # 注意
-/// - 僅支援 ASCII 範圍 (0-255) 的字符
-/// - 多字節 Unicode 字符會被截斷為低 8 位
-/// - '\t' 擴展為 4 個空格
-/// - '\n' 換行並將游標移至行首
-/// - '\r' 將游標移至行首
-#[no_mangle]
-pub fn tty_put_char(chr: char) {
+/// 切換前台控制台
+///
+/// 保存目前前台控制台的畫面，再將選定控制台的畫面複製到 VGA 緩衝區
+///
+/// # 參數
+/// * `index` - 控制台編號，超出範圍時不做任何事
+pub fn tty_switch(index: usize) {
     unsafe {
-        if let Some(vga_ptr) = TTY_STATE.vga_buffer {
-            match chr {
-                '\t' => {
-                    TTY_STATE.x += 4;
-                }
-                '\n' => {
-                    TTY_STATE.y += 1;
-                    TTY_STATE.x = 0;
+        if index >= TTY_COUNT || index == TTY_ACTIVE {
+            return;
+        }
+
+        mouse_cursor_hide();
+        let old = &mut *ptr::addr_of_mut!(TTY_STATES[TTY_ACTIVE]);
+        old.set_view(0);
+        if let Some(vga_ptr) = TTY_VGA {
+            ptr::copy_nonoverlapping(vga_ptr.as_ptr(), old.back_buffer.as_mut_ptr(), TTY_WIDTH * TTY_HEIGHT);
+        }
+        old.vga_buffer = NonNull::new(old.back_buffer.as_mut_ptr());
+        old.active = false;
+
+        let new = &mut *ptr::addr_of_mut!(TTY_STATES[index]);
+        if let Some(vga_ptr) = TTY_VGA {
+            ptr::copy_nonoverlapping(new.back_buffer.as_ptr(), vga_ptr.as_ptr(), TTY_WIDTH * TTY_HEIGHT);
+        }
+        new.vga_buffer = TTY_VGA;
+        new.active = true;
+        new.cursor_pos = None;
+        new.sync_cursor_shape();
+        new.sync_cursor();
+
+        TTY_ACTIVE = index;
+        mouse_cursor_show();
+    }
+}
+
+/// 目前的前台控制台
+pub fn tty_active_console() -> usize {
+    unsafe {
+        TTY_ACTIVE
+    }
+}
+
+/// 將內核日誌（`print!`）綁定到指定的控制台
+///
+/// # 參數
+/// * `index` - 控制台編號，超出範圍時不做任何事
+pub fn tty_set_log_console(index: usize) {
+    let _guard = TTY_LOCK.lock();
+    if index < TTY_COUNT {
+        unsafe {
+            TTY_LOG = index;
+            TTY_TARGET = index;
+        }
+    }
+}
+
+/// 內核日誌輸出的控制台
+pub fn tty_log_console() -> usize {
+    unsafe {
+        TTY_LOG
+    }
+}
+
+/// 從內核命令列解析 `console=tty<n>`（n 從 1 起算）
+///
+/// # 返回
+/// 控制台編號（從 0 起算），沒有、格式錯誤或為 `tty0` 時返回 `None`
+pub fn tty_console_from_cmdline(cmdline: &str) -> Option<usize> {
+    cmdline
+        .split_whitespace()
+        .filter_map(|arg| arg.strip_prefix("console=tty"))
+        .filter_map(|n| n.parse::<usize>().ok())
+        .find(|n| (1..=TTY_COUNT).contains(n))
+        .map(|n| n - 1)
+}
+
+impl TTYState {
+    /// 以目前的背景色填滿緩衝區的 `[start, end)` 區間
+    fn fill_cells(&mut self, start: usize, end: usize) {
+        if let Some(vga_ptr) = self.vga_buffer {
+            for i in start..end.min(TTY_WIDTH * TTY_HEIGHT) {
+                unsafe { *vga_ptr.as_ptr().add(i) = self.theme_color };
+            }
+        }
+    }
+
+    /// 將 `top..=bottom` 行向上（`up`）或向下捲動 `n` 行，空出的行以背景色填滿
+    fn scroll_lines(&mut self, top: usize, bottom: usize, n: usize, up: bool) {
+        let Some(vga_ptr) = self.vga_buffer else {
+            return;
+        };
+        if top > bottom || bottom >= TTY_HEIGHT {
+            return;
+        }
+
+        let rows = bottom - top + 1;
+        let n = n.min(rows);
+        let buffer_ptr = vga_ptr.as_ptr();
+        let moved = (rows - n) * TTY_WIDTH;
+
+        unsafe {
+            if up {
+                core::ptr::copy(buffer_ptr.add((top + n) * TTY_WIDTH), buffer_ptr.add(top * TTY_WIDTH), moved);
+            } else {
+                core::ptr::copy(buffer_ptr.add(top * TTY_WIDTH), buffer_ptr.add((top + n) * TTY_WIDTH), moved);
+            }
+        }
+
+        if up {
+            self.fill_cells((bottom + 1 - n) * TTY_WIDTH, (bottom + 1) * TTY_WIDTH);
+        } else {
+            self.fill_cells(top * TTY_WIDTH, (top + n) * TTY_WIDTH);
+        }
+    }
+
+    /// 將捲動區域向上捲動 `n` 行
+    ///
+    /// 只有捲動區域為整個畫面時，捲出頂部的行才保留在回捲緩衝區
+    fn scroll_region_up(&mut self, n: usize) {
+        if self.scroll_top == 0 && self.scroll_bottom == TTY_HEIGHT - 1 {
+            self.scrollback_push(n.min(TTY_HEIGHT));
+        }
+        self.scroll_lines(self.scroll_top, self.scroll_bottom, n, true);
+    }
+
+    /// 換行：在捲動區域底部時捲動區域，否則下移一行
+    fn line_feed(&mut self) {
+        if self.y == self.scroll_bottom {
+            self.scroll_region_up(1);
+        } else if self.y + 1 < TTY_HEIGHT {
+            self.y += 1;
+        }
+    }
+
+    /// 反向換行：在捲動區域頂部時向下捲動區域，否則上移一行
+    fn reverse_line_feed(&mut self) {
+        if self.y == self.scroll_top {
+            self.scroll_lines(self.scroll_top, self.scroll_bottom, 1, false);
+        } else if self.y > 0 {
+            self.y -= 1;
+        }
+    }
+
+    /// 以目前的背景色清空畫面並將游標移至左上角
+    fn clear(&mut self) {
+        self.set_view(0);
+        self.fill_cells(0, TTY_WIDTH * TTY_HEIGHT);
+        self.x = 0;
+        self.y = 0;
+        self.sync_cursor();
+    }
+
+    /// 將畫面頂部的 `n` 行存入回捲緩衝區
+    fn scrollback_push(&mut self, n: usize) {
+        let Some(vga_ptr) = self.vga_buffer else {
+            return;
+        };
+
+        for row in 0..n {
+            let mut line = [0; TTY_WIDTH];
+            unsafe {
+                ptr::copy_nonoverlapping(vga_ptr.as_ptr().add(row * TTY_WIDTH), line.as_mut_ptr(), TTY_WIDTH);
+            }
+            self.scrollback.push_overwrite(line);
+        }
+        while self.scrollback.len() > self.scrollback_limit {
+            self.scrollback.pop();
+        }
+    }
+
+    /// 顯示往回捲動 `offset` 行的畫面，0 為即時畫面
+    ///
+    /// 離開即時畫面時先保存它，返回時還原；修改緩衝區前需先返回即時畫面
+    fn set_view(&mut self, offset: usize) {
+        let Some(vga_ptr) = self.vga_buffer else {
+            return;
+        };
+        let offset = offset.min(self.scrollback.len());
+        if offset == self.view_offset {
+            return;
+        }
+
+        let buffer_ptr = vga_ptr.as_ptr();
+        let history = self.scrollback.len();
+        unsafe {
+            if self.view_offset == 0 {
+                ptr::copy_nonoverlapping(buffer_ptr, self.live_screen.as_mut_ptr(), TTY_WIDTH * TTY_HEIGHT);
+            }
+
+            for row in 0..TTY_HEIGHT {
+                let line = history - offset + row;
+                let dst = buffer_ptr.add(row * TTY_WIDTH);
+                match self.scrollback.get(line) {
+                    Some(saved) => ptr::copy_nonoverlapping(saved.as_ptr(), dst, TTY_WIDTH),
+                    None => {
+                        let src = self.live_screen.as_ptr().add((line - history) * TTY_WIDTH);
+                        ptr::copy_nonoverlapping(src, dst, TTY_WIDTH);
+                    }
                 }
-                '\r' => {
-                    TTY_STATE.x = 0;
+            }
+        }
+
+        let was_live = self.view_offset == 0;
+        self.view_offset = offset;
+        if was_live != (offset == 0) {
+            self.sync_cursor_shape();
+            self.sync_cursor();
+        }
+    }
+
+    /// 將游標位置寫入 CRTC，不在前台、位置未變或檢視回捲歷史時不寫入
+    fn sync_cursor(&mut self) {
+        if !self.active || self.vga_buffer.is_none() || self.view_offset > 0 {
+            return;
+        }
+
+        let pos = self.y * TTY_WIDTH + self.x.min(TTY_WIDTH - 1);
+        if self.cursor_pos != Some(pos) {
+            vga_crtc_write(VGA_CRTC_CURSOR_LOW, pos as u8);
+            vga_crtc_write(VGA_CRTC_CURSOR_HIGH, (pos >> 8) as u8);
+            self.cursor_pos = Some(pos);
+        }
+    }
+
+    /// 將游標形狀與開關寫入 CRTC，檢視回捲歷史時隱藏游標；不在前台時不寫入
+    fn sync_cursor_shape(&mut self) {
+        if !self.active {
+            return;
+        }
+        let (start, end) = self.cursor_shape;
+        let disable = if self.cursor_visible && self.view_offset == 0 { 0 } else { VGA_CURSOR_DISABLE };
+        vga_crtc_write(VGA_CRTC_CURSOR_START, start | disable);
+        vga_crtc_write(VGA_CRTC_CURSOR_END, end);
+    }
+
+    fn set_theme(&mut self, fg: u8, bg: u8) {
+        self.default_fg = fg;
+        self.default_bg = bg;
+        self.sgr = Sgr::new(fg, bg);
+        self.theme_color = self.sgr.attribute();
+    }
+
+    /// 處理控制字元
+    fn put_control(&mut self, chr: char) {
+        match chr {
+            '\t' => self.x += 4,
+            '\n' => {
+                self.x = 0;
+                self.line_feed();
+            }
+            '\r' => self.x = 0,
+            '\x08' => self.x = self.x.saturating_sub(1),
+            '\x1b' => self.parser.state = EscapeState::Escape,
+            _ => {}
+        }
+    }
+
+    /// 在游標處寫入字元並前進
+    fn put_printable(&mut self, chr: char) {
+        if let Some(vga_ptr) = self.vga_buffer {
+            let offset = self.x + self.y * TTY_WIDTH;
+            let glyph = cp437::cp437_from_char(chr).unwrap_or(cp437::CP437_REPLACEMENT);
+            unsafe { *vga_ptr.as_ptr().add(offset) = self.theme_color | glyph as VgaAttribute };
+            self.x += 1;
+        }
+    }
+
+    /// 保存游標位置與屬性
+    fn save_cursor(&mut self) {
+        self.saved = SavedCursor { x: self.x, y: self.y, sgr: self.sgr };
+    }
+
+    /// 還原保存的游標位置與屬性
+    fn restore_cursor(&mut self) {
+        self.x = self.saved.x;
+        self.y = self.saved.y;
+        self.sgr = self.saved.sgr;
+        self.theme_color = self.sgr.attribute();
+    }
+
+    /// 處理 ESC 之後的字元
+    fn put_escape(&mut self, chr: char) {
+        self.parser.state = EscapeState::Ground;
+        match chr {
+            '[' => self.parser.start(),
+            '7' => self.save_cursor(),
+            '8' => self.restore_cursor(),
+            'D' => self.line_feed(),
+            'E' => {
+                self.x = 0;
+                self.line_feed();
+            }
+            'M' => self.reverse_line_feed(),
+            'c' => {
+                self.set_theme(self.default_fg, self.default_bg);
+                self.scroll_top = 0;
+                self.scroll_bottom = TTY_HEIGHT - 1;
+                self.fill_cells(0, TTY_WIDTH * TTY_HEIGHT);
+                self.x = 0;
+                self.y = 0;
+            }
+            _ => {}
+        }
+    }
+
+    /// 處理 CSI 序列中的字元
+    fn put_csi(&mut self, chr: char) {
+        match chr {
+            '0'..='9' => self.parser.digit(chr as u16 - '0' as u16),
+            ';' => self.parser.separator(),
+            '?' if self.parser.count == 0 => self.parser.private = true,
+            // 其餘參數字元（`:`、`<=>` 及非開頭的 `?`）只消耗到結束字元為止
+            ':'..='?' => self.parser.unsupported = true,
+            // 中間字元，目前支援的序列都不使用
+            ' '..='/' => {}
+            '@'..='~' => {
+                self.parser.state = EscapeState::Ground;
+                if self.parser.unsupported {
+                    return;
                 }
-                _ => {
-                    let offset = TTY_STATE.x + TTY_STATE.y * TTY_WIDTH;
-                    // VGA 文本模式僅支持 8 位字符
-                    *vga_ptr.as_ptr().add(offset) = TTY_STATE.theme_color | (chr as u8) as VgaAttribute;
-                    TTY_STATE.x += 1;
+                if self.parser.private {
+                    self.csi_private(chr);
+                } else {
+                    self.csi_dispatch(chr);
                 }
             }
+            '\x18' | '\x1a' => self.parser.state = EscapeState::Ground,
+            // 序列中的控制字元照常執行
+            c if (c as u32) < 0x20 => self.put_control(c),
+            _ => self.parser.state = EscapeState::Ground,
+        }
+    }
+
+    /// 執行 CSI 序列
+    fn csi_dispatch(&mut self, command: char) {
+        let n = self.parser.param(0, 1) as usize;
+        let (x, y) = (self.x, self.y);
+        // 游標在捲動區域內時，上下移動不超出區域
+        let top = if y >= self.scroll_top { self.scroll_top } else { 0 };
+        let bottom = if y <= self.scroll_bottom { self.scroll_bottom } else { TTY_HEIGHT - 1 };
+        let in_region = y >= self.scroll_top && y <= self.scroll_bottom;
 
-            if TTY_STATE.x >= TTY_WIDTH {
-                TTY_STATE.x = 0;
-                TTY_STATE.y += 1;
+        match command {
+            'A' => self.y = y.saturating_sub(n).max(top),
+            'B' | 'e' => self.y = (y + n).min(bottom),
+            'C' | 'a' => self.x = (x + n).min(TTY_WIDTH - 1),
+            'D' => self.x = x.saturating_sub(n),
+            'E' => {
+                self.x = 0;
+                self.y = (y + n).min(bottom);
             }
-            
-            if TTY_STATE.y >= TTY_HEIGHT {
-                tty_scroll_up();
+            'F' => {
+                self.x = 0;
+                self.y = y.saturating_sub(n).max(top);
             }
+            'G' | '`' => self.x = (n - 1).min(TTY_WIDTH - 1),
+            'd' => self.y = (n - 1).min(TTY_HEIGHT - 1),
+            'H' | 'f' => {
+                self.y = (n - 1).min(TTY_HEIGHT - 1);
+                self.x = (self.parser.param(1, 1) as usize - 1).min(TTY_WIDTH - 1);
+            }
+            'J' => {
+                let cursor = y * TTY_WIDTH + x;
+                match self.parser.param(0, 0) {
+                    0 => self.fill_cells(cursor, TTY_WIDTH * TTY_HEIGHT),
+                    1 => self.fill_cells(0, cursor + 1),
+                    2 => self.fill_cells(0, TTY_WIDTH * TTY_HEIGHT),
+                    3 => {
+                        self.fill_cells(0, TTY_WIDTH * TTY_HEIGHT);
+                        self.scrollback.clear();
+                    }
+                    _ => {}
+                }
+            }
+            'K' => {
+                let line = y * TTY_WIDTH;
+                match self.parser.param(0, 0) {
+                    0 => self.fill_cells(line + x, line + TTY_WIDTH),
+                    1 => self.fill_cells(line, line + x + 1),
+                    2 => self.fill_cells(line, line + TTY_WIDTH),
+                    _ => {}
+                }
+            }
+            'X' => self.fill_cells(y * TTY_WIDTH + x, y * TTY_WIDTH + (x + n).min(TTY_WIDTH)),
+            'L' if in_region => {
+                self.scroll_lines(y, self.scroll_bottom, n, false);
+                self.x = 0;
+            }
+            'M' if in_region => {
+                self.scroll_lines(y, self.scroll_bottom, n, true);
+                self.x = 0;
+            }
+            'S' => self.scroll_region_up(n),
+            'T' => self.scroll_lines(self.scroll_top, self.scroll_bottom, n, false),
+            'm' => self.csi_sgr(),
+            'r' => {
+                let top = self.parser.param(0, 1) as usize - 1;
+                let bottom = self.parser.param(1, TTY_HEIGHT as u16) as usize - 1;
+                if top < bottom && bottom < TTY_HEIGHT {
+                    self.scroll_top = top;
+                    self.scroll_bottom = bottom;
+                    self.x = 0;
+                    self.y = 0;
+                }
+            }
+            's' => self.save_cursor(),
+            'u' => self.restore_cursor(),
+            _ => {}
+        }
+    }
+
+    /// 執行私有 CSI 序列，只支援顯示與隱藏游標（`?25h` / `?25l`）
+    fn csi_private(&mut self, command: char) {
+        if self.parser.param(0, 0) != 25 {
+            return;
+        }
+        match command {
+            'h' => self.cursor_visible = true,
+            'l' => self.cursor_visible = false,
+            _ => return,
+        }
+        self.sync_cursor_shape();
+    }
+
+    /// 處理一個字元，游標超出行尾時換行
+    fn put_char(&mut self, chr: char) {
+        match self.parser.state {
+            EscapeState::Escape => self.put_escape(chr),
+            EscapeState::Csi => self.put_csi(chr),
+            EscapeState::Ground if (chr as u32) < 0x20 => self.put_control(chr),
+            EscapeState::Ground => self.put_printable(chr),
+        }
+
+        if self.x >= TTY_WIDTH {
+            self.x = 0;
+            self.line_feed();
+        }
+    }
+
+    /// 執行 SGR 序列（CSI ... m）
+    fn csi_sgr(&mut self) {
+        let params = self.parser.params;
+        let count = self.parser.count.max(1);
+        let mut i = 0;
+
+        while i < count {
+            match params[i] {
+                0 => self.sgr = Sgr::new(self.default_fg, self.default_bg),
+                1 => self.sgr.bold = true,
+                22 => self.sgr.bold = false,
+                7 => self.sgr.reverse = true,
+                27 => self.sgr.reverse = false,
+                p @ 30..=37 => self.sgr.fg = ANSI_TO_VGA[p as usize - 30],
+                39 => self.sgr.fg = self.default_fg,
+                p @ 40..=47 => self.sgr.bg = ANSI_TO_VGA[p as usize - 40],
+                49 => self.sgr.bg = self.default_bg,
+                p @ 90..=97 => self.sgr.fg = ANSI_TO_VGA[p as usize - 90] | 0x08,
+                p @ 100..=107 => self.sgr.bg = ANSI_TO_VGA[p as usize - 100] | 0x08,
+                // 38;5;n / 48;5;n 只支援前 16 色，38;2;r;g;b / 48;2;r;g;b 被略過
+                p @ (38 | 48) => match params.get(i + 1) {
+                    Some(5) if i + 2 < count => {
+                        if let Some(color) = ansi_256_to_vga(params[i + 2]) {
+                            if p == 38 {
+                                self.sgr.fg = color;
+                            } else {
+                                self.sgr.bg = color;
+                            }
+                        }
+                        i += 2;
+                    }
+                    Some(2) => i += 4,
+                    _ => {}
+                },
+                _ => {}
+            }
+            i += 1;
+        }
+
+        self.theme_color = self.sgr.attribute();
+    }
+}
 
-            // update_cursor();
+/// 由 256 色編號取得 VGA 顏色
+fn ansi_256_to_vga(index: u16) -> Option<u8> {
+    match index {
+        0..=7 => Some(ANSI_TO_VGA[index as usize]),
+        8..=15 => Some(ANSI_TO_VGA[index as usize - 8] | 0x08),
+        _ => None,
+    }
+}
+
+/// 向 TTY 輸出單個字符
+///
+/// 支援 ANSI/VT100 轉義序列：
+/// - `ESC 7` / `ESC 8` 保存與還原游標，`ESC D` / `ESC M` / `ESC E` 換行，`ESC c` 重置
+/// - CSI 游標移動 `A B C D E F G H f d`、清除 `J K X`、插入與刪除行 `L M`、
+///   捲動 `S T`、捲動區域 `r`、保存與還原游標 `s u`
+/// - SGR `m`：16 色前景與背景（ANSI 顏色對應到 `VGA_COLOR_*`）、粗體、反白
+///
+/// # 注意
+/// - 字符轉換為代碼頁 437 字形，包括框線、方塊、希臘字母與 Latin-1 字母；
+///   沒有對應字形的字符（如中文）顯示為 `■`
+/// - '\t' 擴展為 4 個空格
+/// - '\n' 換行並將游標移至行首
+/// - '\r' 將游標移至行首
+/// - '\x08' 將游標左移一格
+#[no_mangle]
+pub fn tty_put_char(chr: char) {
+    unsafe {
+        let state = tty_state();
+        if state.vga_buffer.is_none() {
+            return;
         }
+
+        mouse_cursor_hide();
+        state.set_view(0);
+        state.put_char(chr);
+        mouse_cursor_show();
+        state.sync_cursor();
     }
 }
 
 /// 向 TTY 輸出字符串
 /// 
 /// # 注意
-/// - 字符串中的多字節 Unicode 字符會被截斷為低 8 位
+/// - 字符的轉換方式與 `tty_put_char` 相同
 /// - 支持 Rust 字符串切片 (&str)
 #[no_mangle]
 pub fn tty_put_str(s: &str) {
-    for chr in s.chars() {
-        tty_put_char(chr);
+    unsafe {
+        let state = tty_state();
+        if state.vga_buffer.is_none() {
+            return;
+        }
+
+        mouse_cursor_hide();
+        state.set_view(0);
+        for chr in s.chars() {
+            state.put_char(chr);
+        }
+        mouse_cursor_show();
+        state.sync_cursor();
     }
 }
 
+/// 向指定的控制台輸出字符串
+///
+/// # 參數
+/// * `index` - 控制台編號，超出範圍時輸出到內核日誌控制台
+/// * `s` - 要輸出的字符串
+pub fn tty_console_write(index: usize, s: &str) {
+    let _guard = tty_lock_console(index);
+    tty_put_str(s);
+}
+
 /// 向上滾動一行
+///
+/// 只捲動捲動區域，游標位置不變
 #[no_mangle]
 pub fn tty_scroll_up() {
     unsafe {
-        if let Some(vga_ptr) = TTY_STATE.vga_buffer {
-            let last_line = TTY_WIDTH * (TTY_HEIGHT - 1);
-            let buffer_ptr = vga_ptr.as_ptr();
-            
-            // 將所有行向上移動一行
-            core::ptr::copy(
-                buffer_ptr.add(TTY_WIDTH),
-                buffer_ptr,
-                last_line
-            );
-            
-            // 清空最後一行
-            for i in 0..TTY_WIDTH {
-                *buffer_ptr.add(i + last_line) = TTY_STATE.theme_color;
-            }
-            
-            TTY_STATE.y = if TTY_STATE.y == 0 { 0 } else { TTY_HEIGHT - 1 };
-        }
+        mouse_cursor_hide();
+        let state = tty_state();
+        state.set_view(0);
+        state.scroll_region_up(1);
+        mouse_cursor_show();
     }
 }
 
@@ -197,17 +987,9 @@ pub fn tty_scroll_up() {
 #[no_mangle]
 pub fn tty_clear() {
     unsafe {
-        if let Some(vga_ptr) = TTY_STATE.vga_buffer {
-            let buffer_ptr = vga_ptr.as_ptr();
-
-            for i in 0..(TTY_WIDTH * TTY_HEIGHT) {
-                *buffer_ptr.add(i) = TTY_STATE.theme_color;
-            }
-
-            TTY_STATE.x = 0;
-            TTY_STATE.y = 0;
-            // update_cursor();
-        }
+        mouse_cursor_hide();
+        tty_state().clear();
+        mouse_cursor_show();
     }
 }
 
@@ -215,12 +997,16 @@ pub fn tty_clear() {
 #[no_mangle]
 pub fn tty_clear_line(y: usize) {
     unsafe {
-        if let Some(vga_ptr) = TTY_STATE.vga_buffer {
+        let state = tty_state();
+        if let Some(vga_ptr) = state.vga_buffer {
             let buffer_ptr = vga_ptr.as_ptr();
 
+            mouse_cursor_hide();
+            state.set_view(0);
             for i in 0..TTY_WIDTH {
-                *buffer_ptr.add(i + y * TTY_WIDTH) = TTY_STATE.theme_color;
+                *buffer_ptr.add(i + y * TTY_WIDTH) = state.theme_color;
             }
+            mouse_cursor_show();
         }
     }
 }
@@ -229,17 +1015,136 @@ pub fn tty_clear_line(y: usize) {
 #[no_mangle]
 pub fn tty_set_cpos(x: usize, y: usize) {
     unsafe {
-        TTY_STATE.x = x % TTY_WIDTH;
-        TTY_STATE.y = y % TTY_HEIGHT;
-        // update_cursor();
+        let state = tty_state();
+        state.x = x % TTY_WIDTH;
+        state.y = y % TTY_HEIGHT;
+        state.sync_cursor();
+    }
+}
+
+/// 顯示或隱藏硬體游標
+#[allow(dead_code)]
+pub fn tty_set_cursor_visible(visible: bool) {
+    unsafe {
+        let state = tty_state();
+        state.cursor_visible = visible;
+        state.sync_cursor_shape();
     }
 }
 
+/// 設置硬體游標形狀
+///
+/// # 參數
+/// * `start` - 起始掃描線 (0-15)
+/// * `end` - 結束掃描線 (0-15)，小於 `start` 時游標不顯示
+#[allow(dead_code)]
+pub fn tty_set_cursor_shape(start: u8, end: u8) {
+    unsafe {
+        let state = tty_state();
+        state.cursor_shape = (start & 0x1F, end & 0x1F);
+        state.sync_cursor_shape();
+    }
+}
+
+/// 往回捲動畫面以檢視回捲歷史
+///
+/// 任何輸出都會先返回即時畫面
+///
+/// # 參數
+/// * `lines` - 捲動的行數，超出歷史時停在最舊的一行
+pub fn tty_scrollback_up(lines: usize) {
+    unsafe {
+        let state = tty_state();
+        mouse_cursor_hide();
+        state.set_view(state.view_offset.saturating_add(lines));
+        mouse_cursor_show();
+    }
+}
+
+/// 往即時畫面方向捲動
+///
+/// # 參數
+/// * `lines` - 捲動的行數
+pub fn tty_scrollback_down(lines: usize) {
+    unsafe {
+        let state = tty_state();
+        mouse_cursor_hide();
+        state.set_view(state.view_offset.saturating_sub(lines));
+        mouse_cursor_show();
+    }
+}
+
+/// 返回即時畫面
+pub fn tty_scrollback_reset() {
+    tty_scrollback_down(usize::MAX);
+}
+
+/// 目前往回捲動的行數，0 表示顯示即時畫面
+#[allow(dead_code)]
+pub fn tty_scrollback_offset() -> usize {
+    unsafe {
+        tty_state().view_offset
+    }
+}
+
+/// 設置所有控制台的回捲緩衝區保留的行數
+///
+/// # 參數
+/// * `lines` - 保留的行數，最多 `TTY_SCROLLBACK_MAX`；超出的最舊行被丟棄
+pub fn tty_set_scrollback_limit(lines: usize) {
+    unsafe {
+        mouse_cursor_hide();
+        for state in (*ptr::addr_of_mut!(TTY_STATES)).iter_mut() {
+            state.set_view(0);
+            state.scrollback_limit = lines.min(TTY_SCROLLBACK_MAX);
+            while state.scrollback.len() > state.scrollback_limit {
+                state.scrollback.pop();
+            }
+        }
+        mouse_cursor_show();
+    }
+}
+
+/// 從內核命令列解析 `scrollback=<行數>`
+///
+/// # 返回
+/// 指定的行數，沒有或格式錯誤時返回 `None`
+pub fn tty_scrollback_from_cmdline(cmdline: &str) -> Option<usize> {
+    cmdline
+        .split_whitespace()
+        .filter_map(|arg| arg.strip_prefix("scrollback="))
+        .find_map(|n| n.parse().ok())
+}
+
 /// 獲取游標位置
 #[no_mangle]
 pub fn tty_get_cpos() -> (usize, usize) {
     unsafe {
-        (TTY_STATE.x, TTY_STATE.y)
+        let state = tty_state();
+        (state.x, state.y)
+    }
+}
+
+/// 設置滑鼠游標位置
+///
+/// 游標以反轉前景色與背景色的方塊顯示，不影響文字輸出
+///
+/// # 參數
+/// * `pos` - 字元格座標，`None` 隱藏游標
+#[allow(dead_code)]
+pub fn tty_set_mouse_cursor(pos: Option<(usize, usize)>) {
+    unsafe {
+        mouse_cursor_hide();
+        MOUSE_CURSOR = pos.map(|(x, y)| (x.min(TTY_WIDTH - 1), y.min(TTY_HEIGHT - 1)));
+        mouse_cursor_show();
+    }
+}
+
+/// 獲取滑鼠游標位置
+#[allow(dead_code)]
+pub fn tty_get_mouse_cursor() -> Option<(usize, usize)> {
+    unsafe {
+        MOUSE_CURSOR
     }
 }
 
@@ -247,6 +1152,332 @@ pub fn tty_get_cpos() -> (usize, usize) {
 #[no_mangle]
 pub fn tty_get_theme() -> VgaAttribute {
     unsafe {
-        TTY_STATE.theme_color
+        tty_state().theme_color
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+    use crate::hal::io::mock_io_take_writes;
+    use crate::kernel::tty::mock::MockVga;
+
+    const GREY: VgaAttribute = (VGA_COLOR_LIGHT_GREY as VgaAttribute) << 8;
+
+    #[test]
+    fn put_char_uses_theme() {
+        let vga = MockVga::new();
+        tty_put_char('A');
+        tty_set_theme(VGA_COLOR_WHITE, VGA_COLOR_BLUE);
+        tty_put_char('B');
+
+        assert_eq!(vga.cell(0, 0), GREY | b'A' as VgaAttribute);
+        assert_eq!(vga.cell(1, 0), 0x1F00 | b'B' as VgaAttribute);
+        assert_eq!(tty_get_cpos(), (2, 0));
+    }
+
+    #[test]
+    fn control_characters() {
+        let vga = MockVga::new();
+        tty_put_str("ab\tc\nxyz\r0");
+
+        assert_eq!(vga.line(0), "ab    c");
+        assert_eq!(vga.line(1), "0yz");
+        assert_eq!(tty_get_cpos(), (1, 1));
+    }
+
+    #[test]
+    fn wraps_at_line_end() {
+        let vga = MockVga::new();
+        for _ in 0..TTY_WIDTH {
+            tty_put_char('x');
+        }
+        assert_eq!(tty_get_cpos(), (0, 1));
+
+        tty_put_char('y');
+        assert_eq!(vga.line(1), "y");
+    }
+
+    #[test]
+    fn scrolls_at_bottom() {
+        let vga = MockVga::new();
+        tty_put_str("top\nsecond");
+        tty_set_cpos(0, TTY_HEIGHT - 1);
+        tty_put_str("last\n");
+
+        assert_eq!(vga.line(0), "second");
+        assert_eq!(vga.line(TTY_HEIGHT - 2), "last");
+        assert_eq!(vga.line(TTY_HEIGHT - 1), "");
+        assert_eq!(vga.cell(0, TTY_HEIGHT - 1), GREY);
+        assert_eq!(tty_get_cpos(), (0, TTY_HEIGHT - 1));
+    }
+
+    #[test]
+    fn set_cpos_wraps_and_clear_line() {
+        let vga = MockVga::new();
+        tty_set_cpos(TTY_WIDTH + 3, TTY_HEIGHT + 2);
+        assert_eq!(tty_get_cpos(), (3, 2));
+
+        tty_put_str("gone");
+        tty_clear_line(2);
+        assert_eq!(vga.line(2), "");
+        assert_eq!(tty_get_cpos(), (7, 2));
+    }
+
+    #[test]
+    fn mouse_cursor_overlays_text() {
+        let vga = MockVga::new();
+        tty_put_str("ab");
+        tty_set_mouse_cursor(Some((1, 0)));
+        assert_eq!(vga.cell(1, 0), 0x7000 | b'b' as VgaAttribute);
+
+        // 在游標下輸出文字，游標仍在最上層
+        tty_set_cpos(1, 0);
+        tty_put_char('c');
+        assert_eq!(vga.cell(1, 0), 0x7000 | b'c' as VgaAttribute);
+
+        // 捲動後游標停留在原本的螢幕位置
+        tty_set_mouse_cursor(Some((0, TTY_HEIGHT)));
+        assert_eq!(tty_get_mouse_cursor(), Some((0, TTY_HEIGHT - 1)));
+        assert_eq!(vga.cell(1, 0), GREY | b'c' as VgaAttribute);
+        tty_set_cpos(0, TTY_HEIGHT - 1);
+        tty_put_str("x\n");
+        assert_eq!(vga.cell(0, TTY_HEIGHT - 2), GREY | b'x' as VgaAttribute);
+        assert_eq!(vga.cell(0, TTY_HEIGHT - 1), 0x7000);
+
+        tty_set_mouse_cursor(None);
+        assert_eq!(vga.cell(0, TTY_HEIGHT - 1), GREY);
+    }
+
+    #[test]
+    fn sgr_colors() {
+        let vga = MockVga::new();
+        tty_put_str("\x1b[31mR\x1b[1;44mB\x1b[0mN\x1b[38;5;10;7mG\x1b[27;39;49m ");
+
+        assert_eq!(vga.cell(0, 0), 0x0400 | b'R' as VgaAttribute);
+        assert_eq!(vga.cell(1, 0), 0x1C00 | b'B' as VgaAttribute);
+        assert_eq!(vga.cell(2, 0), GREY | b'N' as VgaAttribute);
+        // 反白：前景與背景互換
+        assert_eq!(vga.cell(3, 0), 0xA000 | b'G' as VgaAttribute);
+        assert_eq!(tty_get_theme(), GREY);
+    }
+
+    #[test]
+    fn cursor_movement_and_erase() {
+        let vga = MockVga::new();
+        tty_put_str("0123456789\nabcdefghij");
+
+        tty_put_str("\x1b[1;4H");
+        assert_eq!(tty_get_cpos(), (3, 0));
+        tty_put_str("\x1b[K\x1b[B\x1b[2D\x1b[1K");
+        assert_eq!(vga.line(0), "012");
+        assert_eq!(vga.line(1), "  cdefghij");
+
+        tty_put_str("\x1b[99;99H");
+        assert_eq!(tty_get_cpos(), (TTY_WIDTH - 1, TTY_HEIGHT - 1));
+        tty_put_str("\x1b[2J\x1b[H");
+        assert_eq!((vga.line(0), vga.line(1)), (String::new(), String::new()));
+        assert_eq!(tty_get_cpos(), (0, 0));
+    }
+
+    #[test]
+    fn save_restore_and_ignored_sequences() {
+        let vga = MockVga::new();
+        tty_put_str("\x1b[32mab\x1b7\x1b[0m\x1b[5;5Hx\x1b8c");
+        assert_eq!(vga.cell(2, 0), 0x0200 | b'c' as VgaAttribute);
+
+        // 私有序列與未知序列不輸出任何字元，序列可以分段輸出
+        tty_put_str("\x1b[?25l\x1b[");
+        tty_put_str("1Zd\x1bQe");
+        assert_eq!(vga.line(0), "abcde");
+    }
+
+    #[test]
+    fn unsupported_parameter_bytes() {
+        let vga = MockVga::new();
+        // 含 `:`、`<=>` 或非開頭 `?` 的序列整個被略過，結束字元不會輸出
+        tty_put_str("a\x1b[>c\x1b[38:5:1mb\x1b[1?5hc\x1b[=3;4Jd");
+        assert_eq!(vga.line(0), "abcd");
+        assert_eq!(vga.cell(1, 0) >> 8, vga.cell(0, 0) >> 8);
+    }
+
+    #[test]
+    fn scroll_region() {
+        let vga = MockVga::new();
+        for row in 0..6 {
+            tty_put_str(&std::format!("line{}\n", row));
+        }
+
+        // 區域為第 2-4 行（從 0 起算為 1-3）
+        tty_put_str("\x1b[2;4r\x1b[4;1H\n");
+        assert_eq!(tty_get_cpos(), (0, 3));
+        let lines: Vec<String> = (0..5).map(|y| vga.line(y)).collect();
+        assert_eq!(lines, ["line0", "line2", "line3", "", "line4"]);
+
+        // 在區域頂部反向換行與插入、刪除行
+        tty_put_str("\x1b[2;1H\x1bM\x1b[3;1H\x1b[M");
+        let lines: Vec<String> = (0..5).map(|y| vga.line(y)).collect();
+        assert_eq!(lines, ["line0", "", "line3", "", "line4"]);
+        tty_put_str("\x1b[2;1H\x1b[2L");
+        let lines: Vec<String> = (0..5).map(|y| vga.line(y)).collect();
+        assert_eq!(lines, ["line0", "", "", "", "line4"]);
+    }
+
+    #[test]
+    fn hardware_cursor() {
+        let _vga = MockVga::new();
+        mock_io_take_writes();
+
+        tty_put_str("ab");
+        assert_eq!(mock_io_take_writes(), [(0x3D4, 0x0F), (0x3D5, 2), (0x3D4, 0x0E), (0x3D5, 0)]);
+        // 位置未變時不寫入端口
+        tty_set_cpos(2, 0);
+        assert_eq!(mock_io_take_writes(), []);
+
+        tty_put_str("\x1b[?25l");
+        assert_eq!(mock_io_take_writes(), [(0x3D4, 0x0A), (0x3D5, 0x2E), (0x3D4, 0x0B), (0x3D5, 15)]);
+        tty_set_cursor_visible(true);
+        tty_set_cursor_shape(TTY_CURSOR_BLOCK.0, TTY_CURSOR_BLOCK.1);
+        assert_eq!(mock_io_take_writes()[4..], [(0x3D4, 0x0A), (0x3D5, 0), (0x3D4, 0x0B), (0x3D5, 15)]);
+    }
+
+    #[test]
+    fn scrollback_view() {
+        let vga = MockVga::new();
+        for row in 0..TTY_HEIGHT + 5 {
+            tty_put_str(&std::format!("line{}\n", row));
+        }
+        assert_eq!(vga.line(0), "line6");
+
+        tty_scrollback_up(2);
+        assert_eq!((vga.line(0), vga.line(2)), (String::from("line4"), String::from("line6")));
+        // 停在最舊的一行
+        tty_scrollback_up(100);
+        assert_eq!(tty_scrollback_offset(), 6);
+        assert_eq!(vga.line(0), "line0");
+        tty_scrollback_down(3);
+        assert_eq!(vga.line(0), "line3");
+
+        // 輸出時返回即時畫面
+        tty_put_char('x');
+        assert_eq!(tty_scrollback_offset(), 0);
+        assert_eq!(vga.line(0), "line6");
+        assert_eq!(vga.line(TTY_HEIGHT - 1), "x");
+    }
+
+    #[test]
+    fn scrollback_limit_and_clear() {
+        let vga = MockVga::new();
+        tty_set_scrollback_limit(2);
+        for row in 0..TTY_HEIGHT + 5 {
+            tty_put_str(&std::format!("line{}\n", row));
+        }
+        tty_scrollback_up(10);
+        assert_eq!(vga.line(0), "line4");
+
+        // 限制套用到所有控制台
+        {
+            let _guard = tty_lock_console(1);
+            for row in 0..TTY_HEIGHT + 5 {
+                tty_put_str(&std::format!("other{}\n", row));
+            }
+        }
+        tty_switch(1);
+        {
+            let _guard = tty_lock_console(1);
+            tty_scrollback_up(10);
+        }
+        assert_eq!(vga.line(0), "other4");
+        tty_switch(0);
+
+        // CSI 3 J 同時清除回捲歷史
+        tty_put_str("\x1b[3J");
+        tty_scrollback_up(10);
+        assert_eq!(tty_scrollback_offset(), 0);
+        assert_eq!(vga.line(0), "");
+    }
+
+    #[test]
+    fn deleted_lines_not_in_scrollback() {
+        let vga = MockVga::new();
+        tty_put_str("first\nsecond\x1b[H\x1b[M");
+        assert_eq!(vga.line(0), "second");
+        // 部分捲動區域內的 CSI S 也不進入回捲歷史
+        tty_put_str("\x1b[1;10r\x1b[2S\x1b[r");
+        tty_scrollback_up(10);
+        assert_eq!(tty_scrollback_offset(), 0);
+
+        // 整個畫面的 CSI S 仍保留捲出的行
+        tty_put_str("top\x1b[S");
+        tty_scrollback_up(10);
+        assert_eq!(tty_scrollback_offset(), 1);
+        assert_eq!(vga.line(0), "top");
+    }
+
+    #[test]
+    fn scrollback_from_cmdline() {
+        assert_eq!(tty_scrollback_from_cmdline("quiet scrollback=200"), Some(200));
+        assert_eq!(tty_scrollback_from_cmdline("scrollback=lots"), None);
+        assert_eq!(tty_scrollback_from_cmdline("console=ttyS0"), None);
+    }
+
+    #[test]
+    fn virtual_consoles() {
+        let vga = MockVga::new();
+        tty_put_str("log");
+        mock_io_take_writes();
+        tty_console_write(1, "shell");
+        // 背景控制台的輸出不影響畫面與硬體游標
+        assert_eq!(vga.line(0), "log");
+        assert_eq!(mock_io_take_writes(), []);
+
+        tty_switch(1);
+        assert_eq!(tty_active_console(), 1);
+        assert_eq!(vga.line(0), "shell");
+        assert_eq!(mock_io_take_writes()[4..], [(0x3D4, 0x0F), (0x3D5, 5), (0x3D4, 0x0E), (0x3D5, 0)]);
+        tty_put_str("!");
+        assert_eq!(vga.line(0), "shell");
+
+        tty_switch(0);
+        assert_eq!(vga.line(0), "log!");
+        tty_switch(TTY_COUNT);
+        assert_eq!(tty_active_console(), 0);
+    }
+
+    #[test]
+    fn log_console_binding() {
+        let vga = MockVga::new();
+        tty_set_log_console(2);
+        {
+            let _guard = tty_lock();
+            tty_put_str("kernel");
+        }
+        {
+            let _guard = tty_lock_console(0);
+            tty_put_str("shell");
+        }
+        assert_eq!(vga.line(0), "shell");
+
+        tty_switch(tty_log_console());
+        assert_eq!(vga.line(0), "kernel");
+    }
+
+    #[test]
+    fn console_from_cmdline() {
+        assert_eq!(tty_console_from_cmdline("quiet console=tty2"), Some(1));
+        assert_eq!(tty_console_from_cmdline("console=ttyS0 console=tty6"), Some(5));
+        assert_eq!(tty_console_from_cmdline("console=tty0"), None);
+        assert_eq!(tty_console_from_cmdline("console=tty7"), None);
+    }
+
+    #[test]
+    fn cp437_glyphs() {
+        let vga = MockVga::new();
+        tty_put_str("┌─┐ é 中\u{3000}！");
+
+        assert_eq!(vga.line(0), "┌─┐ é ■ !");
+        assert_eq!(vga.cell(0, 0), GREY | 0xDA);
+        // 沒有對應字形的字元顯示為替代字形
+        assert_eq!(vga.cell(6, 0), GREY | 0xFE);
     }
 }
@@ -1,13 +1,34 @@
-#![no_std]
-#![no_main]
+#![cfg_attr(not(feature = "std"), no_std)]
+#![cfg_attr(not(feature = "std"), no_main)]
+#![cfg_attr(not(feature = "std"), feature(alloc_error_handler))]
+#![cfg_attr(not(feature = "std"), feature(custom_test_frameworks))]
+#![cfg_attr(not(feature = "std"), test_runner(crate::kernel::test::test_runner))]
+#![cfg_attr(not(feature = "std"), reexport_test_harness_main = "test_main")]
+// 主機端測試只編譯與硬體無關的模組，其餘內核介面未被使用
+#![cfg_attr(feature = "std", allow(dead_code, unused_imports))]
 
+extern crate alloc;
+
+#[cfg(not(feature = "std"))]
+mod boot;
+mod drivers;
 mod kernel;
 mod hal;
 mod libs;
+#[cfg(not(feature = "std"))]
+mod mm;
 
+#[cfg(not(feature = "std"))]
 use core::panic::PanicInfo;
 
+#[cfg(all(not(test), not(feature = "std")))]
+#[panic_handler]
+fn panic(info: &PanicInfo) -> ! {
+    kernel::panic::kernel_panic(info)
+}
+
+#[cfg(all(test, not(feature = "std")))]
 #[panic_handler]
-fn panic(_info: &PanicInfo) -> ! {
-    loop {}
-}
\ No newline at end of file
+fn panic(info: &PanicInfo) -> ! {
+    kernel::test::test_panic(info)
+}
@@ -0,0 +1,186 @@
+// src/libs/datetime.rs
+use core::fmt;
+
+const SECS_PER_MINUTE: u64 = 60;
+const SECS_PER_HOUR: u64 = 60 * SECS_PER_MINUTE;
+const SECS_PER_DAY: u64 = 24 * SECS_PER_HOUR;
+
+/// 1970-01-01 相對 0000-03-01 的天數
+const UNIX_EPOCH_DAYS: i64 = 719_468;
+/// 400 年（一個完整的閏年週期）的天數
+const DAYS_PER_ERA: i64 = 146_097;
+
+/// 日期與時間（UTC，公曆）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct DateTime {
+    pub year: u16,
+    /// 1-12
+    pub month: u8,
+    /// 1-31
+    pub day: u8,
+    /// 0-23
+    pub hour: u8,
+    /// 0-59
+    pub minute: u8,
+    /// 0-59
+    pub second: u8,
+}
+
+/// 是否為閏年
+pub fn is_leap_year(year: u16) -> bool {
+    (year.is_multiple_of(4) && !year.is_multiple_of(100)) || year.is_multiple_of(400)
+}
+
+/// 指定月份的天數
+///
+/// # 參數
+/// * `year` - 年份
+/// * `month` - 月份 (1-12)
+/// # 返回
+/// 月份無效時返回 0
+pub fn days_in_month(year: u16, month: u8) -> u8 {
+    match month {
+        1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
+        4 | 6 | 9 | 11 => 30,
+        2 if is_leap_year(year) => 29,
+        2 => 28,
+        _ => 0,
+    }
+}
+
+impl DateTime {
+    /// UNIX 紀元 1970-01-01 00:00:00
+    #[allow(dead_code)]
+    pub const UNIX_EPOCH: DateTime = DateTime::new(1970, 1, 1, 0, 0, 0);
+
+    pub const fn new(year: u16, month: u8, day: u8, hour: u8, minute: u8, second: u8) -> Self {
+        Self { year, month, day, hour, minute, second }
+    }
+
+    /// 各欄位是否在有效範圍內且不早於 UNIX 紀元
+    pub fn is_valid(&self) -> bool {
+        self.year >= 1970
+            && self.month >= 1
+            && self.day >= 1
+            && self.day <= days_in_month(self.year, self.month)
+            && self.hour < 24
+            && self.minute < 60
+            && self.second < 60
+    }
+
+    /// 轉換為 UNIX 時間戳
+    ///
+    /// # 返回
+    /// 自 1970-01-01 00:00:00 UTC 起的秒數，需先確認 `is_valid`
+    pub fn to_unix(self) -> u64 {
+        // 以 3 月為一年的開始，閏日落在年末
+        let (year, month) = if self.month <= 2 {
+            (self.year as i64 - 1, self.month as i64 + 9)
+        } else {
+            (self.year as i64, self.month as i64 - 3)
+        };
+
+        let era = year.div_euclid(400);
+        let year_of_era = year - era * 400;
+        let day_of_year = (153 * month + 2) / 5 + self.day as i64 - 1;
+        let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+        let days = (era * DAYS_PER_ERA + day_of_era - UNIX_EPOCH_DAYS) as u64;
+
+        days * SECS_PER_DAY
+            + self.hour as u64 * SECS_PER_HOUR
+            + self.minute as u64 * SECS_PER_MINUTE
+            + self.second as u64
+    }
+
+    /// 由 UNIX 時間戳建立
+    ///
+    /// # 參數
+    /// * `timestamp` - 自 1970-01-01 00:00:00 UTC 起的秒數
+    pub fn from_unix(timestamp: u64) -> Self {
+        let secs = timestamp % SECS_PER_DAY;
+        let days = (timestamp / SECS_PER_DAY) as i64 + UNIX_EPOCH_DAYS;
+
+        let era = days.div_euclid(DAYS_PER_ERA);
+        let day_of_era = days - era * DAYS_PER_ERA;
+        let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146_096) / 365;
+        let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+        let month = (5 * day_of_year + 2) / 153;
+        let day = day_of_year - (153 * month + 2) / 5 + 1;
+        let month = if month < 10 { month + 3 } else { month - 9 };
+        let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+
+        Self {
+            year: year as u16,
+            month: month as u8,
+            day: day as u8,
+            hour: (secs / SECS_PER_HOUR) as u8,
+            minute: (secs % SECS_PER_HOUR / SECS_PER_MINUTE) as u8,
+            second: (secs % SECS_PER_MINUTE) as u8,
+        }
+    }
+
+    /// 星期幾
+    ///
+    /// # 返回
+    /// 0 為星期日，6 為星期六
+    #[allow(dead_code)]
+    pub fn weekday(&self) -> u8 {
+        // 1970-01-01 是星期四
+        ((self.to_unix() / SECS_PER_DAY + 4) % 7) as u8
+    }
+}
+
+impl fmt::Display for DateTime {
+    /// ISO 8601 格式：`YYYY-MM-DD HH:MM:SS`
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{:04}-{:02}-{:02} {:02}:{:02}:{:02}",
+            self.year, self.month, self.day, self.hour, self.minute, self.second
+        )
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+    use std::format;
+
+    #[test]
+    fn known_timestamps() {
+        assert_eq!(DateTime::UNIX_EPOCH.to_unix(), 0);
+        assert_eq!(DateTime::new(2000, 2, 29, 12, 0, 0).to_unix(), 951_825_600);
+        assert_eq!(DateTime::new(2038, 1, 19, 3, 14, 8).to_unix(), 1 << 31);
+        assert_eq!(DateTime::from_unix(1_700_000_000), DateTime::new(2023, 11, 14, 22, 13, 20));
+    }
+
+    #[test]
+    fn round_trip() {
+        // 每次跨過約 3 天又幾秒，覆蓋各月份與閏年
+        let mut timestamp = 0;
+        while timestamp < 5_000_000_000 {
+            let dt = DateTime::from_unix(timestamp);
+            assert!(dt.is_valid(), "{}", dt);
+            assert_eq!(dt.to_unix(), timestamp);
+            timestamp += 3 * SECS_PER_DAY + 7;
+        }
+    }
+
+    #[test]
+    fn leap_years_and_validity() {
+        assert!(is_leap_year(2000));
+        assert!(!is_leap_year(1900));
+        assert!(is_leap_year(2024));
+        assert!(DateTime::new(2024, 2, 29, 0, 0, 0).is_valid());
+        assert!(!DateTime::new(2023, 2, 29, 0, 0, 0).is_valid());
+        assert!(!DateTime::new(2023, 13, 1, 0, 0, 0).is_valid());
+        assert!(!DateTime::new(1969, 12, 31, 23, 59, 59).is_valid());
+    }
+
+    #[test]
+    fn weekday_and_format() {
+        assert_eq!(DateTime::UNIX_EPOCH.weekday(), 4);
+        assert_eq!(DateTime::new(2026, 10, 18, 0, 0, 0).weekday(), 0);
+        assert_eq!(format!("{}", DateTime::new(2026, 1, 2, 3, 4, 5)), "2026-01-02 03:04:05");
+    }
+}
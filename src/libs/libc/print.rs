@@ -1,6 +1,8 @@
 // src/libs/libc/print.rs
 
 use core::fmt;
+#[cfg(not(feature = "std"))]
+use crate::drivers::serial;
 use crate::kernel::tty::tty;
 
 pub struct Writer;
@@ -8,12 +10,15 @@ pub struct Writer;
 impl fmt::Write for Writer {
     fn write_str(&mut self, s: &str) -> fmt::Result {
         tty::tty_put_str(s);
+        #[cfg(not(feature = "std"))]
+        serial::serial_mirror_str(s);
         Ok(())
     }
 }
 
 pub fn _print(args: fmt::Arguments) {
     use core::fmt::Write;
+    let _guard = tty::tty_lock();
     Writer.write_fmt(args).unwrap();
 }
 
@@ -26,4 +31,20 @@ macro_rules! print {
 macro_rules! println {
     () => ($crate::print!("\n"));
     ($($arg:tt)*) => ($crate::print!("{}\n", format_args!($($arg)*)));
-}
\ No newline at end of file
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use crate::kernel::tty::mock::MockVga;
+
+    #[test]
+    fn formats_to_tty() {
+        let vga = MockVga::new();
+        print!("{:>4}|{:#x}|{:08b}", 7, 255, 5u8);
+        println!("|{}", -1);
+        print!("next");
+
+        assert_eq!(vga.line(0), "   7|0xff|00000101|-1");
+        assert_eq!(vga.line(1), "next");
+    }
+}
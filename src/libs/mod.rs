@@ -1 +1,4 @@
-pub mod libc;
\ No newline at end of file
+pub mod datetime;
+pub mod libc;
+pub mod ring;
+pub mod sync;
\ No newline at end of file
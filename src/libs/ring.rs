@@ -0,0 +1,134 @@
+// src/libs/ring.rs
+
+/// 固定容量的環形緩衝區
+///
+/// 不需要堆，可以放在 `static` 中，供中斷處理程序與讀取端之間傳遞資料
+pub struct RingBuffer<T: Copy, const N: usize> {
+    buffer: [Option<T>; N],
+    head: usize,
+    len: usize,
+}
+
+#[allow(dead_code)]
+impl<T: Copy, const N: usize> RingBuffer<T, N> {
+    pub const fn new() -> Self {
+        Self {
+            buffer: [const { None }; N],
+            head: 0,
+            len: 0,
+        }
+    }
+
+    /// 放入一個元素
+    ///
+    /// # 返回
+    /// 緩衝區已滿時返回 `false`，元素被丟棄
+    pub fn push(&mut self, item: T) -> bool {
+        if self.len == N {
+            return false;
+        }
+
+        self.buffer[(self.head + self.len) % N] = Some(item);
+        self.len += 1;
+        true
+    }
+
+    /// 放入一個元素，緩衝區已滿時覆蓋最舊的元素
+    ///
+    /// # 返回
+    /// 被覆蓋的元素
+    pub fn push_overwrite(&mut self, item: T) -> Option<T> {
+        if self.len < N {
+            self.push(item);
+            return None;
+        }
+
+        let oldest = self.buffer[self.head].replace(item);
+        self.head = (self.head + 1) % N;
+        oldest
+    }
+
+    /// 取出最舊的元素
+    pub fn pop(&mut self) -> Option<T> {
+        if self.len == 0 {
+            return None;
+        }
+
+        let item = self.buffer[self.head].take();
+        self.head = (self.head + 1) % N;
+        self.len -= 1;
+        item
+    }
+
+    /// 取出最新的元素
+    pub fn pop_back(&mut self) -> Option<T> {
+        if self.len == 0 {
+            return None;
+        }
+
+        self.len -= 1;
+        self.buffer[(self.head + self.len) % N].take()
+    }
+
+    /// 按放入順序讀取元素，0 為最舊
+    pub fn get(&self, index: usize) -> Option<T> {
+        if index >= self.len {
+            return None;
+        }
+
+        self.buffer[(self.head + index) % N]
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.len == N
+    }
+
+    pub const fn capacity(&self) -> usize {
+        N
+    }
+
+    pub fn clear(&mut self) {
+        while self.pop().is_some() {}
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fifo_order_and_capacity() {
+        let mut ring = RingBuffer::<u8, 3>::new();
+        assert!(ring.push(1) && ring.push(2) && ring.push(3));
+        assert!(!ring.push(4));
+        assert!(ring.is_full());
+
+        assert_eq!(ring.pop(), Some(1));
+        assert!(ring.push(4));
+        assert_eq!((ring.get(0), ring.get(2), ring.get(3)), (Some(2), Some(4), None));
+        assert_eq!(ring.pop_back(), Some(4));
+        assert_eq!(ring.pop(), Some(2));
+        assert_eq!(ring.pop(), Some(3));
+        assert_eq!(ring.pop(), None);
+    }
+
+    #[test]
+    fn overwrite_drops_oldest() {
+        let mut ring = RingBuffer::<u32, 2>::new();
+        assert_eq!(ring.push_overwrite(1), None);
+        assert_eq!(ring.push_overwrite(2), None);
+        assert_eq!(ring.push_overwrite(3), Some(1));
+        assert_eq!((ring.get(0), ring.get(1)), (Some(2), Some(3)));
+
+        ring.clear();
+        assert!(ring.is_empty());
+    }
+}
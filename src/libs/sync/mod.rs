@@ -0,0 +1,5 @@
+// src/libs/sync/mod.rs
+
+pub mod spinlock;
+
+pub use spinlock::{SpinLock, SpinLockGuard};
@@ -0,0 +1,119 @@
+// src/libs/sync/spinlock.rs
+
+use core::cell::UnsafeCell;
+use core::ops::{Deref, DerefMut};
+use core::sync::atomic::{AtomicBool, Ordering};
+use crate::hal::cpu;
+
+/// 自旋鎖
+///
+/// 持有鎖期間會禁用中斷，避免中斷處理程序在同一個 CPU 上搶同一把鎖造成死鎖
+pub struct SpinLock<T> {
+    locked: AtomicBool,
+    data: UnsafeCell<T>,
+}
+
+unsafe impl<T: Send> Sync for SpinLock<T> {}
+unsafe impl<T: Send> Send for SpinLock<T> {}
+
+/// 自旋鎖守衛，離開作用域時釋放鎖並還原中斷狀態
+pub struct SpinLockGuard<'a, T> {
+    lock: &'a SpinLock<T>,
+    irq_enabled: bool,
+}
+
+#[allow(dead_code)]
+impl<T> SpinLock<T> {
+    pub const fn new(data: T) -> Self {
+        Self {
+            locked: AtomicBool::new(false),
+            data: UnsafeCell::new(data),
+        }
+    }
+
+    /// 取得鎖，必要時自旋等待
+    pub fn lock(&self) -> SpinLockGuard<'_, T> {
+        let irq_enabled = cpu::cpu_save_interrupts();
+
+        while self
+            .locked
+            .compare_exchange_weak(false, true, Ordering::Acquire, Ordering::Relaxed)
+            .is_err()
+        {
+            while self.locked.load(Ordering::Relaxed) {
+                cpu::cpu_pause();
+            }
+        }
+
+        SpinLockGuard { lock: self, irq_enabled }
+    }
+
+    /// 嘗試取得鎖，失敗時立即返回 `None`
+    pub fn try_lock(&self) -> Option<SpinLockGuard<'_, T>> {
+        let irq_enabled = cpu::cpu_save_interrupts();
+
+        if self
+            .locked
+            .compare_exchange(false, true, Ordering::Acquire, Ordering::Relaxed)
+            .is_ok()
+        {
+            Some(SpinLockGuard { lock: self, irq_enabled })
+        } else {
+            cpu::cpu_restore_interrupts(irq_enabled);
+            None
+        }
+    }
+
+    /// 鎖是否被持有
+    pub fn is_locked(&self) -> bool {
+        self.locked.load(Ordering::Relaxed)
+    }
+
+    /// 強制釋放鎖
+    ///
+    /// # Safety
+    /// 只能在持有者已不可能繼續執行時使用（例如 panic 路徑）
+    pub unsafe fn force_unlock(&self) {
+        self.locked.store(false, Ordering::Release);
+    }
+}
+
+impl<T> Deref for SpinLockGuard<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        unsafe { &*self.lock.data.get() }
+    }
+}
+
+impl<T> DerefMut for SpinLockGuard<'_, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        unsafe { &mut *self.lock.data.get() }
+    }
+}
+
+impl<T> Drop for SpinLockGuard<'_, T> {
+    fn drop(&mut self) {
+        self.lock.locked.store(false, Ordering::Release);
+        cpu::cpu_restore_interrupts(self.irq_enabled);
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lock_disables_and_restores_interrupts() {
+        let lock = SpinLock::new(1);
+        cpu::cpu_enable_interrupts();
+        {
+            let mut guard = lock.lock();
+            assert!(!cpu::cpu_interrupts_enabled());
+            assert!(lock.try_lock().is_none());
+            *guard += 1;
+        }
+        assert!(cpu::cpu_interrupts_enabled());
+        assert_eq!(*lock.lock(), 2);
+    }
+}
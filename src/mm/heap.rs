@@ -0,0 +1,324 @@
+// src/mm/heap.rs
+use core::alloc::{GlobalAlloc, Layout};
+use core::fmt;
+use core::mem;
+use core::ptr;
+use x86::bits32::paging::PTFlags;
+use crate::libs::sync::SpinLock;
+use crate::mm::{paging, pmm};
+use crate::println;
+
+/// 內核堆起始虛擬地址
+pub const KERNEL_HEAP_START: usize = 0xD000_0000;
+/// 內核堆初始大小
+pub const KERNEL_HEAP_INITIAL_SIZE: usize = 1024 * 1024;
+/// 內核堆最大大小
+pub const KERNEL_HEAP_MAX_SIZE: usize = 256 * 1024 * 1024;
+
+/// 空閒區塊，直接存放在空閒記憶體的開頭
+struct FreeBlock {
+    size: usize,
+    next: *mut FreeBlock,
+}
+
+/// 最小區塊大小，任何分配都至少佔用這麼多以便釋放後能容納 `FreeBlock`
+const MIN_BLOCK_SIZE: usize = mem::size_of::<FreeBlock>();
+const BLOCK_ALIGN: usize = mem::align_of::<FreeBlock>();
+
+/// 堆統計資訊
+#[derive(Debug, Clone, Copy)]
+pub struct HeapStats {
+    /// 已映射的堆大小
+    pub size: usize,
+    /// 已分配的位元組數
+    pub used: usize,
+    /// 空閒的位元組數
+    pub free: usize,
+    /// 空閒區塊數（碎片程度）
+    pub free_blocks: usize,
+}
+
+impl fmt::Display for HeapStats {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Heap: {} KiB mapped, {} bytes used, {} bytes free in {} blocks",
+            self.size / 1024,
+            self.used,
+            self.free,
+            self.free_blocks
+        )
+    }
+}
+
+/// 以地址排序的首次適配鏈表分配器
+struct Heap {
+    head: *mut FreeBlock,
+    start: usize,
+    end: usize,
+    used: usize,
+}
+
+unsafe impl Send for Heap {}
+
+#[inline]
+fn align_up(addr: usize, align: usize) -> usize {
+    (addr + align - 1) & !(align - 1)
+}
+
+impl Heap {
+    const fn empty() -> Self {
+        Self {
+            head: ptr::null_mut(),
+            start: 0,
+            end: 0,
+            used: 0,
+        }
+    }
+
+    /// 分配請求實際佔用的大小與對齊
+    fn block_layout(layout: Layout) -> (usize, usize) {
+        let size = align_up(core::cmp::max(layout.size(), MIN_BLOCK_SIZE), BLOCK_ALIGN);
+        let align = core::cmp::max(layout.align(), BLOCK_ALIGN);
+        (size, align)
+    }
+
+    /// 將區塊按地址插入空閒鏈表並與相鄰區塊合併
+    unsafe fn insert_free(&mut self, addr: usize, size: usize) {
+        let mut prev: *mut FreeBlock = ptr::null_mut();
+        let mut cur = self.head;
+
+        while !cur.is_null() && (cur as usize) < addr {
+            prev = cur;
+            cur = (*cur).next;
+        }
+
+        let block = addr as *mut FreeBlock;
+        block.write(FreeBlock { size, next: cur });
+
+        // 與後一個區塊合併
+        if !cur.is_null() && addr + size == cur as usize {
+            (*block).size += (*cur).size;
+            (*block).next = (*cur).next;
+        }
+
+        // 與前一個區塊合併
+        if !prev.is_null() && prev as usize + (*prev).size == addr {
+            (*prev).size += (*block).size;
+            (*prev).next = (*block).next;
+        } else if prev.is_null() {
+            self.head = block;
+        } else {
+            (*prev).next = block;
+        }
+    }
+
+    /// 在空閒鏈表中尋找並切割合適的區塊
+    unsafe fn allocate_first_fit(&mut self, size: usize, align: usize) -> Option<usize> {
+        let mut prev: *mut FreeBlock = ptr::null_mut();
+        let mut cur = self.head;
+
+        while !cur.is_null() {
+            let block_start = cur as usize;
+            let block_end = block_start + (*cur).size;
+            let alloc_start = align_up(block_start, align);
+            let front = alloc_start - block_start;
+
+            // 對齊留下的前段太小則無法放回鏈表，改為嘗試下一個對齊位置
+            let alloc_start = if front != 0 && front < MIN_BLOCK_SIZE {
+                align_up(block_start + MIN_BLOCK_SIZE, align)
+            } else {
+                alloc_start
+            };
+            let alloc_end = alloc_start.saturating_add(size);
+            let back = block_end.saturating_sub(alloc_end);
+
+            if alloc_end <= block_end && (back == 0 || back >= MIN_BLOCK_SIZE) {
+                let next = (*cur).next;
+
+                if prev.is_null() {
+                    self.head = next;
+                } else {
+                    (*prev).next = next;
+                }
+
+                if alloc_start > block_start {
+                    self.insert_free(block_start, alloc_start - block_start);
+                }
+                if back > 0 {
+                    self.insert_free(alloc_end, back);
+                }
+
+                return Some(alloc_start);
+            }
+
+            prev = cur;
+            cur = (*cur).next;
+        }
+
+        None
+    }
+
+    /// 映射新的頁面以擴展堆
+    unsafe fn grow(&mut self, min_size: usize) -> bool {
+        let size = align_up(min_size, paging::PAGE_SIZE);
+
+        if self.end + size > KERNEL_HEAP_START + KERNEL_HEAP_MAX_SIZE {
+            return false;
+        }
+
+        let mut mapped = 0;
+        while mapped < size {
+            let frame = match pmm::alloc_frame() {
+                Some(frame) => frame,
+                None => break,
+            };
+            if paging::map(self.end + mapped, frame, PTFlags::RW).is_err() {
+                pmm::free_frame(frame);
+                break;
+            }
+            mapped += paging::PAGE_SIZE;
+        }
+
+        if mapped == 0 {
+            return false;
+        }
+
+        let old_end = self.end;
+        self.end += mapped;
+        self.insert_free(old_end, mapped);
+
+        mapped >= size
+    }
+
+    unsafe fn alloc(&mut self, layout: Layout) -> *mut u8 {
+        let (size, align) = Self::block_layout(layout);
+
+        loop {
+            if let Some(addr) = self.allocate_first_fit(size, align) {
+                self.used += size;
+                return addr as *mut u8;
+            }
+
+            // 最壞情況下需要額外的對齊空間
+            if !self.grow(size + align) {
+                return ptr::null_mut();
+            }
+        }
+    }
+
+    unsafe fn dealloc(&mut self, ptr: *mut u8, layout: Layout) {
+        let (size, _) = Self::block_layout(layout);
+        self.used -= size;
+        self.insert_free(ptr as usize, size);
+    }
+
+    fn stats(&self) -> HeapStats {
+        let mut free = 0;
+        let mut free_blocks = 0;
+        let mut cur = self.head;
+
+        while !cur.is_null() {
+            unsafe {
+                free += (*cur).size;
+                cur = (*cur).next;
+            }
+            free_blocks += 1;
+        }
+
+        HeapStats {
+            size: self.end - self.start,
+            used: self.used,
+            free,
+            free_blocks,
+        }
+    }
+}
+
+/// 由自旋鎖保護的內核堆
+pub struct KernelHeap(SpinLock<Heap>);
+
+unsafe impl GlobalAlloc for KernelHeap {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        self.0.lock().alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        self.0.lock().dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static KERNEL_HEAP: KernelHeap = KernelHeap(SpinLock::new(Heap::empty()));
+
+/// 初始化內核堆
+///
+/// 需在 `pmm_init` 與 `paging_init` 之後呼叫
+///
+/// # 返回
+/// 無法映射初始堆空間時返回 `false`
+pub fn heap_init() -> bool {
+    let mut heap = KERNEL_HEAP.0.lock();
+
+    heap.start = KERNEL_HEAP_START;
+    heap.end = KERNEL_HEAP_START;
+
+    unsafe { heap.grow(KERNEL_HEAP_INITIAL_SIZE) }
+}
+
+/// 獲取堆統計資訊
+pub fn heap_stats() -> HeapStats {
+    KERNEL_HEAP.0.lock().stats()
+}
+
+/// 輸出堆統計資訊
+pub fn heap_print_stats() {
+    println!("{}", heap_stats());
+}
+
+#[alloc_error_handler]
+fn alloc_error_handler(layout: Layout) -> ! {
+    panic!("kernel heap allocation failed: {:?} ({})", layout, heap_stats());
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::boxed::Box;
+    use alloc::vec::Vec;
+    use super::*;
+
+    #[test_case]
+    fn box_allocation() {
+        let a = Box::new(41);
+        let b = Box::new(13);
+        assert_eq!(*a + *b, 54);
+    }
+
+    #[test_case]
+    fn large_vec() {
+        let n = 1000;
+        let v: Vec<usize> = (0..n).collect();
+        assert_eq!(v.iter().sum::<usize>(), (n - 1) * n / 2);
+    }
+
+    #[test_case]
+    fn freed_memory_is_reused() {
+        let used = heap_stats().used;
+        for i in 0..KERNEL_HEAP_INITIAL_SIZE {
+            let x = Box::new(i);
+            assert_eq!(*x, i);
+        }
+        assert_eq!(heap_stats().used, used);
+    }
+
+    #[test_case]
+    fn aligned_allocation() {
+        let layout = Layout::from_size_align(64, 4096).unwrap();
+        unsafe {
+            let p = alloc::alloc::alloc(layout);
+            assert!(!p.is_null());
+            assert_eq!(p as usize % 4096, 0);
+            alloc::alloc::dealloc(p, layout);
+        }
+    }
+}
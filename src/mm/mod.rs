@@ -0,0 +1,44 @@
+// src/mm/mod.rs
+use core::ptr;
+
+pub mod pmm;
+pub mod paging;
+pub mod heap;
+pub mod slab;
+
+/// 內核虛擬地址基址（與 linker.ld、src/arch/x86/memory.h 保持一致）
+pub const KERNEL_VIRT_BASE: usize = 0xC000_0000;
+/// 引導時映射到 `KERNEL_VIRT_BASE` 的物理記憶體大小
+#[allow(dead_code)]
+pub const KERNEL_DIRECT_MAP_SIZE: usize = 8 * 1024 * 1024;
+
+extern "C" {
+    static __kernel_start: u8;
+    static __kernel_end: u8;
+}
+
+/// 內核映像佔用的虛擬地址範圍（由 linker.ld 提供）
+///
+/// # 返回
+/// `(start, end)`，`end` 已對齊到 4 KiB
+pub fn kernel_image_range() -> (usize, usize) {
+    (
+        ptr::addr_of!(__kernel_start) as usize,
+        ptr::addr_of!(__kernel_end) as usize,
+    )
+}
+
+/// 將直接映射區內的物理地址轉換為內核虛擬地址
+///
+/// # 注意
+/// - 只有低於 `KERNEL_DIRECT_MAP_SIZE` 的物理地址可以直接存取
+#[inline]
+pub fn phys_to_virt(phys: usize) -> usize {
+    phys + KERNEL_VIRT_BASE
+}
+
+/// 將內核映像或直接映射區內的虛擬地址轉換為物理地址
+#[inline]
+pub fn virt_to_phys(virt: usize) -> usize {
+    virt - KERNEL_VIRT_BASE
+}
@@ -0,0 +1,382 @@
+// src/mm/paging.rs
+use core::fmt;
+use core::ptr;
+use x86::bits32::paging::{
+    pd_index,
+    pt_index,
+    PAddr,
+    PDEntry,
+    PDFlags,
+    PTEntry,
+    PTFlags,
+    VAddr,
+    BASE_PAGE_SIZE,
+    PAGE_SIZE_ENTRIES,
+};
+use x86::controlregs::Cr0;
+use x86::irq::PageFaultError;
+use crate::hal::cpu;
+use crate::mm::{self, pmm};
+
+/// 頁大小
+pub const PAGE_SIZE: usize = BASE_PAGE_SIZE;
+
+/// 頁目錄中用於遞迴映射的索引
+const RECURSIVE_INDEX: usize = PAGE_SIZE_ENTRIES - 1;
+/// 透過遞迴映射存取頁目錄的虛擬地址
+const PAGE_DIRECTORY_VADDR: usize = 0xFFFF_F000;
+/// 透過遞迴映射存取所有頁表的起始虛擬地址
+const PAGE_TABLES_VADDR: usize = 0xFFC0_0000;
+
+/// 分頁操作錯誤
+#[allow(dead_code)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PagingError {
+    /// 地址未對齊到頁邊界
+    NotAligned,
+    /// 虛擬地址已被映射
+    AlreadyMapped,
+    /// 虛擬地址未被映射
+    NotMapped,
+    /// 虛擬地址落在遞迴映射區域
+    Reserved,
+    /// 虛擬地址由 4 MiB 大頁覆蓋
+    LargePage,
+    /// 無法分配頁表
+    OutOfMemory,
+}
+
+impl fmt::Display for PagingError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PagingError::NotAligned => write!(f, "address not page aligned"),
+            PagingError::AlreadyMapped => write!(f, "page already mapped"),
+            PagingError::NotMapped => write!(f, "page not mapped"),
+            PagingError::Reserved => write!(f, "address in recursive mapping area"),
+            PagingError::LargePage => write!(f, "address covered by a 4 MiB page"),
+            PagingError::OutOfMemory => write!(f, "out of physical memory"),
+        }
+    }
+}
+
+// boot.S 建立的頁目錄，已包含內核高半區映射與遞迴映射
+extern "C" {
+    static mut boot_page_directory: [PDEntry; PAGE_SIZE_ENTRIES];
+    /// boot.S 中內核堆疊下方的保護頁
+    static stack_guard: u8;
+}
+
+/// 頁表，需對齊到頁
+#[repr(C, align(4096))]
+struct PageTable([PTEntry; PAGE_SIZE_ENTRIES]);
+
+/// 拆分內核堆疊所在的 4 MiB 大頁時使用的頁表
+static mut STACK_PAGE_TABLE: PageTable = PageTable([PTEntry(0); PAGE_SIZE_ENTRIES]);
+
+/// 當前頁目錄的可存取指標
+#[inline]
+fn directory() -> *mut PDEntry {
+    PAGE_DIRECTORY_VADDR as *mut PDEntry
+}
+
+/// 指定頁目錄項對應頁表的可存取指標
+#[inline]
+fn table(pd_idx: usize) -> *mut PTEntry {
+    (PAGE_TABLES_VADDR + pd_idx * PAGE_SIZE) as *mut PTEntry
+}
+
+/// 內核頁目錄的物理地址
+#[allow(dead_code)]
+pub fn kernel_page_directory() -> usize {
+    mm::virt_to_phys(ptr::addr_of!(boot_page_directory) as usize)
+}
+
+#[inline]
+fn is_page_aligned(addr: usize) -> bool {
+    addr.is_multiple_of(PAGE_SIZE)
+}
+
+/// 內核堆疊保護頁的虛擬地址
+#[allow(dead_code)]
+pub fn stack_guard_page() -> usize {
+    ptr::addr_of!(stack_guard) as usize
+}
+
+/// 將 4 MiB 大頁拆分為頁表，映射內容不變
+///
+/// # 參數
+/// * `pd_idx` - 大頁的頁目錄索引
+/// * `pt` - 新頁表，需位於內核映像內
+unsafe fn split_large_page(pd_idx: usize, pt: &mut PageTable) {
+    let pde = directory().add(pd_idx);
+    let base = (*pde).address().as_usize();
+
+    for (i, pte) in pt.0.iter_mut().enumerate() {
+        *pte = PTEntry::new(PAddr::from(base + i * PAGE_SIZE), PTFlags::P | PTFlags::RW);
+    }
+
+    let phys = mm::virt_to_phys(pt as *mut PageTable as usize);
+    *pde = PDEntry::new(PAddr::from(phys), PDFlags::P | PDFlags::RW);
+}
+
+/// 接管 boot.S 建立的頁目錄
+///
+/// 分頁在進入 Rust 前已由 boot.S 啟用，這裡移除引導用的恆等映射，
+/// 讓低 3 GiB 留給用戶空間，並啟用 CR0.WP 使內核也遵守唯讀頁。
+/// 內核堆疊所在的大頁被拆分為 4 KiB 頁，並解除堆疊下方保護頁的映射，
+/// 堆疊溢位因此觸發 #PF，進而成為由雙重錯誤任務處理的 #DF
+pub fn paging_init() {
+    let kernel_pd_idx = pd_index(VAddr::from_usize(mm::KERNEL_VIRT_BASE));
+    let guard = stack_guard_page();
+
+    unsafe {
+        let pd = directory();
+        for i in 0..kernel_pd_idx {
+            *pd.add(i) = PDEntry(0);
+        }
+
+        split_large_page(pd_index(VAddr::from_usize(guard)), &mut *ptr::addr_of_mut!(STACK_PAGE_TABLE));
+    }
+
+    cpu::cpu_flush_tlb();
+    unmap(guard).expect("kernel stack guard page not mapped");
+    cpu::cpu_w_cr0(cpu::cpu_r_cr0() | Cr0::CR0_WRITE_PROTECT);
+}
+
+/// 將虛擬頁映射到物理頁框
+///
+/// # 參數
+/// * `virt` - 虛擬地址（需對齊到頁）
+/// * `phys` - 物理地址（需對齊到頁）
+/// * `flags` - 頁表項屬性，`P` 會自動加上
+#[allow(dead_code)]
+pub fn map(virt: usize, phys: usize, flags: PTFlags) -> Result<(), PagingError> {
+    if !is_page_aligned(virt) || !is_page_aligned(phys) {
+        return Err(PagingError::NotAligned);
+    }
+
+    let vaddr = VAddr::from_usize(virt);
+    let pd_idx = pd_index(vaddr);
+
+    if pd_idx == RECURSIVE_INDEX {
+        return Err(PagingError::Reserved);
+    }
+
+    unsafe {
+        let pde = directory().add(pd_idx);
+        let pde_flags = if flags.contains(PTFlags::US) {
+            PDFlags::P | PDFlags::RW | PDFlags::US
+        } else {
+            PDFlags::P | PDFlags::RW
+        };
+
+        if !(*pde).is_present() {
+            let frame = pmm::alloc_frame().ok_or(PagingError::OutOfMemory)?;
+            *pde = PDEntry::new(PAddr::from(frame), pde_flags);
+
+            let pt = table(pd_idx);
+            cpu::cpu_invlpg(pt as usize);
+            ptr::write_bytes(pt, 0, PAGE_SIZE_ENTRIES);
+        } else if (*pde).is_page() {
+            return Err(PagingError::LargePage);
+        } else if !(*pde).flags().contains(pde_flags) {
+            // 已有頁表但權限不足（例如首次加入用戶頁）
+            *pde = PDEntry((*pde).0 | pde_flags.bits());
+        }
+
+        let pte = table(pd_idx).add(pt_index(vaddr));
+        if (*pte).is_present() {
+            return Err(PagingError::AlreadyMapped);
+        }
+
+        *pte = PTEntry::new(PAddr::from(phys), flags | PTFlags::P);
+        cpu::cpu_invlpg(virt);
+    }
+
+    Ok(())
+}
+
+/// 映射一段連續的地址範圍
+///
+/// # 參數
+/// * `virt` - 起始虛擬地址
+/// * `phys` - 起始物理地址
+/// * `size` - 大小（位元組，向上對齊到頁）
+/// * `flags` - 頁表項屬性
+#[allow(dead_code)]
+pub fn map_range(virt: usize, phys: usize, size: usize, flags: PTFlags) -> Result<(), PagingError> {
+    let pages = size.div_ceil(PAGE_SIZE);
+
+    for i in 0..pages {
+        map(virt + i * PAGE_SIZE, phys + i * PAGE_SIZE, flags)?;
+    }
+
+    Ok(())
+}
+
+/// 解除虛擬頁的映射
+///
+/// 頁框本身不會被釋放，由呼叫者決定是否歸還給 `pmm`
+///
+/// # 參數
+/// * `virt` - 虛擬地址（需對齊到頁）
+/// # 返回
+/// 原本映射到的物理地址
+#[allow(dead_code)]
+pub fn unmap(virt: usize) -> Result<usize, PagingError> {
+    if !is_page_aligned(virt) {
+        return Err(PagingError::NotAligned);
+    }
+
+    let vaddr = VAddr::from_usize(virt);
+    let pd_idx = pd_index(vaddr);
+
+    if pd_idx == RECURSIVE_INDEX {
+        return Err(PagingError::Reserved);
+    }
+
+    unsafe {
+        let pde = *directory().add(pd_idx);
+        if !pde.is_present() {
+            return Err(PagingError::NotMapped);
+        }
+        if pde.is_page() {
+            return Err(PagingError::LargePage);
+        }
+
+        let pte = table(pd_idx).add(pt_index(vaddr));
+        if !(*pte).is_present() {
+            return Err(PagingError::NotMapped);
+        }
+
+        let phys = (*pte).address().as_usize();
+        *pte = PTEntry(0);
+        cpu::cpu_invlpg(virt);
+
+        Ok(phys)
+    }
+}
+
+/// 將虛擬地址轉換為物理地址
+///
+/// # 參數
+/// * `virt` - 任意虛擬地址
+/// # 返回
+/// 對應的物理地址，未映射時返回 `None`
+#[allow(dead_code)]
+pub fn translate(virt: usize) -> Option<usize> {
+    let vaddr = VAddr::from_usize(virt);
+    let pd_idx = pd_index(vaddr);
+
+    unsafe {
+        let pde = *directory().add(pd_idx);
+        if !pde.is_present() {
+            return None;
+        }
+
+        if pde.is_page() {
+            return Some(pde.address().as_usize() + vaddr.large_page_offset() as usize);
+        }
+
+        let pte = *table(pd_idx).add(pt_index(vaddr));
+        if !pte.is_present() {
+            return None;
+        }
+
+        Some(pte.address().as_usize() + vaddr.base_page_offset() as usize)
+    }
+}
+
+/// 處理分頁錯誤
+///
+/// 由 #PF 異常處理程序呼叫。目前只修復頁表已允許該存取、但 TLB 仍保留舊條目
+/// 所造成的偽錯誤；未映射或權限不符的存取交還給呼叫者處理
+///
+/// # 參數
+/// * `addr` - 觸發錯誤的虛擬地址 (CR2)
+/// * `error` - CPU 壓入的錯誤碼
+/// # 返回
+/// 錯誤已被修復、可以重新執行指令時返回 `true`
+pub fn handle_page_fault(addr: usize, error: PageFaultError) -> bool {
+    // 保留位被設置代表頁表本身已損壞
+    if error.contains(PageFaultError::RSVD) {
+        return false;
+    }
+
+    let vaddr = VAddr::from_usize(addr);
+    let pd_idx = pd_index(vaddr);
+    let write = error.contains(PageFaultError::WR);
+    let user = error.contains(PageFaultError::US);
+
+    let allowed = unsafe {
+        let pde = *directory().add(pd_idx);
+        if !pde.is_present() {
+            return false;
+        }
+
+        let pde_ok = (!write || pde.is_writeable()) && (!user || pde.is_user_mode_allowed());
+        if pde.is_page() {
+            pde_ok
+        } else {
+            let pte = *table(pd_idx).add(pt_index(vaddr));
+            pde_ok
+                && pte.is_present()
+                && (!write || pte.is_writeable())
+                && (!user || pte.is_user_mode_allowed())
+        }
+    };
+
+    if allowed {
+        cpu::cpu_invlpg(addr);
+    }
+
+    allowed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use x86::bits32::paging::LARGE_PAGE_SIZE;
+
+    /// 測試用的虛擬地址，位於內核映像與堆之間
+    const SCRATCH: usize = 0xCF00_0000;
+
+    #[test_case]
+    fn map_translate_unmap() {
+        let frame = pmm::alloc_frame().expect("out of frames");
+
+        assert_eq!(map(SCRATCH, frame, PTFlags::RW), Ok(()));
+        assert_eq!(translate(SCRATCH + 0x123), Some(frame + 0x123));
+        assert_eq!(map(SCRATCH, frame, PTFlags::RW), Err(PagingError::AlreadyMapped));
+
+        unsafe {
+            (SCRATCH as *mut u32).write_volatile(0xDEAD_BEEF);
+            assert_eq!((SCRATCH as *const u32).read_volatile(), 0xDEAD_BEEF);
+        }
+
+        assert_eq!(unmap(SCRATCH), Ok(frame));
+        assert_eq!(translate(SCRATCH), None);
+        pmm::free_frame(frame);
+    }
+
+    #[test_case]
+    fn rejects_unaligned_and_reserved() {
+        assert_eq!(map(SCRATCH + 1, 0, PTFlags::RW), Err(PagingError::NotAligned));
+        assert_eq!(map(PAGE_DIRECTORY_VADDR, 0, PTFlags::RW), Err(PagingError::Reserved));
+
+        // 保護頁所在的大頁已被拆分，另一個直接映射的大頁仍保持原樣
+        let large = if stack_guard_page() < mm::KERNEL_VIRT_BASE + LARGE_PAGE_SIZE {
+            mm::KERNEL_VIRT_BASE + LARGE_PAGE_SIZE
+        } else {
+            mm::KERNEL_VIRT_BASE
+        };
+        assert_eq!(unmap(large), Err(PagingError::LargePage));
+    }
+
+    #[test_case]
+    fn stack_guard_unmapped() {
+        let guard = stack_guard_page();
+        assert_eq!(translate(guard), None);
+        assert_eq!(translate(guard + PAGE_SIZE), Some(mm::virt_to_phys(guard + PAGE_SIZE)));
+    }
+}
@@ -0,0 +1,342 @@
+// src/mm/pmm.rs
+use core::fmt;
+use core::ptr;
+use crate::boot::multiboot::{BootInfo, MemoryRegionType};
+use crate::println;
+use crate::mm;
+
+/// 物理頁框大小
+pub const FRAME_SIZE: usize = 4096;
+/// 可管理的最大頁框數（覆蓋 32 位元的 4 GiB 物理地址空間）
+pub const MAX_FRAMES: usize = 1 << 20;
+
+const BITMAP_WORDS: usize = MAX_FRAMES / 32;
+/// 低於 1 MiB 的區域保留給 BIOS、VGA 等
+const LOW_MEMORY_END: usize = 0x100000;
+
+/// 物理記憶體分配器統計資訊
+#[derive(Debug, Clone, Copy)]
+pub struct PmmStats {
+    /// 被記憶體映射回報為可用的頁框數
+    pub total_frames: usize,
+    /// 目前已使用的頁框數（含內核與保留區域）
+    pub used_frames: usize,
+    /// 目前空閒的頁框數
+    pub free_frames: usize,
+    /// 內核映像佔用的頁框數
+    pub kernel_frames: usize,
+}
+
+impl fmt::Display for PmmStats {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "PMM: {} KiB total, {} KiB used, {} KiB free, kernel {} KiB",
+            self.total_frames * FRAME_SIZE / 1024,
+            self.used_frames * FRAME_SIZE / 1024,
+            self.free_frames * FRAME_SIZE / 1024,
+            self.kernel_frames * FRAME_SIZE / 1024
+        )
+    }
+}
+
+// 位元為 1 表示頁框空閒，全零初始化讓點陣圖落在 .bss 而非 .data
+struct FrameBitmap {
+    bits: [u32; BITMAP_WORDS],
+    // 最高可用頁框號 + 1
+    frame_limit: usize,
+    total_frames: usize,
+    free_frames: usize,
+    kernel_frames: usize,
+    // 下一次搜尋的起點
+    next_hint: usize,
+}
+
+static mut PMM: FrameBitmap = FrameBitmap {
+    bits: [0; BITMAP_WORDS],
+    frame_limit: 0,
+    total_frames: 0,
+    free_frames: 0,
+    kernel_frames: 0,
+    next_hint: 0,
+};
+
+#[inline]
+fn pmm() -> &'static mut FrameBitmap {
+    unsafe { &mut *ptr::addr_of_mut!(PMM) }
+}
+
+impl FrameBitmap {
+    #[inline]
+    fn is_used(&self, frame: usize) -> bool {
+        self.bits[frame / 32] & (1 << (frame % 32)) == 0
+    }
+
+    #[inline]
+    fn set_used(&mut self, frame: usize) {
+        if !self.is_used(frame) {
+            self.bits[frame / 32] &= !(1 << (frame % 32));
+            self.free_frames -= 1;
+        }
+    }
+
+    #[inline]
+    fn set_free(&mut self, frame: usize) {
+        if self.is_used(frame) {
+            self.bits[frame / 32] |= 1 << (frame % 32);
+            self.free_frames += 1;
+        }
+    }
+
+    fn find_free(&self, from: usize) -> Option<usize> {
+        let mut word = from / 32;
+        let last_word = self.frame_limit.div_ceil(32);
+        // 忽略起點所在字組中低於 `from` 的頁框
+        let mut bits = self.bits.get(word)? & (!0u32 << (from % 32));
+
+        loop {
+            if bits != 0 {
+                let frame = word * 32 + bits.trailing_zeros() as usize;
+                return (frame < self.frame_limit).then_some(frame);
+            }
+            word += 1;
+            if word >= last_word {
+                break;
+            }
+            bits = self.bits[word];
+        }
+
+        None
+    }
+}
+
+/// 頁框號轉換為物理地址
+#[inline]
+fn frame_to_addr(frame: usize) -> usize {
+    frame * FRAME_SIZE
+}
+
+/// 物理地址轉換為頁框號
+#[inline]
+fn addr_to_frame(addr: usize) -> usize {
+    addr / FRAME_SIZE
+}
+
+/// 初始化物理頁框分配器
+///
+/// 根據 Multiboot 記憶體映射釋放可用區域，並保留低端記憶體、內核映像及引導模組
+///
+/// # 參數
+/// * `info` - 解析後的引導資訊，為 `None` 時不會有任何可用頁框
+pub fn pmm_init(info: Option<&BootInfo>) {
+    let pmm = pmm();
+
+    let info = match info {
+        Some(info) => info,
+        None => return,
+    };
+
+    if !info.memory_map().is_empty() {
+        for entry in info.memory_map() {
+            if entry.kind != MemoryRegionType::Available {
+                continue;
+            }
+            let end = core::cmp::min(entry.end(), MAX_FRAMES as u64 * FRAME_SIZE as u64);
+            if entry.base >= end {
+                continue;
+            }
+            // 只使用完整落在區域內的頁框
+            let first = (entry.base as usize).div_ceil(FRAME_SIZE);
+            let last = end as usize / FRAME_SIZE;
+            mark_region_free(pmm, first, last);
+        }
+    } else if let Some(upper) = info.mem_upper_kb() {
+        let last = core::cmp::min(
+            (LOW_MEMORY_END + upper as usize * 1024) / FRAME_SIZE,
+            MAX_FRAMES,
+        );
+        mark_region_free(pmm, addr_to_frame(LOW_MEMORY_END), last);
+    }
+
+    pmm_reserve_range(0, LOW_MEMORY_END);
+
+    let (kernel_start, kernel_end) = mm::kernel_image_range();
+    let before = pmm.free_frames;
+    pmm_reserve_range(mm::virt_to_phys(kernel_start), mm::virt_to_phys(kernel_end));
+    pmm.kernel_frames = before - pmm.free_frames;
+
+    for module in info.modules() {
+        pmm_reserve_range(module.start as usize, module.end as usize);
+    }
+
+    pmm.next_hint = 0;
+}
+
+fn mark_region_free(pmm: &mut FrameBitmap, first: usize, last: usize) {
+    for frame in first..last {
+        if pmm.is_used(frame) {
+            pmm.set_free(frame);
+            pmm.total_frames += 1;
+        }
+    }
+
+    if last > pmm.frame_limit {
+        pmm.frame_limit = last;
+    }
+}
+
+/// 將物理地址範圍標記為已使用
+///
+/// # 參數
+/// * `start` - 起始地址（向下對齊到頁框）
+/// * `end` - 結束地址（不含，向上對齊到頁框）
+pub fn pmm_reserve_range(start: usize, end: usize) {
+    let pmm = pmm();
+    let first = addr_to_frame(start);
+    let last = core::cmp::min(
+        (end.saturating_add(FRAME_SIZE - 1)) / FRAME_SIZE,
+        pmm.frame_limit,
+    );
+
+    for frame in first..last {
+        pmm.set_used(frame);
+    }
+}
+
+/// 分配一個物理頁框
+///
+/// # 返回
+/// 頁框的物理地址，記憶體耗盡時返回 `None`
+#[allow(dead_code)]
+pub fn alloc_frame() -> Option<usize> {
+    let pmm = pmm();
+
+    let frame = pmm.find_free(pmm.next_hint).or_else(|| pmm.find_free(0))?;
+    pmm.set_used(frame);
+    pmm.next_hint = frame + 1;
+
+    Some(frame_to_addr(frame))
+}
+
+/// 釋放一個物理頁框
+///
+/// # 參數
+/// * `addr` - 由 `alloc_frame` 或 `alloc_contiguous` 返回的物理地址
+#[allow(dead_code)]
+pub fn free_frame(addr: usize) {
+    let pmm = pmm();
+    let frame = addr_to_frame(addr);
+
+    if frame >= pmm.frame_limit {
+        return;
+    }
+
+    pmm.set_free(frame);
+    if frame < pmm.next_hint {
+        pmm.next_hint = frame;
+    }
+}
+
+/// 分配連續的物理頁框
+///
+/// # 參數
+/// * `count` - 頁框數量
+/// # 返回
+/// 第一個頁框的物理地址，找不到足夠長的連續區域時返回 `None`
+#[allow(dead_code)]
+pub fn alloc_contiguous(count: usize) -> Option<usize> {
+    alloc_contiguous_aligned(count, 1)
+}
+
+/// 分配連續且對齊的物理頁框
+///
+/// # 參數
+/// * `count` - 頁框數量
+/// * `align` - 第一個頁框號的對齊（頁框數，需為 2 的冪）
+/// # 返回
+/// 第一個頁框的物理地址，找不到符合條件的連續區域時返回 `None`
+#[allow(dead_code)]
+pub fn alloc_contiguous_aligned(count: usize, align: usize) -> Option<usize> {
+    if count == 0 || !align.is_power_of_two() {
+        return None;
+    }
+
+    let pmm = pmm();
+    let mut start = pmm.find_free(0)?.next_multiple_of(align);
+
+    while start + count <= pmm.frame_limit {
+        match (start..start + count).find(|&frame| pmm.is_used(frame)) {
+            Some(used) => start = pmm.find_free(used + 1)?.next_multiple_of(align),
+            None => {
+                for frame in start..start + count {
+                    pmm.set_used(frame);
+                }
+                return Some(frame_to_addr(start));
+            }
+        }
+    }
+
+    None
+}
+
+/// 釋放連續的物理頁框
+///
+/// # 參數
+/// * `addr` - 由 `alloc_contiguous` 返回的物理地址
+/// * `count` - 頁框數量
+#[allow(dead_code)]
+pub fn free_contiguous(addr: usize, count: usize) {
+    for i in 0..count {
+        free_frame(addr + i * FRAME_SIZE);
+    }
+}
+
+/// 獲取分配器統計資訊
+pub fn pmm_stats() -> PmmStats {
+    let pmm = pmm();
+
+    PmmStats {
+        total_frames: pmm.total_frames,
+        used_frames: pmm.total_frames - pmm.free_frames,
+        free_frames: pmm.free_frames,
+        kernel_frames: pmm.kernel_frames,
+    }
+}
+
+/// 輸出分配器統計資訊
+pub fn pmm_print_stats() {
+    println!("{}", pmm_stats());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test_case]
+    fn alloc_and_free_frame() {
+        let before = pmm_stats().free_frames;
+        let frame = alloc_frame().expect("out of frames");
+        assert_eq!(frame % FRAME_SIZE, 0);
+        assert_eq!(pmm_stats().free_frames, before - 1);
+        free_frame(frame);
+        assert_eq!(pmm_stats().free_frames, before);
+    }
+
+    #[test_case]
+    fn contiguous_frames() {
+        let before = pmm_stats().free_frames;
+        let base = alloc_contiguous(4).expect("no contiguous frames");
+        assert_eq!(pmm_stats().free_frames, before - 4);
+        free_contiguous(base, 4);
+        assert_eq!(pmm_stats().free_frames, before);
+    }
+
+    #[test_case]
+    fn aligned_contiguous_frames() {
+        let before = pmm_stats().free_frames;
+        let base = alloc_contiguous_aligned(4, 4).expect("no aligned frames");
+        assert_eq!(base % (4 * FRAME_SIZE), 0);
+        free_contiguous(base, 4);
+        assert_eq!(pmm_stats().free_frames, before);
+    }
+}
@@ -0,0 +1,550 @@
+// src/mm/slab.rs
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use core::fmt;
+use core::marker::PhantomData;
+use core::mem;
+use core::ops::{Deref, DerefMut};
+use core::ptr::{self, NonNull};
+use crate::libs::sync::SpinLock;
+use crate::mm::{self, pmm};
+use crate::mm::paging::PAGE_SIZE;
+use crate::println;
+
+/// 每個 slab 至少容納的物件數
+const SLAB_MIN_OBJECTS: usize = 8;
+/// 單個 slab 的最大頁數
+const SLAB_MAX_PAGES: usize = 8;
+/// 釋放後填入物件的毒化位元組
+#[cfg(feature = "debug")]
+const SLAB_POISON: u8 = 0x6B;
+
+/// 空閒物件，鏈結指標存放在物件開頭
+struct FreeObject {
+    next: *mut FreeObject,
+}
+
+/// Slab 頭部，位於 slab 記憶體開頭，slab 以自身大小對齊以便由物件地址找回
+struct Slab {
+    next: *mut Slab,
+    prev: *mut Slab,
+    free_list: *mut FreeObject,
+    in_use: usize,
+}
+
+/// slab 雙向鏈表
+struct SlabList {
+    head: *mut Slab,
+}
+
+impl SlabList {
+    const fn new() -> Self {
+        Self { head: ptr::null_mut() }
+    }
+
+    unsafe fn push(&mut self, slab: *mut Slab) {
+        (*slab).prev = ptr::null_mut();
+        (*slab).next = self.head;
+        if !self.head.is_null() {
+            (*self.head).prev = slab;
+        }
+        self.head = slab;
+    }
+
+    unsafe fn remove(&mut self, slab: *mut Slab) {
+        if (*slab).prev.is_null() {
+            self.head = (*slab).next;
+        } else {
+            (*(*slab).prev).next = (*slab).next;
+        }
+        if !(*slab).next.is_null() {
+            (*(*slab).next).prev = (*slab).prev;
+        }
+        (*slab).next = ptr::null_mut();
+        (*slab).prev = ptr::null_mut();
+    }
+}
+
+/// 快取的可變狀態
+struct CacheState {
+    partial: SlabList,
+    full: SlabList,
+    empty: SlabList,
+    slabs: usize,
+    active_objects: usize,
+    total_allocs: usize,
+    total_frees: usize,
+}
+
+unsafe impl Send for CacheState {}
+
+/// 物件快取統計資訊
+#[derive(Debug, Clone, Copy)]
+pub struct SlabStats {
+    pub name: &'static str,
+    pub object_size: usize,
+    pub slabs: usize,
+    pub active_objects: usize,
+    pub free_objects: usize,
+    pub total_allocs: usize,
+    pub total_frees: usize,
+}
+
+impl fmt::Display for SlabStats {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{:<16} size {:>5}  slabs {:>4}  active {:>6}  free {:>6}  allocs {} frees {}",
+            self.name,
+            self.object_size,
+            self.slabs,
+            self.active_objects,
+            self.free_objects,
+            self.total_allocs,
+            self.total_frees
+        )
+    }
+}
+
+/// 固定大小物件的快取
+pub struct KmemCache {
+    name: &'static str,
+    object_size: usize,
+    stride: usize,
+    first_offset: usize,
+    slab_size: usize,
+    objects_per_slab: usize,
+    state: SpinLock<CacheState>,
+}
+
+static CACHES: SpinLock<Vec<&'static KmemCache>> = SpinLock::new(Vec::new());
+
+#[inline]
+fn align_up(value: usize, align: usize) -> usize {
+    (value + align - 1) & !(align - 1)
+}
+
+/// 建立物件快取
+///
+/// # 參數
+/// * `name` - 快取名稱，用於統計輸出
+/// * `size` - 物件大小
+/// * `align` - 物件對齊（2 的冪，0 表示使用指標對齊）
+/// # 返回
+/// 物件過大或參數無效時返回 `None`
+#[allow(dead_code)]
+pub fn kmem_cache_create(name: &'static str, size: usize, align: usize) -> Option<&'static KmemCache> {
+    let align = core::cmp::max(align, mem::align_of::<FreeObject>());
+    let max_slab_size = SLAB_MAX_PAGES * PAGE_SIZE;
+    // 先排除過大的參數，避免下面向上對齊時溢位
+    if size == 0 || size > max_slab_size || !align.is_power_of_two() || align > max_slab_size {
+        return None;
+    }
+
+    let stride = align_up(core::cmp::max(size, mem::size_of::<FreeObject>()), align);
+    let first_offset = align_up(mem::size_of::<Slab>(), align);
+    if first_offset >= max_slab_size {
+        return None;
+    }
+
+    // 選擇能容納至少 SLAB_MIN_OBJECTS 個物件的最小 slab
+    let mut slab_size = PAGE_SIZE;
+    while slab_size.saturating_sub(first_offset) / stride < SLAB_MIN_OBJECTS && slab_size < max_slab_size {
+        slab_size *= 2;
+    }
+
+    let objects_per_slab = slab_size.saturating_sub(first_offset) / stride;
+    if objects_per_slab == 0 {
+        return None;
+    }
+
+    let cache: &'static KmemCache = Box::leak(Box::new(KmemCache {
+        name,
+        object_size: size,
+        stride,
+        first_offset,
+        slab_size,
+        objects_per_slab,
+        state: SpinLock::new(CacheState {
+            partial: SlabList::new(),
+            full: SlabList::new(),
+            empty: SlabList::new(),
+            slabs: 0,
+            active_objects: 0,
+            total_allocs: 0,
+            total_frees: 0,
+        }),
+    }));
+
+    CACHES.lock().push(cache);
+
+    Some(cache)
+}
+
+/// 從快取分配一個物件
+///
+/// # 返回
+/// 未初始化的物件記憶體，記憶體耗盡時返回 `None`
+#[allow(dead_code)]
+pub fn kmem_cache_alloc(cache: &KmemCache) -> Option<NonNull<u8>> {
+    cache.alloc()
+}
+
+/// 將物件歸還給快取
+///
+/// # Safety
+/// `obj` 必須是同一個快取的 `kmem_cache_alloc` 所返回且尚未釋放
+#[allow(dead_code)]
+pub unsafe fn kmem_cache_free(cache: &KmemCache, obj: NonNull<u8>) {
+    cache.free(obj)
+}
+
+/// 輸出所有快取的統計資訊
+#[allow(dead_code)]
+pub fn slab_print_stats() {
+    let caches = CACHES.lock();
+    for cache in caches.iter() {
+        println!("{}", cache.stats());
+    }
+}
+
+#[allow(dead_code)]
+impl KmemCache {
+    #[inline]
+    fn slab_pages(&self) -> usize {
+        self.slab_size / PAGE_SIZE
+    }
+
+    /// 從物理頁框分配器取得以自身大小對齊的 slab 記憶體
+    ///
+    /// slab 經由直接映射區存取，超出直接映射區的頁框會被歸還
+    fn alloc_pages(&self) -> *mut u8 {
+        let pages = self.slab_pages();
+        let phys = match pmm::alloc_contiguous_aligned(pages, pages) {
+            Some(phys) => phys,
+            None => return ptr::null_mut(),
+        };
+
+        if phys + self.slab_size > mm::KERNEL_DIRECT_MAP_SIZE {
+            pmm::free_contiguous(phys, pages);
+            return ptr::null_mut();
+        }
+
+        mm::phys_to_virt(phys) as *mut u8
+    }
+
+    /// 將 slab 記憶體歸還給物理頁框分配器
+    fn free_pages(&self, slab: *mut Slab) {
+        pmm::free_contiguous(mm::virt_to_phys(slab as usize), self.slab_pages());
+    }
+
+    /// 由物件地址找回所屬的 slab
+    #[inline]
+    fn slab_of(&self, obj: *mut u8) -> *mut Slab {
+        (obj as usize & !(self.slab_size - 1)) as *mut Slab
+    }
+
+    /// 從物理頁框分配器取得新 slab 並切分為空閒物件
+    unsafe fn grow(&self, state: &mut CacheState) -> bool {
+        let mem = self.alloc_pages();
+        if mem.is_null() {
+            return false;
+        }
+
+        let slab = mem as *mut Slab;
+        let mut free_list: *mut FreeObject = ptr::null_mut();
+
+        // 反向建立鏈表，使分配順序與地址順序一致
+        for i in (0..self.objects_per_slab).rev() {
+            let obj = mem.add(self.first_offset + i * self.stride);
+            self.poison(obj);
+            let free = obj as *mut FreeObject;
+            (*free).next = free_list;
+            free_list = free;
+        }
+
+        slab.write(Slab {
+            next: ptr::null_mut(),
+            prev: ptr::null_mut(),
+            free_list,
+            in_use: 0,
+        });
+
+        state.partial.push(slab);
+        state.slabs += 1;
+
+        true
+    }
+
+    /// 分配一個物件
+    pub fn alloc(&self) -> Option<NonNull<u8>> {
+        let mut state = self.state.lock();
+
+        unsafe {
+            if state.partial.head.is_null() {
+                if !state.empty.head.is_null() {
+                    let slab = state.empty.head;
+                    state.empty.remove(slab);
+                    state.partial.push(slab);
+                } else if !self.grow(&mut state) {
+                    return None;
+                }
+            }
+
+            let slab = state.partial.head;
+            let obj = (*slab).free_list;
+            (*slab).free_list = (*obj).next;
+            (*slab).in_use += 1;
+
+            self.check_poison(obj as *mut u8);
+
+            if (*slab).in_use == self.objects_per_slab {
+                state.partial.remove(slab);
+                state.full.push(slab);
+            }
+
+            state.active_objects += 1;
+            state.total_allocs += 1;
+
+            NonNull::new(obj as *mut u8)
+        }
+    }
+
+    /// 釋放一個物件
+    ///
+    /// # Safety
+    /// `obj` 必須由本快取分配且尚未釋放
+    pub unsafe fn free(&self, obj: NonNull<u8>) {
+        let obj = obj.as_ptr();
+        let mut state = self.state.lock();
+        let slab = self.slab_of(obj);
+
+        self.check_double_free(slab, obj);
+
+        let was_full = (*slab).in_use == self.objects_per_slab;
+
+        self.poison(obj);
+        let free = obj as *mut FreeObject;
+        (*free).next = (*slab).free_list;
+        (*slab).free_list = free;
+        (*slab).in_use -= 1;
+
+        if was_full {
+            state.full.remove(slab);
+            state.partial.push(slab);
+        }
+
+        if (*slab).in_use == 0 {
+            state.partial.remove(slab);
+            // 保留一個空 slab 避免頻繁向頁框分配器申請
+            if state.empty.head.is_null() {
+                state.empty.push(slab);
+            } else {
+                self.free_pages(slab);
+                state.slabs -= 1;
+            }
+        }
+
+        state.active_objects -= 1;
+        state.total_frees += 1;
+    }
+
+    /// 釋放所有空 slab
+    pub fn shrink(&self) {
+        let mut state = self.state.lock();
+
+        unsafe {
+            while !state.empty.head.is_null() {
+                let slab = state.empty.head;
+                state.empty.remove(slab);
+                self.free_pages(slab);
+                state.slabs -= 1;
+            }
+        }
+    }
+
+    /// 快取名稱
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// 物件大小
+    pub fn object_size(&self) -> usize {
+        self.object_size
+    }
+
+    /// 獲取統計資訊
+    pub fn stats(&self) -> SlabStats {
+        let state = self.state.lock();
+
+        SlabStats {
+            name: self.name,
+            object_size: self.object_size,
+            slabs: state.slabs,
+            active_objects: state.active_objects,
+            free_objects: state.slabs * self.objects_per_slab - state.active_objects,
+            total_allocs: state.total_allocs,
+            total_frees: state.total_frees,
+        }
+    }
+
+    /// 以毒化位元組填滿空閒物件（鏈結指標除外）
+    #[cfg(feature = "debug")]
+    unsafe fn poison(&self, obj: *mut u8) {
+        let skip = mem::size_of::<FreeObject>();
+        ptr::write_bytes(obj.add(skip), SLAB_POISON, self.stride - skip);
+    }
+
+    #[cfg(not(feature = "debug"))]
+    #[inline]
+    unsafe fn poison(&self, _obj: *mut u8) {}
+
+    /// 檢查空閒物件在釋放後是否被寫入
+    #[cfg(feature = "debug")]
+    unsafe fn check_poison(&self, obj: *mut u8) {
+        let skip = mem::size_of::<FreeObject>();
+        for i in skip..self.stride {
+            if *obj.add(i) != SLAB_POISON {
+                panic!(
+                    "slab {}: object {:p} modified after free (offset {})",
+                    self.name, obj, i
+                );
+            }
+        }
+    }
+
+    #[cfg(not(feature = "debug"))]
+    #[inline]
+    unsafe fn check_poison(&self, _obj: *mut u8) {}
+
+    /// 檢查物件是否已在空閒鏈表中
+    #[cfg(feature = "debug")]
+    unsafe fn check_double_free(&self, slab: *mut Slab, obj: *mut u8) {
+        let offset = obj as usize - slab as usize;
+        if offset < self.first_offset || (offset - self.first_offset) % self.stride != 0 {
+            panic!("slab {}: invalid free of {:p}", self.name, obj);
+        }
+
+        let mut cur = (*slab).free_list;
+        while !cur.is_null() {
+            if cur as *mut u8 == obj {
+                panic!("slab {}: double free of {:p}", self.name, obj);
+            }
+            cur = (*cur).next;
+        }
+    }
+
+    #[cfg(not(feature = "debug"))]
+    #[inline]
+    unsafe fn check_double_free(&self, _slab: *mut Slab, _obj: *mut u8) {}
+}
+
+/// 型別化的物件快取
+pub struct ObjectCache<T> {
+    cache: &'static KmemCache,
+    _marker: PhantomData<T>,
+}
+
+#[allow(dead_code)]
+impl<T> ObjectCache<T> {
+    /// 建立存放 `T` 的快取
+    pub fn new(name: &'static str) -> Option<Self> {
+        let cache = kmem_cache_create(name, mem::size_of::<T>(), mem::align_of::<T>())?;
+        Some(Self {
+            cache,
+            _marker: PhantomData,
+        })
+    }
+
+    /// 分配物件並以 `value` 初始化
+    pub fn alloc(&self, value: T) -> Option<SlabBox<T>> {
+        let obj = self.cache.alloc()?.cast::<T>();
+        unsafe {
+            obj.as_ptr().write(value);
+        }
+        Some(SlabBox {
+            obj,
+            cache: self.cache,
+        })
+    }
+
+    /// 底層的快取
+    pub fn cache(&self) -> &'static KmemCache {
+        self.cache
+    }
+}
+
+/// 由 `ObjectCache` 分配的物件，離開作用域時自動歸還
+pub struct SlabBox<T> {
+    obj: NonNull<T>,
+    cache: &'static KmemCache,
+}
+
+impl<T> Deref for SlabBox<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        unsafe { self.obj.as_ref() }
+    }
+}
+
+impl<T> DerefMut for SlabBox<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        unsafe { self.obj.as_mut() }
+    }
+}
+
+impl<T> Drop for SlabBox<T> {
+    fn drop(&mut self) {
+        unsafe {
+            ptr::drop_in_place(self.obj.as_ptr());
+            self.cache.free(self.obj.cast());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test_case]
+    fn cache_alloc_free() {
+        let cache = kmem_cache_create("test-64", 64, 16).expect("cache creation failed");
+        let a = cache.alloc().expect("alloc failed");
+        let b = cache.alloc().expect("alloc failed");
+
+        assert_ne!(a, b);
+        assert_eq!(a.as_ptr() as usize % 16, 0);
+        assert_eq!(cache.stats().active_objects, 2);
+
+        unsafe {
+            cache.free(a);
+            cache.free(b);
+        }
+        assert_eq!(cache.stats().active_objects, 0);
+    }
+
+    #[test_case]
+    fn oversized_alignment_rejected() {
+        assert!(kmem_cache_create("test-align", 64, SLAB_MAX_PAGES * PAGE_SIZE).is_none());
+        assert!(kmem_cache_create("test-align", 64, 1 << 31).is_none());
+        assert!(kmem_cache_create("test-size", usize::MAX, 16).is_none());
+
+        // 對齊大於一頁時 slab 頭部佔用整個對齊單位
+        let cache = kmem_cache_create("test-8k", 64, 8192).expect("cache creation failed");
+        let obj = cache.alloc().expect("alloc failed");
+        assert_eq!(obj.as_ptr() as usize % 8192, 0);
+        unsafe { cache.free(obj) };
+    }
+
+    #[test_case]
+    fn object_cache() {
+        let cache = ObjectCache::<[u32; 4]>::new("test-obj").expect("cache creation failed");
+        {
+            let mut obj = cache.alloc([1, 2, 3, 4]).expect("alloc failed");
+            obj[3] = 5;
+            assert_eq!(obj.iter().sum::<u32>(), 11);
+        }
+        assert_eq!(cache.cache().stats().active_objects, 0);
+    }
+}